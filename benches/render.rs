@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_todos::bench;
+
+fn draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw");
+    group.sample_size(10);
+    for n in bench::SIZES {
+        let mut home = bench::home(n).unwrap();
+        let mut terminal = bench::terminal().unwrap();
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter(|| bench::draw(&mut home, &mut terminal).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(bench::ROUND.len() as u64));
+    for n in bench::SIZES {
+        let mut home = bench::home(n).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter(|| bench::update(&mut home).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, draw, update);
+criterion_main!(benches);
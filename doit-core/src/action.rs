@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumVariantNames};
+
+use uuid::Uuid;
+
+use crate::todo::TodoItem;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize, EnumVariantNames)]
+pub enum Action {
+    Tick,
+    Render,
+    Resize(u16, u16),
+    Suspend,
+    Resume,
+    Quit,
+    Refresh,
+    Error(String),
+    Help,
+    SaveAndClose,
+    /// Writes the todos to disk without quitting.
+    Save,
+
+    // Home Actions
+    AddTodo,
+    InsertTodo(String),
+    CompleteTodo(usize),
+    DeleteTodo(usize),
+    ReopenTodo(usize),
+    ToggleCompletedPanel,
+    ToggleDetailPane,
+    /// Goes back to before the last this many changes to the list.
+    Undo(usize),
+    EnterHistoryMode,
+    EnterStatsMode,
+    ToggleHabits,
+    EnterMatrixMode,
+    EnterThemesMode,
+    /// Moves a todo into a quadrant of the Eisenhower matrix, numbered in reading order from 0.
+    MoveToQuadrant(usize, usize),
+    /// Lists only the todos in the next context, or all of them after the last one.
+    CycleContext,
+    /// Lists only the todos assigned to the next person, or all of them after the last one.
+    CycleAssignee,
+    /// Reorders the list by urgency score, most urgent first.
+    SortByUrgency,
+    /// In the table view, sorts the list by its next column, ascending and then descending.
+    CycleSort,
+    /// Writes the listed todos to a file and opens it in the editor, to change many at once.
+    BulkEdit,
+    /// Attaches a file path or link to the todo at this index.
+    Attach(usize, String),
+    /// Adds a step to the checklist of the todo at this index.
+    AddChecklistStep(usize, String),
+    /// Ticks a checklist step, by todo index and step index, or unticks it.
+    ToggleChecklistStep(usize, usize),
+    /// Inserts a copy of the yanked todo after the selection, under a new title if given.
+    PasteTodo(Option<String>),
+    /// Makes the todo at this index a project, moving its subtasks into it.
+    PromoteToProject(usize),
+    /// Lists only the todos in this project.
+    OpenProject(String),
+    /// Adds subtasks with these titles under the todo at this index.
+    SplitTodo(usize, Vec<String>),
+    /// Leaves a comment on the todo at this index.
+    Comment(usize, String),
+    /// Opens the notes of the todo at this index in the editor.
+    EditNotes(usize),
+    /// Suspends the TUI while the user edits a file in their editor.
+    OpenEditor(std::path::PathBuf),
+    /// The editor was closed after saving the file it was opened on.
+    EditorClosed(std::path::PathBuf),
+    /// What to write to the terminal to draw the image at this path in the area at this x, y,
+    /// width and height, decoded away from the UI.
+    ImageDecoded(std::path::PathBuf, (u16, u16, u16, u16), String),
+    ToggleStar(usize),
+    TodosChanged(Vec<TodoItem>),
+    UpsertTodos(Vec<TodoItem>),
+    /// Todos deleted elsewhere, removed by id.
+    RemoveTodos(Vec<Uuid>),
+    /// Issues fetched from Jira, merged into the todos already made from them.
+    MergeJiraIssues(Vec<TodoItem>),
+    SyncGoogleTasks,
+    ReloadConfig,
+    /// Doubles or halves how often the list ticks or the screen is redrawn, while running.
+    FasterTicks,
+    SlowerTicks,
+    FasterFrames,
+    SlowerFrames,
+    /// Continuations of a half-typed key sequence and their actions; empty to hide them.
+    KeyHints(Vec<(String, String)>),
+    Toast(String),
+    /// An action a plugin or script made up, by name and with whatever it carries. Components
+    /// act on the names they know in `update` and pass over the rest.
+    Custom(String, serde_json::Value),
+    /// Hands the keys to the next pane, or back to the list after the last one.
+    FocusNext,
+    CompleteJiraIssue(String),
+    ReopenJiraIssue(String),
+    EnterCommandMode,
+    EnterBrowseMode,
+    EnterHelpMode,
+    EnterTagsMode,
+    EnterProjectsMode,
+    RenameTag(String, String),
+    DeleteTag(String),
+    ExitCurrentMode,
+    BrowseListUp,
+    BrowseListDown,
+    BrowseListJump(usize),
+}
@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use color_eyre::eyre::Result;
+
+use crate::todo;
+
+/// How many earlier versions of the data file are kept alongside it.
+const KEPT: usize = 3;
+
+/// Where the `n`th most recent earlier version of `data_file` is kept, counting from 1.
+pub fn path(data_file: &Path, n: usize) -> PathBuf {
+    let mut path = todo::state_path(data_file).into_os_string();
+    path.push(format!(".bak{n}"));
+    PathBuf::from(path)
+}
+
+/// Keeps the data file as it is now as the most recent backup, before it is replaced, shifting
+/// the older ones along and dropping the oldest.
+pub fn rotate(data_file: &Path) -> Result<()> {
+    if !data_file.exists() {
+        return Ok(());
+    }
+    for n in (1..KEPT).rev() {
+        let from = path(data_file, n);
+        if from.exists() {
+            fs::rename(&from, path(data_file, n + 1))?;
+        }
+    }
+    // A second name for the same file costs nothing, where the filesystem allows it
+    let latest = path(data_file, 1);
+    if fs::hard_link(data_file, &latest).is_err() {
+        fs::copy(data_file, &latest)?;
+    }
+    Ok(())
+}
+
+/// The most recent backup that reads back intact.
+pub fn latest(data_file: &Path) -> Option<PathBuf> {
+    (1..=KEPT)
+        .map(|n| path(data_file, n))
+        .find(|backup| backup.exists() && todo::load_document(backup).is_ok())
+}
+
+/// When the data file cannot be read, offers to put the most recent intact backup in its place,
+/// keeping the damaged file next to it. Without a backup, or when the offer is turned down,
+/// everything is left as it is.
+pub fn recover(input: &mut impl BufRead, output: &mut impl Write, data_file: &Path) -> Result<()> {
+    let Err(e) = todo::load_document(data_file) else {
+        return Ok(());
+    };
+    let Some(backup) = latest(data_file) else {
+        return Ok(());
+    };
+    let saved = fs::metadata(&backup)?.modified()?;
+    writeln!(output, "{} could not be read: {e}", data_file.display())?;
+    write!(
+        output,
+        "Restore the backup saved {}? [y/N]: ",
+        DateTime::<Local>::from(saved).format("%Y-%m-%d %H:%M")
+    )?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    if !line.trim().eq_ignore_ascii_case("y") {
+        return Ok(());
+    }
+    let mut damaged = data_file.as_os_str().to_owned();
+    damaged.push(".damaged");
+    fs::rename(data_file, &damaged)?;
+    fs::copy(&backup, data_file)?;
+    writeln!(
+        output,
+        "Restored, the damaged file is kept as {}",
+        Path::new(&damaged).display()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{crdt::Document, todo::TodoItem};
+
+    #[test]
+    fn test_restore_damaged_data_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-backup-{}", Uuid::new_v4()));
+        let data_file = dir.join("home.json");
+        let first = Document::from_todos(&[TodoItem::parse("pay rent")]);
+        todo::write_document(&data_file, &first)?;
+        todo::write_document(&data_file, &Document::default())?;
+        assert_eq!(todo::load_document(&path(&data_file, 1))?, first);
+
+        fs::write(&data_file, "{ \"items\": {")?;
+        let mut output = Vec::new();
+        recover(&mut "n\n".as_bytes(), &mut output, &data_file)?;
+        assert!(todo::load_document(&data_file).is_err());
+        recover(&mut "y\n".as_bytes(), &mut output, &data_file)?;
+        assert_eq!(todo::load_document(&data_file)?, first);
+        assert!(dir.join("home.json.damaged").exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
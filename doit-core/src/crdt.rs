@@ -0,0 +1,405 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{
+    intern::Name,
+    todo::{ChecklistItem, Comment, Event, TodoItem},
+};
+
+/// A last-writer-wins register: the most recent write wins, with ties broken by value so that
+/// every replica picks the same one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lww<T> {
+    pub value: T,
+    pub at: DateTime<Utc>,
+}
+
+impl<T: Ord + Clone> Lww<T> {
+    /// A register holding `value`, as written at `at`.
+    pub fn new(value: T, at: DateTime<Utc>) -> Self {
+        Self { value, at }
+    }
+
+    /// Writes a new value, leaving the timestamp alone if nothing actually changed.
+    pub fn set(&mut self, value: T, at: DateTime<Utc>) {
+        if value != self.value {
+            self.value = value;
+            self.at = at;
+        }
+    }
+
+    /// Keeps whichever of the two values was written last.
+    pub fn join(&mut self, other: &Self) {
+        if (other.at, &other.value) > (self.at, &self.value) {
+            *self = other.clone();
+        }
+    }
+}
+
+/// The replicated state of one todo. Deleted todos stay behind as tombstones so that the
+/// deletion wins over replicas that still have the item.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub created: DateTime<Utc>,
+    pub title: Lww<String>,
+    pub due: Lww<Option<NaiveDate>>,
+    #[serde(default)]
+    pub scheduled: Lww<Option<NaiveDate>>,
+    pub done: Lww<bool>,
+    #[serde(default)]
+    pub completed: Lww<Option<DateTime<Utc>>>,
+    #[serde(default)]
+    pub project: Lww<Option<Name>>,
+    #[serde(default)]
+    pub context: Lww<Option<Name>>,
+    #[serde(default)]
+    pub assignee: Lww<Option<Name>>,
+    #[serde(default)]
+    pub flagged: Lww<bool>,
+    #[serde(default)]
+    pub starred: Lww<bool>,
+    #[serde(default)]
+    pub tags: Lww<Vec<Name>>,
+    #[serde(default)]
+    pub estimate: Lww<Option<u32>>,
+    #[serde(default)]
+    pub source: Lww<Option<String>>,
+    #[serde(default)]
+    pub url: Lww<Option<String>>,
+    #[serde(default)]
+    pub notes: Lww<Option<String>>,
+    #[serde(default)]
+    pub attachments: Lww<Vec<String>>,
+    #[serde(default)]
+    pub checklist: Lww<Vec<ChecklistItem>>,
+    #[serde(default)]
+    pub parent: Lww<Option<Uuid>>,
+    /// Events only ever get added, so copies merge by taking all of them.
+    #[serde(default)]
+    pub history: BTreeSet<Event>,
+    /// Comments are only ever added too.
+    #[serde(default)]
+    pub comments: BTreeSet<Comment>,
+    pub deleted: Lww<bool>,
+    /// Fields written by newer versions, kept as they are so that saving doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Entry {
+    fn new(todo: &TodoItem, created: DateTime<Utc>, at: DateTime<Utc>) -> Self {
+        Self {
+            created,
+            title: Lww::new(todo.title.clone(), at),
+            due: Lww::new(todo.due, at),
+            scheduled: Lww::new(todo.scheduled, at),
+            done: Lww::new(todo.done, at),
+            completed: Lww::new(todo.completed, at),
+            project: Lww::new(todo.project.clone(), at),
+            context: Lww::new(todo.context.clone(), at),
+            assignee: Lww::new(todo.assignee.clone(), at),
+            flagged: Lww::new(todo.flagged, at),
+            starred: Lww::new(todo.starred, at),
+            tags: Lww::new(todo.tags.clone(), at),
+            estimate: Lww::new(todo.estimate, at),
+            source: Lww::new(todo.source.clone(), at),
+            url: Lww::new(todo.url.clone(), at),
+            notes: Lww::new(todo.notes.clone(), at),
+            attachments: Lww::new(todo.attachments.clone(), at),
+            checklist: Lww::new(todo.checklist.clone(), at),
+            parent: Lww::new(todo.parent, at),
+            history: todo.history.iter().copied().collect(),
+            comments: todo.comments.iter().cloned().collect(),
+            deleted: Lww::new(false, at),
+            extra: Map::new(),
+        }
+    }
+
+    fn join(&mut self, other: &Self) {
+        self.created = self.created.min(other.created);
+        self.title.join(&other.title);
+        self.due.join(&other.due);
+        self.scheduled.join(&other.scheduled);
+        self.done.join(&other.done);
+        self.completed.join(&other.completed);
+        self.project.join(&other.project);
+        self.context.join(&other.context);
+        self.assignee.join(&other.assignee);
+        self.flagged.join(&other.flagged);
+        self.starred.join(&other.starred);
+        self.tags.join(&other.tags);
+        self.estimate.join(&other.estimate);
+        self.source.join(&other.source);
+        self.url.join(&other.url);
+        self.notes.join(&other.notes);
+        self.attachments.join(&other.attachments);
+        self.checklist.join(&other.checklist);
+        self.parent.join(&other.parent);
+        self.history.extend(&other.history);
+        self.comments.extend(other.comments.iter().cloned());
+        self.deleted.join(&other.deleted);
+        // Fields this version can't merge keep the greater of the two as written, so that every
+        // replica keeps the same one
+        for (name, theirs) in &other.extra {
+            match self.extra.get_mut(name) {
+                Some(ours) => {
+                    if theirs.to_string().cmp(&ours.to_string()).is_gt() {
+                        *ours = theirs.clone();
+                    }
+                }
+                None => {
+                    self.extra.insert(name.clone(), theirs.clone());
+                }
+            }
+        }
+    }
+
+    fn updated(&self) -> DateTime<Utc> {
+        let stamps = [
+            self.due.at,
+            self.scheduled.at,
+            self.done.at,
+            self.completed.at,
+            self.project.at,
+            self.context.at,
+            self.assignee.at,
+            self.flagged.at,
+            self.starred.at,
+            self.tags.at,
+            self.estimate.at,
+            self.source.at,
+            self.url.at,
+            self.notes.at,
+            self.attachments.at,
+            self.checklist.at,
+            self.parent.at,
+        ];
+        stamps.into_iter().fold(self.title.at, DateTime::max)
+    }
+
+    /// The todo this entry holds, under `id`.
+    fn into_todo(self, id: Uuid) -> TodoItem {
+        TodoItem {
+            id,
+            updated: self.updated(),
+            title: self.title.value,
+            due: self.due.value,
+            scheduled: self.scheduled.value,
+            done: self.done.value,
+            completed: self.completed.value,
+            project: self.project.value,
+            context: self.context.value,
+            assignee: self.assignee.value,
+            flagged: self.flagged.value,
+            starred: self.starred.value,
+            tags: self.tags.value,
+            estimate: self.estimate.value,
+            source: self.source.value,
+            url: self.url.value,
+            notes: self.notes.value,
+            attachments: self.attachments.value,
+            checklist: self.checklist.value,
+            parent: self.parent.value,
+            history: self.history.into_iter().collect(),
+            comments: self.comments.into_iter().collect(),
+            origin: None,
+        }
+    }
+}
+
+/// A state-based CRDT holding every todo ever seen, keyed by id.
+///
+/// Joining two documents is commutative, associative and idempotent, so copies edited
+/// concurrently on different devices converge no matter the order they are merged in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Document {
+    pub items: BTreeMap<Uuid, Entry>,
+}
+
+impl Document {
+    /// Converts a plain list, stamping every field with the item's last update.
+    pub fn from_todos(todos: &[TodoItem]) -> Self {
+        let items = todos
+            .iter()
+            .enumerate()
+            .map(|(i, todo)| {
+                // Keep the original ordering even when every item shares the same timestamp
+                let created = todo.updated + Duration::nanoseconds(i as i64);
+                (todo.id, Entry::new(todo, created, todo.updated))
+            })
+            .collect();
+        Self { items }
+    }
+
+    /// The live todos in creation order.
+    pub fn todos(&self) -> Vec<TodoItem> {
+        let mut entries: Vec<(&Uuid, &Entry)> = self
+            .items
+            .iter()
+            .filter(|(_, entry)| !entry.deleted.value)
+            .collect();
+        entries.sort_by_key(|(id, entry)| (entry.created, **id));
+        entries
+            .into_iter()
+            .map(|(id, entry)| entry.clone().into_todo(*id))
+            .collect()
+    }
+
+    /// Like `todos`, but moves each todo out of the document rather than copying it.
+    pub fn into_todos(self) -> Vec<TodoItem> {
+        let mut entries: Vec<(Uuid, Entry)> = self
+            .items
+            .into_iter()
+            .filter(|(_, entry)| !entry.deleted.value)
+            .collect();
+        entries.sort_by_key(|(id, entry)| (entry.created, *id));
+        entries
+            .into_iter()
+            .map(|(id, entry)| entry.into_todo(id))
+            .collect()
+    }
+
+    /// Records `todos` as the new local state, stamping only the fields that changed.
+    ///
+    /// `todos` must have been derived from this document, as anything missing from it is
+    /// treated as deleted.
+    pub fn record(&mut self, todos: &[TodoItem], at: DateTime<Utc>) {
+        let present: HashSet<Uuid> = todos.iter().map(|todo| todo.id).collect();
+        for todo in todos {
+            match self.items.get_mut(&todo.id) {
+                Some(entry) => {
+                    entry.title.set(todo.title.clone(), at);
+                    entry.due.set(todo.due, at);
+                    entry.scheduled.set(todo.scheduled, at);
+                    entry.done.set(todo.done, at);
+                    entry.completed.set(todo.completed, at);
+                    entry.project.set(todo.project.clone(), at);
+                    entry.context.set(todo.context.clone(), at);
+                    entry.assignee.set(todo.assignee.clone(), at);
+                    entry.flagged.set(todo.flagged, at);
+                    entry.starred.set(todo.starred, at);
+                    entry.tags.set(todo.tags.clone(), at);
+                    entry.estimate.set(todo.estimate, at);
+                    entry.source.set(todo.source.clone(), at);
+                    entry.url.set(todo.url.clone(), at);
+                    entry.notes.set(todo.notes.clone(), at);
+                    entry.attachments.set(todo.attachments.clone(), at);
+                    entry.checklist.set(todo.checklist.clone(), at);
+                    entry.parent.set(todo.parent, at);
+                    entry.history.extend(&todo.history);
+                    entry.comments.extend(todo.comments.iter().cloned());
+                    entry.deleted.set(false, at);
+                }
+                None => {
+                    self.items
+                        .insert(todo.id, Entry::new(todo, todo.updated.min(at), at));
+                }
+            }
+        }
+        for (id, entry) in self.items.iter_mut() {
+            if !present.contains(id) {
+                entry.deleted.set(true, at);
+            }
+        }
+    }
+
+    /// Merges in another replica, so that both end up the same whichever order they are joined.
+    pub fn join(&mut self, other: &Self) {
+        for (id, theirs) in &other.items {
+            self.items
+                .entry(*id)
+                .and_modify(|ours| ours.join(theirs))
+                .or_insert_with(|| theirs.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::todo::EventKind;
+
+    #[test]
+    fn test_concurrent_edits_converge() {
+        let base = Document::from_todos(&[TodoItem::new("a".into()), TodoItem::new("b".into())]);
+        let now = Utc::now();
+
+        let mut ours = base.clone();
+        let mut todos = ours.todos();
+        todos[0].done = true;
+        todos.push(TodoItem::new("c".into()));
+        ours.record(&todos, now);
+
+        let mut theirs = base.clone();
+        let mut todos = theirs.todos();
+        todos[1].title = "b renamed".into();
+        todos.remove(0);
+        theirs.record(&todos, now + Duration::seconds(1));
+
+        let mut left = ours.clone();
+        left.join(&theirs);
+        let mut right = theirs.clone();
+        right.join(&ours);
+        assert_eq!(left, right);
+
+        let titles: Vec<(String, bool)> = left
+            .todos()
+            .into_iter()
+            .map(|todo| (todo.title, todo.done))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![("b renamed".to_string(), false), ("c".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn test_history_keeps_events_from_both_sides() {
+        let base = Document::from_todos(&[TodoItem::new("a".into())]);
+        let now = Utc::now();
+
+        let mut ours = base.clone();
+        let mut todos = ours.todos();
+        todos[0].log(EventKind::Completed);
+        ours.record(&todos, now);
+
+        let mut theirs = base.clone();
+        let mut todos = theirs.todos();
+        todos[0].log(EventKind::Edited);
+        theirs.record(&todos, now);
+
+        ours.join(&theirs);
+        let kinds: Vec<EventKind> = ours.todos()[0]
+            .history
+            .iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(kinds, vec![EventKind::Completed, EventKind::Edited]);
+    }
+
+    #[test]
+    fn test_keep_fields_from_newer_versions() -> serde_json::Result<()> {
+        let mut document = Document::from_todos(&[TodoItem::new("a".into())]);
+        let mut value = serde_json::to_value(&document)?;
+        let entry = value["items"].as_object_mut().unwrap().values_mut().next();
+        entry.unwrap()["priority"] = serde_json::json!({ "value": 2, "at": Utc::now() });
+        let mut newer: Document = serde_json::from_value(value.clone())?;
+        assert_eq!(serde_json::to_value(&newer)?, value);
+
+        let mut todos = newer.todos();
+        todos[0].done = true;
+        newer.record(&todos, Utc::now());
+        document.join(&newer);
+        let entry = document.items.values().next().unwrap();
+        assert!(entry.extra.contains_key("priority"));
+        Ok(())
+    }
+}
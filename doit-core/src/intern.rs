@@ -0,0 +1,160 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    ops::Deref,
+    sync::{Arc, Mutex, Weak},
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The names in use, which new ones with the same text share. Only weak references are kept, so
+/// a name is freed once no todo has it any more.
+#[derive(Default)]
+struct Names {
+    shared: HashMap<Box<str>, Weak<str>>,
+    /// How many names were left after the freed ones were last cleared out.
+    live: usize,
+}
+
+lazy_static! {
+    static ref NAMES: Mutex<Names> = Mutex::new(Names::default());
+}
+
+/// A tag, project, context or assignee name. The same few names turn up on thousands of todos,
+/// so each is stored once and shared rather than copied onto every todo.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    /// The shared name with this text, made on first use.
+    pub fn new(name: &str) -> Self {
+        let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(shared) = names.shared.get(name).and_then(Weak::upgrade) {
+            return Self(shared);
+        }
+        // Clear out the freed names whenever the table has doubled since, which keeps it to
+        // about twice the names in use for a constant cost per name made
+        if names.shared.len() >= (names.live * 2).max(64) {
+            names.shared.retain(|_, shared| shared.strong_count() > 0);
+            names.live = names.shared.len();
+        }
+        let shared: Arc<str> = name.into();
+        names.shared.insert(name.into(), Arc::downgrade(&shared));
+        Self(shared)
+    }
+
+    /// The text of the name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<&String> for Name {
+    fn from(name: &String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(String::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_names_are_shared() {
+        let tags: Vec<Name> = serde_json::from_str(r#"["work", "home", "work"]"#).unwrap();
+        assert_eq!(tags, vec!["work", "home", "work"]);
+        assert!(Arc::ptr_eq(&tags[0].0, &tags[2].0));
+        assert!(Arc::ptr_eq(&tags[0].0, &Name::from("work").0));
+        assert_eq!(
+            serde_json::to_string(&tags).unwrap(),
+            r#"["work","home","work"]"#
+        );
+    }
+
+    #[test]
+    fn test_unused_names_are_freed() {
+        let name = Name::new("only used here");
+        let shared = Arc::downgrade(&name.0);
+        drop(name);
+        assert!(shared.upgrade().is_none());
+        assert_eq!(Name::new("only used here"), "only used here");
+    }
+}
@@ -0,0 +1,33 @@
+//! The todo list engine behind DoIt, for embedding it in other programs or building another
+//! front end on it.
+//!
+//! - [`todo`](mod@todo) holds the data model, [`TodoItem`](todo::TodoItem), and reads and
+//!   writes data files.
+//! - [`crdt`] holds the replicated [`Document`](crdt::Document) that data files store, which
+//!   merges edits made in several places without losing any.
+//! - [`migrate`] brings data files written by older versions up to date and checks them for
+//!   damage, and [`backup`] keeps earlier versions of them next to them.
+//! - [`lock`] keeps two sessions from editing the same data file at once.
+//! - [`action`] names the messages a front end passes between its parts. Only the names live
+//!   here: carrying them out is up to the front end, as DoIt's own does in its list view.
+//!
+//! ```no_run
+//! use doit_core::todo::{self, TodoItem};
+//!
+//! # fn main() -> color_eyre::Result<()> {
+//! let path = todo::data_file();
+//! todo::update(&path, |todos| todos.push(TodoItem::parse("pay rent due:tomorrow")))?;
+//! for todo in todo::load(&path)? {
+//!     println!("{todo}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod action;
+pub mod backup;
+pub mod crdt;
+pub mod intern;
+pub mod lock;
+pub mod migrate;
+pub mod todo;
@@ -0,0 +1,59 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+
+use crate::todo;
+
+/// Held by the one session editing a data file, until it is dropped or the process exits.
+#[derive(Debug)]
+pub struct Lock {
+    _file: File,
+}
+
+/// The file locked on behalf of `data_file`, alongside it, as saving replaces the data file itself.
+pub fn path(data_file: &Path) -> PathBuf {
+    let mut path = todo::state_path(data_file).into_os_string();
+    path.push(".lock");
+    PathBuf::from(path)
+}
+
+/// Takes the advisory lock on `data_file`, or returns `None` when another session holds it.
+pub fn try_acquire(data_file: &Path) -> Result<Option<Lock>> {
+    let path = path(data_file);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(Lock { _file: file })),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_second_session_is_refused() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-lock-{}", Uuid::new_v4()));
+        let data_file = dir.join("home.json");
+        let first = try_acquire(&data_file)?;
+        assert!(first.is_some());
+        assert!(try_acquire(&data_file)?.is_none());
+        drop(first);
+        assert!(try_acquire(&data_file)?.is_some());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
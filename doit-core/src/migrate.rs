@@ -0,0 +1,206 @@
+use std::{collections::BTreeMap, io::Read};
+
+use color_eyre::eyre::{bail, eyre, Result};
+use serde::Deserialize;
+use serde_json::{json, value::RawValue, Value};
+use uuid::Uuid;
+
+use crate::{
+    crdt::{Document, Entry},
+    todo::TodoItem,
+};
+
+/// The version written to every saved document. Bump it, and add a step to `MIGRATIONS`, whenever
+/// a change to the format needs more than a new defaulted field. New fields on an entry don't: a
+/// build that doesn't know them keeps them as they are.
+pub const CURRENT: u64 = 3;
+
+/// Each step upgrades a document from the version at its index to the next one.
+const MIGRATIONS: [fn(Value) -> Result<Value>; CURRENT as usize] =
+    [from_list, add_version, drop_checksum];
+
+/// Version 0 is the plain list of todos written before the CRDT document.
+fn from_list(value: Value) -> Result<Value> {
+    let mut todos: Vec<TodoItem> = serde_json::from_value(value)?;
+    // Plain lists predate ids, so derive them from the content to make every load of the same
+    // file agree on them
+    for (i, todo) in todos.iter_mut().enumerate() {
+        if todo.id.is_nil() {
+            let name = format!("{i}:{}", todo.title);
+            todo.id = Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes());
+        }
+    }
+    Ok(serde_json::to_value(Document::from_todos(&todos))?)
+}
+
+/// Version 1 is the document as first written, without a version field.
+fn add_version(mut value: Value) -> Result<Value> {
+    value["version"] = json!(2);
+    Ok(value)
+}
+
+/// Version 2 checksummed its entries as this build would write them again, not as they were
+/// saved, which no longer matches once the format changes; the checksum is dropped.
+fn drop_checksum(mut value: Value) -> Result<Value> {
+    if let Value::Object(fields) = &mut value {
+        fields.remove("checksum");
+    }
+    value["version"] = json!(3);
+    Ok(value)
+}
+
+fn version_of(value: &Value) -> Result<u64> {
+    match value {
+        Value::Array(_) => Ok(0),
+        Value::Object(fields) => match fields.get("version") {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| eyre!("Data file version {version} is not a number")),
+            None => Ok(1),
+        },
+        _ => bail!("Data file holds neither a document nor a list of todos"),
+    }
+}
+
+/// Upgrades a saved document of any earlier version and reads it. Unknown fields of the document
+/// itself are an error rather than being dropped on the next save.
+pub fn decode(mut value: Value) -> Result<Document> {
+    let version = version_of(&value)?;
+    if version > CURRENT {
+        bail!("Data file is version {version}, but this build only reads up to {CURRENT}; upgrade to open it");
+    }
+    for migrate in &MIGRATIONS[version as usize..] {
+        value = migrate(value)?;
+    }
+    let mut checksum = None;
+    if let Value::Object(fields) = &mut value {
+        fields.remove("version");
+        checksum = fields.remove("checksum").and_then(|sum| sum.as_u64());
+    }
+    // Written compactly, the entries read back come out as the text they were saved as
+    verify(&value["items"].to_string(), checksum)?;
+    serde_json::from_value(value).map_err(|e| eyre!("Data file could not be read: {e}"))
+}
+
+/// A CRC-32 of the entries as saved.
+fn checksum(items: &str) -> u64 {
+    u64::from(crc32fast::hash(items.as_bytes()))
+}
+
+/// Checks the saved text of the entries against the checksum saved with them. Documents saved
+/// before checksums were added have none to check.
+fn verify(items: &str, saved: Option<u64>) -> Result<()> {
+    match saved {
+        Some(saved) if saved != checksum(items) => {
+            bail!("Data file is damaged: its checksum does not match its contents")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A document of this version or version 1, which only lacks the version field and the checksum.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Current {
+    version: Option<u64>,
+    checksum: Option<u64>,
+    items: Box<RawValue>,
+}
+
+/// Reads a saved document straight from `reader` into place. Only documents that need upgrading,
+/// or have problems to report, are read again from `reopen` whole into a JSON tree for `decode`.
+pub fn read<R: Read>(reader: R, reopen: impl FnOnce() -> Result<R>) -> Result<Document> {
+    if let Ok(Current {
+        version,
+        checksum,
+        items,
+    }) = serde_json::from_reader(reader)
+    {
+        if version.is_none_or(|version| version == CURRENT) {
+            verify(items.get(), checksum)?;
+            let items: BTreeMap<Uuid, Entry> = serde_json::from_str(items.get())
+                .map_err(|e| eyre!("Data file could not be read: {e}"))?;
+            return Ok(Document { items });
+        }
+    }
+    decode(serde_json::from_reader(reopen()?)?)
+}
+
+/// Serializes a document tagged with the current version and a checksum of its entries as they
+/// will be written.
+pub fn encode(document: &Document) -> Result<Value> {
+    let items = serde_json::to_value(&document.items)?;
+    Ok(json!({
+        "version": CURRENT,
+        "checksum": checksum(&items.to_string()),
+        "items": items,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_upgrade_legacy_list() -> Result<()> {
+        let document = decode(json!([{ "title": "a" }, { "title": "b", "done": true }]))?;
+        let titles: Vec<(String, bool)> = document
+            .todos()
+            .into_iter()
+            .map(|todo| (todo.title, todo.done))
+            .collect();
+        assert_eq!(titles, vec![("a".into(), false), ("b".into(), true)]);
+        assert_eq!(decode(encode(&document)?)?, document);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_in_place_or_upgrade() -> Result<()> {
+        let document = decode(json!([{ "title": "a" }]))?;
+        let current = serde_json::to_vec(&encode(&document)?)?;
+        assert_eq!(read(current.as_slice(), || unreachable!())?, document);
+        let legacy = br#"[{ "title": "a" }]"#.as_slice();
+        assert_eq!(read(legacy, || Ok(legacy))?, document);
+        let newer = br#"{ "version": 4, "items": {} }"#.as_slice();
+        assert!(read(newer, || Ok(newer)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_refuse_unknown_fields_and_newer_versions() {
+        assert!(decode(json!({ "version": 3, "items": {}, "labels": [] })).is_err());
+        assert!(decode(json!({ "version": 4, "items": {} })).is_err());
+    }
+
+    #[test]
+    fn test_detect_damage() -> Result<()> {
+        let document = decode(json!([{ "title": "pay rent" }]))?;
+        let saved = serde_json::to_string(&encode(&document)?)?;
+        let damaged = saved.replace("pay rent", "pay renT");
+        assert!(read(damaged.as_bytes(), || Ok(damaged.as_bytes())).is_err());
+        assert!(decode(serde_json::from_str(&damaged)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum_the_saved_text() -> Result<()> {
+        let document = decode(json!([{ "title": "pay rent" }]))?;
+        let saved = serde_json::to_string(&encode(&document)?)?;
+        assert_eq!(read(saved.as_bytes(), || unreachable!())?, document);
+
+        // Version 2 summed entries as written again, so a timestamp saved as `+00:00` rather
+        // than `Z` never matched; its checksum is no longer held against it
+        let Value::Object(mut items) = serde_json::to_value(&document.items)? else {
+            unreachable!()
+        };
+        let mut item = items.values_mut().next().unwrap().take();
+        let created = item["created"].as_str().unwrap().replace('Z', "+00:00");
+        item["created"] = json!(created);
+        *items.values_mut().next().unwrap() = item;
+        let older = serde_json::to_vec(&json!({ "version": 2, "checksum": 1, "items": items }))?;
+        assert_eq!(read(older.as_slice(), || Ok(older.as_slice()))?, document);
+        Ok(())
+    }
+}
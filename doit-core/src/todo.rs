@@ -0,0 +1,618 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{backup, crdt::Document, intern::Name, migrate};
+
+/// Something that happened to a todo, kept in its history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EventKind {
+    Created,
+    Edited,
+    Completed,
+    Reopened,
+    /// The due date was moved.
+    Rescheduled,
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventKind::Created => write!(f, "created"),
+            EventKind::Edited => write!(f, "edited"),
+            EventKind::Completed => write!(f, "completed"),
+            EventKind::Reopened => write!(f, "reopened"),
+            EventKind::Rescheduled => write!(f, "rescheduled"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Event {
+    pub at: DateTime<Utc>,
+    pub kind: EventKind,
+}
+
+/// One step of a todo's checklist, lighter than a todo of its own.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub done: bool,
+}
+
+/// A progress note on a todo, kept apart from its description.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Comment {
+    pub at: DateTime<Utc>,
+    pub text: String,
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    /// Stable identity used to match items across copies of the data file.
+    #[serde(default)]
+    pub id: Uuid,
+    /// When any field was last changed, used to pick a side when two copies disagree.
+    #[serde(default)]
+    pub updated: DateTime<Utc>,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// The day to start on, as opposed to `due`, the day to be done by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub done: bool,
+    /// When the todo was completed from the list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<Name>,
+    /// Where or with what the todo can be done, GTD style, such as `phone` or `errands`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Name>,
+    /// Who owns the todo on a shared list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<Name>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flagged: bool,
+    /// Pinned above the rest of the list.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub starred: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Name>,
+    /// Effort in whatever unit the user estimates in, points or hours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// The Markdown file, relative to the vault, that the todo was read from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// A web page for the todo, such as the issue it was fetched from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// File paths and links attached to the todo, opened with the platform opener.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// Steps ticked off in the detail pane.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    /// The todo this is a subtask of, listed just above it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
+    /// What happened to the todo, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Event>,
+    /// Comments left on the todo, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+    /// The shared file the todo was read from and is saved back to, by name, or none for the
+    /// data file.
+    #[serde(skip)]
+    pub origin: Option<String>,
+}
+
+impl TodoItem {
+    /// An open todo with just a title, created now.
+    pub fn new(title: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            updated: Utc::now(),
+            title,
+            ..Self::default()
+        }
+    }
+
+    /// A fresh, open copy to start a similar todo from, with its checklist unticked and without
+    /// the original's history or comments.
+    pub fn duplicate(&self) -> Self {
+        let mut copy = Self {
+            id: Uuid::new_v4(),
+            updated: Utc::now(),
+            done: false,
+            completed: None,
+            history: Vec::new(),
+            comments: Vec::new(),
+            ..self.clone()
+        };
+        for step in &mut copy.checklist {
+            step.done = false;
+        }
+        copy.log(EventKind::Created);
+        copy
+    }
+
+    /// Records that the item was just edited.
+    pub fn touch(&mut self) {
+        self.updated = Utc::now();
+    }
+
+    /// Adds an event to the history, dated now.
+    pub fn log(&mut self, kind: EventKind) {
+        self.history.push(Event {
+            at: Utc::now(),
+            kind,
+        });
+    }
+
+    /// Builds a todo from quick-add input, pulling `due:<date>`, `est:<number>`,
+    /// `attach:<path or link>`, `assign:<name>`, `+project`, `@context` and `#tag` tokens out of
+    /// the title.
+    ///
+    /// The date may be `today`, `tomorrow` or an ISO `YYYY-MM-DD` date. Tokens that fail to
+    /// parse are left in the title untouched.
+    pub fn parse(input: &str) -> Self {
+        let mut todo = Self::new(String::new());
+        let mut words = Vec::new();
+        for word in input.split_whitespace() {
+            if let Some(date) = word.strip_prefix("due:").and_then(parse_date) {
+                todo.due = Some(date);
+            } else if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+                todo.project = Some(project.into());
+            } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+                todo.context = Some(context.into());
+            } else if let Some(estimate) = word.strip_prefix("est:").and_then(|e| e.parse().ok()) {
+                todo.estimate = Some(estimate);
+            } else if let Some(assignee) = word.strip_prefix("assign:").filter(|a| !a.is_empty()) {
+                todo.assignee = Some(assignee.into());
+            } else if let Some(attachment) = word.strip_prefix("attach:").filter(|a| !a.is_empty())
+            {
+                todo.attachments.push(attachment.to_string());
+            } else if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+                todo.tags.push(tag.into());
+            } else {
+                words.push(word);
+            }
+        }
+        todo.title = words.join(" ");
+        todo
+    }
+
+    /// The quick-add input that `parse` would turn back into this todo's title, due date,
+    /// estimate, attachments, assignee, project, context and tags.
+    pub fn quick_add(&self) -> String {
+        let mut words = vec![self.title.clone()];
+        words.extend(self.due.map(|due| format!("due:{due}")));
+        words.extend(self.estimate.map(|estimate| format!("est:{estimate}")));
+        words.extend(self.attachments.iter().map(|a| format!("attach:{a}")));
+        words.extend(self.assignee.iter().map(|a| format!("assign:{a}")));
+        words.extend(self.project.iter().map(|project| format!("+{project}")));
+        words.extend(self.context.iter().map(|context| format!("@{context}")));
+        words.extend(self.tags.iter().map(|tag| format!("#{tag}")));
+        words.join(" ")
+    }
+
+    /// Whether the todo is open and due on or before `date`.
+    pub fn is_due_on(&self, date: NaiveDate) -> bool {
+        !self.done && self.due.is_some_and(|due| due <= date)
+    }
+}
+
+impl std::fmt::Display for TodoItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)?;
+        if let Some(project) = &self.project {
+            write!(f, " +{project}")?;
+        }
+        if let Some(context) = &self.context {
+            write!(f, " @{context}")?;
+        }
+        for tag in &self.tags {
+            write!(f, " #{tag}")?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, " [{source}]")?;
+        }
+        if let Some(due) = self.due {
+            write!(f, " (due {due})")?;
+        }
+        if let Some(estimate) = self.estimate {
+            write!(f, " ~{estimate}")?;
+        }
+        if self.flagged {
+            write!(f, " ⚑")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    let today = Local::now().date_naive();
+    match raw {
+        "today" => Some(today),
+        "tomorrow" => today.checked_add_days(Days::new(1)),
+        _ => NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok(),
+    }
+}
+
+static DATA_FILE: OnceLock<PathBuf> = OnceLock::new();
+static GLOBAL_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Where todos are saved: the path given to `set_data_file`, or else `global_file`.
+pub fn data_file() -> PathBuf {
+    DATA_FILE.get().cloned().unwrap_or_else(global_file)
+}
+
+/// Where todos are saved outside of projects: the path given to `set_global_file`, or
+/// `./.data/home.json`.
+pub fn global_file() -> PathBuf {
+    GLOBAL_FILE
+        .get()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("./.data/home.json"))
+}
+
+/// The file a codebase can keep its own todos in, used instead of the data file when the TUI is
+/// started inside it.
+pub const LOCAL_FILE: &str = ".doit.json";
+
+/// The nearest `LOCAL_FILE` in `dir` or above it, looking no higher than the repository root.
+pub fn find_local(dir: &Path) -> Option<PathBuf> {
+    for dir in dir.ancestors() {
+        let path = dir.join(LOCAL_FILE);
+        if path.is_file() {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            return None;
+        }
+    }
+    None
+}
+
+/// Saves todos somewhere other than the default from now on. Only the first call has any effect.
+pub fn set_data_file(path: PathBuf) {
+    let _ = DATA_FILE.set(path);
+}
+
+/// Moves the global data file, and the data file too unless `set_data_file` says otherwise. Only
+/// the first call has any effect.
+pub fn set_global_file(path: PathBuf) {
+    let _ = GLOBAL_FILE.set(path);
+}
+
+/// What the files kept along with `data_file` are named after, such as its backups, its lock and
+/// its sync state. That is the data file itself, apart from a project's `LOCAL_FILE`: those of
+/// a project are kept with the global data file instead, out of the repository and apart for
+/// every user.
+pub fn state_path(data_file: &Path) -> PathBuf {
+    if data_file.file_name() != Some(LOCAL_FILE.as_ref()) {
+        return data_file.to_path_buf();
+    }
+    let project = data_file
+        .parent()
+        .and_then(|dir| dir.canonicalize().ok())
+        .unwrap_or_default();
+    let name = project
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let hash = Uuid::new_v5(&Uuid::NAMESPACE_URL, project.as_os_str().as_encoded_bytes());
+    let dir = format!("{name}-{}", &hash.simple().to_string()[..8]);
+    global_file()
+        .with_file_name("projects")
+        .join(dir)
+        .join("home.json")
+}
+
+/// `state_path` of the data file.
+pub fn state_file() -> PathBuf {
+    state_path(&data_file())
+}
+
+/// How a data file is compressed on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compression {
+    None,
+    Zstd,
+    Gzip,
+}
+
+/// The first bytes of a zstd frame and of a gzip stream.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+impl Compression {
+    /// What a data file is written with, going by its extension.
+    fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("zst") => Self::Zstd,
+            Some("gz") => Self::Gzip,
+            _ => Self::None,
+        }
+    }
+
+    /// What a data file starting with `head` was written with, whatever it is called now.
+    fn of_head(head: &[u8]) -> Self {
+        if head.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else if head.starts_with(&GZIP_MAGIC) {
+            Self::Gzip
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Reads `file` from the start, decompressing it if it was compressed.
+fn decompress(file: File) -> Result<Box<dyn Read>> {
+    let mut file = BufReader::new(file);
+    Ok(match Compression::of_head(file.fill_buf()?) {
+        Compression::None => Box::new(file),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?)),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::GzDecoder::new(file))),
+    })
+}
+
+/// Reads the saved document, treating a missing file as an empty one.
+pub fn load_document(path: &Path) -> Result<Document> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(Document::default()),
+    };
+    migrate::read(decompress(file)?, || decompress(File::open(path)?))
+}
+
+/// Reads the saved todos, treating a missing file as an empty list.
+pub fn load(path: &Path) -> Result<Vec<TodoItem>> {
+    Ok(load_document(path)?.into_todos())
+}
+
+/// Joins `document` with whatever is on disk now and writes the result back, returning it.
+pub fn save_document(path: &Path, document: &Document) -> Result<Document> {
+    let mut merged = document.clone();
+    merged.join(&load_document(path)?);
+    write_document(path, &merged)?;
+    Ok(merged)
+}
+
+/// The todos read from `origin`, as named in `TodoItem::origin`.
+pub fn from_origin(todos: &[TodoItem], origin: Option<&str>) -> Vec<TodoItem> {
+    todos
+        .iter()
+        .filter(|todo| todo.origin.as_deref() == origin)
+        .cloned()
+        .collect()
+}
+
+/// When the data file was last written, if it exists.
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Overwrites the data file without merging, for when entries are dropped on purpose.
+pub fn write_document(path: &Path, document: &Document) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Written aside and then moved into place, so the file is never left half written and the
+    // version it replaces can be kept as a backup
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let file: File = File::create(&tmp)?;
+    let mut writer: BufWriter<File> = BufWriter::new(file);
+    let value = migrate::encode(document)?;
+    match Compression::of_path(path) {
+        Compression::None => serde_json::to_writer(&mut writer, &value)?,
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(&mut writer, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            serde_json::to_writer(&mut encoder, &value)?;
+            encoder.finish()?;
+        }
+        Compression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(&mut writer, flate2::Compression::default());
+            serde_json::to_writer(&mut encoder, &value)?;
+            encoder.finish()?;
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    backup::rotate(path)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Applies `edit` to the saved todos and writes them back, for one-shot commands.
+pub fn update(path: &Path, edit: impl FnOnce(&mut Vec<TodoItem>)) -> Result<()> {
+    let mut document = load_document(path)?;
+    let mut todos = document.todos();
+    edit(&mut todos);
+    document.record(&todos, Utc::now());
+    save_document(path, &document)?;
+    Ok(())
+}
+
+/// Replaces todos with matching ids and appends the rest.
+pub fn upsert(todos: &mut Vec<TodoItem>, items: Vec<TodoItem>) {
+    for item in items {
+        match todos.iter_mut().find(|todo| todo.id == item.id) {
+            Some(todo) => *todo = item,
+            None => todos.push(item),
+        }
+    }
+}
+
+/// Formats the parting reminder printed once the TUI has closed.
+pub fn summary(todos: &[TodoItem], due_today_only: bool) -> String {
+    let today = Local::now().date_naive();
+    let remaining: Vec<(usize, &TodoItem)> = todos
+        .iter()
+        .enumerate()
+        .filter(|(_, todo)| !todo.done && (!due_today_only || todo.is_due_on(today)))
+        .collect();
+
+    let scope = if due_today_only { " due today" } else { "" };
+    match remaining.len() {
+        0 => format!("No todos{scope}.\n"),
+        n => {
+            let mut out = format!("{n} todo{}{scope}:\n", if n == 1 { "" } else { "s" });
+            for (i, todo) in remaining {
+                out.push_str(&format!("  {i}: {todo}\n"));
+            }
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_project_state_kept_out_of_the_project() {
+        let global = PathBuf::from("/home/me/.data/home.json");
+        assert_eq!(state_path(&global), global);
+        let project = std::env::temp_dir().join(LOCAL_FILE);
+        let state = state_path(&project);
+        assert!(state.starts_with(global_file().with_file_name("projects")));
+        assert_eq!(state.file_name(), Some("home.json".as_ref()));
+        assert_eq!(state_path(&project), state);
+        assert_ne!(
+            state_path(&PathBuf::from("/elsewhere").join(LOCAL_FILE)),
+            state
+        );
+    }
+
+    #[test]
+    fn test_parse_due_date() {
+        let todo = TodoItem::parse("pay rent due:2024-02-01 online");
+        assert_eq!(todo.title, "pay rent online");
+        assert_eq!(todo.due, NaiveDate::from_ymd_opt(2024, 2, 1));
+    }
+
+    #[test]
+    fn test_parse_project_and_tags() {
+        let todo = TodoItem::parse("+home fix sink #diy + #urgent");
+        assert_eq!(todo.title, "fix sink +");
+        assert_eq!(todo.project.as_deref(), Some("home"));
+        assert_eq!(todo.tags, vec!["diy", "urgent"]);
+    }
+
+    #[test]
+    fn test_parse_context() {
+        let todo = TodoItem::parse("call plumber @phone +home @");
+        assert_eq!(todo.title, "call plumber @");
+        assert_eq!(todo.context.as_deref(), Some("phone"));
+        assert_eq!(todo.to_string(), "call plumber @ +home @phone");
+    }
+
+    #[test]
+    fn test_parse_assignee() {
+        let todo = TodoItem::parse("review PR assign:sam assign:");
+        assert_eq!(todo.title, "review PR assign:");
+        assert_eq!(todo.assignee.as_deref(), Some("sam"));
+        assert_eq!(TodoItem::parse(&todo.quick_add()).assignee, todo.assignee);
+    }
+
+    #[test]
+    fn test_parse_estimate() {
+        let todo = TodoItem::parse("write report est:3 est:lots");
+        assert_eq!(todo.title, "write report est:lots");
+        assert_eq!(todo.estimate, Some(3));
+    }
+
+    #[test]
+    fn test_parse_attachments() {
+        let todo =
+            TodoItem::parse("read spec attach:~/spec.pdf attach:https://example.com attach:");
+        assert_eq!(todo.title, "read spec attach:");
+        assert_eq!(todo.attachments, vec!["~/spec.pdf", "https://example.com"]);
+        assert_eq!(
+            TodoItem::parse(&todo.quick_add()).attachments,
+            todo.attachments
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_due_date_kept_in_title() {
+        let todo = TodoItem::parse("due:someday");
+        assert_eq!(todo.title, "due:someday");
+        assert_eq!(todo.due, None);
+    }
+
+    #[test]
+    fn test_summary_due_today_only() {
+        let mut overdue = TodoItem::new("overdue".into());
+        overdue.due = NaiveDate::from_ymd_opt(2000, 1, 1);
+        let todos = vec![TodoItem::new("someday".into()), overdue];
+        assert_eq!(
+            summary(&todos, true),
+            "1 todo due today:\n  1: overdue (due 2000-01-01)\n"
+        );
+        assert_eq!(summary(&[], false), "No todos.\n");
+    }
+
+    #[test]
+    fn test_find_local_stops_at_repo_root() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("doit-local-{}", Uuid::new_v4()));
+        let repo = root.join("repo");
+        let nested = repo.join("src/deep");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::create_dir(repo.join(".git"))?;
+        std::fs::write(root.join(LOCAL_FILE), "")?;
+        assert_eq!(find_local(&nested), None);
+
+        std::fs::write(repo.join(LOCAL_FILE), "")?;
+        assert_eq!(find_local(&nested), Some(repo.join(LOCAL_FILE)));
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_data_files() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-compressed-{}", Uuid::new_v4()));
+        let document = Document::from_todos(&[TodoItem::parse("water plants #home")]);
+        for (name, magic) in [
+            ("todos.json.zst", &ZSTD_MAGIC[..]),
+            ("todos.json.gz", &GZIP_MAGIC),
+        ] {
+            let path = dir.join(name);
+            write_document(&path, &document)?;
+            assert!(std::fs::read(&path)?.starts_with(magic));
+            assert_eq!(load_document(&path)?, document);
+            // Read by their contents, so renaming one does not lose the todos
+            let renamed = dir.join("todos.json");
+            std::fs::rename(&path, &renamed)?;
+            assert_eq!(load_document(&renamed)?, document);
+        }
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,30 +0,0 @@
-use std::{fmt, string::ToString};
-
-use serde::{
-    de::{self, Deserializer, Visitor},
-    Deserialize, Serialize,
-};
-use strum::Display;
-
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
-pub enum Action {
-    Tick,
-    Render,
-    Resize(u16, u16),
-    Suspend,
-    Resume,
-    Quit,
-    Refresh,
-    Error(String),
-    Help,
-    SaveAndClose,
-
-    // Home Actions
-    AddTodo,
-    EnterCommandMode,
-    EnterBrowseMode,
-    EnterHelpMode,
-    ExitCurrentMode,
-    BrowseListUp,
-    BrowseListDown,
-}
@@ -0,0 +1,218 @@
+use std::fmt::Write;
+
+use chrono::{Days, NaiveDate};
+
+use crate::todo::TodoItem;
+
+/// Lines per printed page, leaving room for the footer.
+pub const PAGE_LINES: usize = 60;
+/// Characters per line, which fits an A4 page in 10pt Courier.
+pub const WIDTH: usize = 80;
+
+/// Breaks `text` into lines of at most `width` characters after `prefix`, lining continuation
+/// lines up under the first word. Words longer than a line are left whole.
+fn wrap(prefix: &str, text: &str, width: usize) -> Vec<String> {
+    let indent = " ".repeat(prefix.chars().count());
+    let mut lines = Vec::new();
+    let mut line = prefix.to_string();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        if !empty && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::replace(&mut line, indent.clone()));
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    lines.push(line);
+    lines
+}
+
+/// Lays out open todos due before today or within the next `days` days, grouped under their
+/// dates.
+pub fn lines(todos: &[TodoItem], today: NaiveDate, days: u64, width: usize) -> Vec<String> {
+    let end = today.checked_add_days(Days::new(days)).unwrap_or(today);
+    let mut due: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|todo| !todo.done && todo.due.is_some_and(|due| due <= end))
+        .collect();
+    due.sort_by_key(|todo| todo.due);
+
+    let mut out = vec![format!("Agenda for {today} to {end}"), String::new()];
+    if due.is_empty() {
+        out.push("Nothing due.".to_string());
+        return out;
+    }
+    let mut heading = None;
+    for todo in due {
+        let date = todo.due.unwrap_or(today);
+        let this = if date < today {
+            "Overdue".to_string()
+        } else {
+            date.format("%A %Y-%m-%d").to_string()
+        };
+        if heading.as_ref() != Some(&this) {
+            if heading.is_some() {
+                out.push(String::new());
+            }
+            out.push(this.clone());
+            heading = Some(this);
+        }
+        let mut text = todo.title.clone();
+        if let Some(project) = &todo.project {
+            text.push_str(&format!(" +{project}"));
+        }
+        if date < today {
+            text.push_str(&format!(" (was due {date})"));
+        }
+        out.extend(wrap("  [ ] ", &text, width));
+    }
+    out
+}
+
+/// Splits lines into pages of `PAGE_LINES`, each ending with a page number.
+pub fn paginate(lines: &[String]) -> Vec<Vec<String>> {
+    let chunks: Vec<&[String]> = lines.chunks(PAGE_LINES).collect();
+    let total = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut page = chunk.to_vec();
+            page.resize(PAGE_LINES, String::new());
+            page.push(format!(
+                "{:>width$}",
+                format!("Page {} of {total}", i + 1),
+                width = WIDTH
+            ));
+            page
+        })
+        .collect()
+}
+
+/// Plain text for a printer, with a form feed between pages.
+pub fn to_text(pages: &[Vec<String>]) -> String {
+    pages
+        .iter()
+        .map(|page| page.join("\n") + "\n")
+        .collect::<Vec<_>>()
+        .join("\x0c")
+}
+
+/// `text` as the inside of a PDF string, in the font's WinAnsiEncoding. That covers Latin-1,
+/// whose letters are written as octal escapes; anything else is replaced.
+fn pdf_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(escaped, "\\{:03o}", u32::from(c));
+            }
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// A minimal PDF with one A4 page per agenda page, set in Courier.
+pub fn to_pdf(pages: &[Vec<String>]) -> Vec<u8> {
+    let font = 3;
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    let mut kids = Vec::new();
+    for page in pages {
+        let mut stream = String::from("BT /F1 10 Tf 12 TL 56 790 Td\n");
+        for line in page {
+            stream.push_str(&format!("({}) Tj T*\n", pdf_string(line)));
+        }
+        stream.push_str("ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+        let contents = objects.len();
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] \
+             /Resources << /Font << /F1 {font} 0 R >> >> /Contents {contents} 0 R >>"
+        ));
+        kids.push(format!("{} 0 R", objects.len()));
+    }
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        kids.len()
+    );
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .bytes(),
+    );
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_agenda_lines() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let todos = vec![
+            TodoItem::parse("far off due:2024-04-01"),
+            TodoItem::parse(
+                "renew the car insurance before the old policy lapses +home due:2024-03-02",
+            ),
+            TodoItem::parse("late due:2024-02-28"),
+            TodoItem::parse("someday"),
+        ];
+        assert_eq!(
+            lines(&todos, today, 7, 40),
+            vec![
+                "Agenda for 2024-03-01 to 2024-03-08",
+                "",
+                "Overdue",
+                "  [ ] late (was due 2024-02-28)",
+                "",
+                "Saturday 2024-03-02",
+                "  [ ] renew the car insurance before the",
+                "      old policy lapses +home",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pdf_keeps_latin1() {
+        assert_eq!(
+            pdf_string(r"Café (Müller) 日本 \"),
+            r"Caf\351 \(M\374ller\) ?? \\"
+        );
+    }
+}
@@ -1,45 +1,435 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Write,
+    ops::RangeInclusive,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
 use color_eyre::eyre::Result;
-use crossterm::event::KeyEvent;
-use ratatui::prelude::Rect;
+use crossterm::{cursor, event::KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    prelude::{Constraint, Direction, Layout, Rect},
+};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedSender},
+        watch,
+    },
+    task::JoinHandle,
+};
 
 use crate::{
     action::Action,
-    components::{home::Home, Component},
-    config::Config,
+    components::{habits::Habits, home::Home, key_hints::KeyHints, tutorial::Tutorial, Component},
+    config::{self, Config, KeyTrie},
+    glyphs, google_tasks,
+    hyperlinks::{self, Link},
+    i18n::tr_with,
+    images, ipc, jira,
     mode::Mode,
+    mqtt, palette, recovery,
+    todo::TodoItem,
     tui,
 };
 
+/// How many decoded images are kept to be drawn again without decoding them anew.
+const IMAGE_CACHE: usize = 16;
+
+/// How long a half-typed key sequence waits before its continuations are shown.
+const KEY_HINT_DELAY: Duration = Duration::from_millis(500);
+
+/// How long a binding that is also the start of longer ones waits for them to be carried on.
+const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+/// The tick and frame rates, per second, that can be switched between while running.
+const RATES: RangeInclusive<f64> = 0.25..=120.0;
+
+/// `rate` doubled, or halved when not `faster`, within `RATES`.
+fn scale(rate: f64, faster: bool) -> f64 {
+    let scaled = if faster { rate * 2.0 } else { rate / 2.0 };
+    scaled.clamp(*RATES.start(), *RATES.end())
+}
+
+/// Fits a drawn frame to the terminal's colors and to the accessibility settings.
+fn adapt(buffer: &mut Buffer, config: &Config, depth: palette::Depth) {
+    palette::degrade(buffer, depth);
+    if config.reduced_motion {
+        palette::stop_blinking(buffer);
+    }
+    if config.ascii {
+        glyphs::to_ascii(buffer);
+    }
+    if config.screen_reader {
+        glyphs::strip_borders(buffer);
+    }
+}
+
+/// SIGTERM and SIGHUP, sent when the process is stopped from outside or its terminal closes,
+/// which are answered by saving and quitting.
+#[cfg(unix)]
+struct QuitSignals {
+    terminate: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl QuitSignals {
+    fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.terminate.recv() => {}
+            _ = self.hangup.recv() => {}
+        }
+    }
+}
+
+/// Elsewhere there are no such signals to wait for.
+#[cfg(not(unix))]
+struct QuitSignals;
+
+#[cfg(not(unix))]
+impl QuitSignals {
+    fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) {
+        std::future::pending().await
+    }
+}
+
+/// Where a registered component is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Over the list, which is where the built-in components draw.
+    Main,
+    /// In a pane of its own to the right of the list, sized by the constraint. Panes take the
+    /// keys while they have the focus.
+    Pane(Constraint),
+}
+
+/// The area each component is drawn in, by placement: panes side by side to the right of the
+/// main area, in the order they were registered.
+fn areas(placements: &[Placement], area: Rect) -> Vec<Rect> {
+    let constraints: Vec<Constraint> = std::iter::once(Constraint::Min(20))
+        .chain(placements.iter().filter_map(|placement| match placement {
+            Placement::Main => None,
+            Placement::Pane(constraint) => Some(*constraint),
+        }))
+        .collect();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(area);
+    let mut panes = columns.iter().skip(1);
+    placements
+        .iter()
+        .map(|placement| match placement {
+            Placement::Main => columns[0],
+            Placement::Pane(_) => panes.next().copied().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Whether component `i` is handed key events: only the focused pane is, and only while no pane
+/// is focused are the rest.
+fn takes_keys(focus: Option<usize>, placements: &[Placement], i: usize) -> bool {
+    match focus {
+        Some(focus) => i == focus,
+        None => !matches!(placements.get(i), Some(Placement::Pane(_))),
+    }
+}
+
+/// The pane to focus after `current`, or none, meaning the list, after the last one.
+fn next_focus(placements: &[Placement], current: Option<usize>) -> Option<usize> {
+    let start = current.map_or(0, |i| i + 1);
+    (start..placements.len()).find(|&i| matches!(placements[i], Placement::Pane(_)))
+}
+
 pub struct App {
     pub config: Config,
     pub tick_rate: f64,
     pub frame_rate: f64,
     pub components: Vec<Box<dyn Component>>,
+    /// Where each of `components` is drawn, in the same order.
+    placements: Vec<Placement>,
+    /// The pane among `components` that takes the keys, or none while the list has them.
+    focus: Option<usize>,
     pub should_quit: bool,
     pub should_suspend: bool,
     pub mode: Mode,
     pub last_tick_key_events: Vec<KeyEvent>,
+    google_tasks_sync: Option<JoinHandle<()>>,
+    /// When the half-typed sequence in `last_tick_key_events` was started.
+    pending_keys_since: Option<Instant>,
+    key_hints_shown: bool,
+    /// How many colors the terminal shows, which every frame is brought down to.
+    depth: palette::Depth,
+    /// Browsing a file that must not change, so nothing that could change it is started.
+    read_only: bool,
+    /// The image file drawn over the frame and where, as a component last asked for it.
+    image: Option<(PathBuf, Rect)>,
+    /// What to write to draw each image decoded so far, by file and area.
+    decoded: HashMap<(PathBuf, Rect), String>,
+    /// The keys of `decoded`, least recently drawn first, to know which to drop when it is full.
+    drawn: VecDeque<(PathBuf, Rect)>,
+    /// The image being decoded, if any.
+    decoding: Option<(PathBuf, Rect)>,
+    /// Whether the terminal makes OSC 8 hyperlinks clickable.
+    hyperlinks: bool,
+    /// The links drawn over the frame as they were last written.
+    links: Vec<Link>,
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
+    pub fn new(tick_rate: f64, frame_rate: f64, tutorial: bool) -> Result<Self> {
         let home = Home::new();
+        let mut components: Vec<Box<dyn Component>> = vec![
+            Box::new(home),
+            Box::new(Habits::new()),
+            Box::new(KeyHints::new()),
+        ];
+        if tutorial {
+            components.push(Box::new(Tutorial::new()));
+        }
         let config = Config::new()?;
         let mode = Mode::Home;
         Ok(Self {
             tick_rate,
             frame_rate,
-            components: vec![Box::new(home)],
+            placements: vec![Placement::Main; components.len()],
+            focus: None,
+            components,
             should_quit: false,
             should_suspend: false,
             config,
             mode,
             last_tick_key_events: Vec::new(),
+            google_tasks_sync: None,
+            pending_keys_since: None,
+            key_hints_shown: false,
+            depth: palette::detect(|name| std::env::var(name).ok()),
+            read_only: false,
+            image: None,
+            decoded: HashMap::new(),
+            drawn: VecDeque::new(),
+            decoding: None,
+            hyperlinks: hyperlinks::detect(|name| std::env::var(name).ok()),
+            links: Vec::new(),
         })
     }
 
+    /// An app for browsing `todos` only, with neither the data file nor any integration behind it.
+    pub fn viewer(tick_rate: f64, frame_rate: f64, todos: Vec<TodoItem>) -> Result<Self> {
+        let mut app = Self::new(tick_rate, frame_rate, false)?;
+        app.components = vec![Box::new(Home::viewer(todos)), Box::new(KeyHints::new())];
+        app.placements = vec![Placement::Main; app.components.len()];
+        app.read_only = true;
+        Ok(app)
+    }
+
+    /// Adds a component next to the built-in ones, before `run`. It is handed the config and
+    /// every action like them, and drawn where `placement` says.
+    pub fn register(&mut self, component: Box<dyn Component>, placement: Placement) {
+        self.components.push(component);
+        self.placements.push(placement);
+    }
+
+    /// Draws the image a component asks for over the frame just drawn, on terminals that can.
+    /// When it changes the old one is taken away by drawing every cell again, and the new one
+    /// comes with the next frame. Images are decoded on a blocking thread, which sends back
+    /// `ImageDecoded` to have them drawn.
+    fn show_image(&mut self, tui: &mut tui::Tui, tx: &UnboundedSender<Action>) -> Result<()> {
+        let Some(protocol) = images::protocol() else {
+            return Ok(());
+        };
+        let image = self
+            .components
+            .iter()
+            .find_map(|component| component.image());
+        if image == self.image {
+            return Ok(());
+        }
+        if self.image.take().is_some() {
+            write!(tui.backend_mut(), "{}", images::clear(protocol))?;
+            tui.clear()?;
+            self.links.clear();
+            tx.send(Action::Render)?;
+            return Ok(());
+        }
+        let Some((path, area)) = image else {
+            return Ok(());
+        };
+        let key = (path.clone(), area);
+        let Some(escape) = self.decoded.get(&key) else {
+            if self.decoding.as_ref() != Some(&(path.clone(), area)) {
+                self.decoding = Some((path.clone(), area));
+                let tx = tx.clone();
+                tokio::task::spawn_blocking(move || match images::draw(protocol, &path, area) {
+                    Ok(escape) => {
+                        let area = (area.x, area.y, area.width, area.height);
+                        let _ = tx.send(Action::ImageDecoded(path, area, escape));
+                    }
+                    Err(e) => log::warn!("Could not preview {}: {e}", path.display()),
+                });
+            }
+            return Ok(());
+        };
+        let backend = tui.backend_mut();
+        crossterm::queue!(
+            backend,
+            cursor::SavePosition,
+            cursor::MoveTo(area.x, area.y)
+        )?;
+        write!(backend, "{escape}")?;
+        crossterm::queue!(backend, cursor::RestorePosition)?;
+        backend.flush()?;
+        self.use_decoded(key);
+        self.image = Some((path, area));
+        Ok(())
+    }
+
+    /// Marks a decoded image as the most recently drawn.
+    fn use_decoded(&mut self, key: (PathBuf, Rect)) {
+        self.drawn.retain(|drawn| *drawn != key);
+        self.drawn.push_back(key);
+    }
+
+    /// Writes the text of `links` in the frame just drawn again, as clickable hyperlinks.
+    /// Only links that changed since the last frame need writing, since cells that stay the
+    /// same aren't drawn again and keep theirs.
+    fn show_links(&mut self, tui: &mut tui::Tui, links: Vec<Link>) -> Result<()> {
+        if links == self.links {
+            return Ok(());
+        }
+        let backend = tui.backend_mut();
+        crossterm::queue!(backend, cursor::SavePosition)?;
+        for link in links.iter().filter(|link| !self.links.contains(link)) {
+            write!(backend, "{}", hyperlinks::open(&link.url))?;
+            ratatui::backend::Backend::draw(
+                backend,
+                link.cells.iter().map(|(x, y, cell)| (*x, *y, cell)),
+            )?;
+            write!(backend, "{}", hyperlinks::CLOSE)?;
+        }
+        crossterm::queue!(backend, cursor::RestorePosition)?;
+        backend.flush()?;
+        self.links = links;
+        Ok(())
+    }
+
+    /// The keybindings section in effect, which is the one for the mode the list is in.
+    fn key_mode(&self) -> Mode {
+        self.components
+            .iter()
+            .find_map(|component| component.key_mode())
+            .unwrap_or(self.mode)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, tx: &UnboundedSender<Action>) -> Result<()> {
+        let chords = self.config.keybindings.chords(self.key_mode());
+        self.last_tick_key_events.push(key);
+        match chords.walk(&self.last_tick_key_events) {
+            Some(node) if node.is_leaf() => {
+                if let Some(action) = node.action() {
+                    log::info!("Got action: {action:?}");
+                    tx.send(action.clone())?;
+                }
+                self.clear_pending_keys(tx)
+            }
+            // Keep a sequence that is still heading somewhere, so its hints can be shown. One
+            // that is a binding itself runs after `CHORD_TIMEOUT` unless it is carried on.
+            Some(_) => {
+                self.pending_keys_since.get_or_insert_with(Instant::now);
+                Ok(())
+            }
+            None => {
+                // The key goes nowhere from here, so a binding waiting on more keys runs after
+                // all, and the key is tried again on its own
+                self.last_tick_key_events.pop();
+                let waiting = chords
+                    .walk(&self.last_tick_key_events)
+                    .and_then(KeyTrie::action)
+                    .cloned();
+                let retry = !self.last_tick_key_events.is_empty();
+                self.clear_pending_keys(tx)?;
+                if let Some(action) = waiting {
+                    log::info!("Got action: {action:?}");
+                    tx.send(action)?;
+                }
+                if retry {
+                    self.handle_key(key, tx)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn clear_pending_keys(&mut self, tx: &UnboundedSender<Action>) -> Result<()> {
+        self.last_tick_key_events.clear();
+        self.pending_keys_since = None;
+        if self.key_hints_shown {
+            self.key_hints_shown = false;
+            tx.send(Action::KeyHints(Vec::new()))?;
+        }
+        Ok(())
+    }
+
+    /// Re-reads the config and hands it to every component, keeping the old one if the new one
+    /// has problems.
+    fn reload_config(&mut self) -> Result<()> {
+        let problems = config::validate::check(&crate::utils::get_config_dir())?;
+        if !problems.is_empty() {
+            let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            color_eyre::eyre::bail!("{}", problems.join("; "));
+        }
+        self.config = Config::new()?;
+        for component in self.components.iter_mut() {
+            component.register_config_handler(self.config.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Syncs with Google Tasks in the background, feeding remote changes back in as an action.
+    fn sync_google_tasks(&mut self, todos: Vec<TodoItem>, tx: UnboundedSender<Action>) {
+        let Some(google) = self.config.google_tasks.clone() else {
+            log::warn!("Google Tasks sync requested but not configured");
+            return;
+        };
+        if self
+            .google_tasks_sync
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+        {
+            return;
+        }
+        let token_path = google_tasks::token_path(&self.config);
+        self.google_tasks_sync = Some(tokio::spawn(async move {
+            let state_path = google_tasks::state_path();
+            match google_tasks::sync(&google, &token_path, &state_path, &todos).await {
+                Ok(pulled) => {
+                    let _ = tx.send(Action::UpsertTodos(pulled.upserts));
+                    if !pulled.deleted.is_empty() {
+                        let _ = tx.send(Action::RemoveTodos(pulled.deleted));
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::Error(format!("Google Tasks sync failed: {e:?}")));
+                }
+            }
+        }));
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let (action_tx, mut action_rx) = mpsc::unbounded_channel();
 
@@ -62,34 +452,116 @@ impl App {
             component.buildup()?;
         }
 
+        // Integrations outside the component tree read the latest list from here
+        let (todos_tx, todos_rx) = watch::channel(Vec::new());
+        if !self.read_only {
+            recovery::follow(todos_rx.clone());
+        }
+
+        let ipc_tx = action_tx.clone();
+        let ipc_task = (!self.read_only).then(|| {
+            tokio::spawn(async move {
+                if let Err(e) = ipc::serve(ipc_tx).await {
+                    log::warn!("Command socket unavailable: {e:?}");
+                }
+            })
+        });
+
+        let mqtt_task = self
+            .config
+            .mqtt
+            .clone()
+            .filter(|_| !self.read_only)
+            .map(|mqtt_config| {
+                let mqtt_rx = todos_rx.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = mqtt::publish(mqtt_config, mqtt_rx).await {
+                        log::warn!("MQTT publishing stopped: {e:?}");
+                    }
+                })
+            });
+
+        let google_tasks_timer = self
+            .config
+            .google_tasks
+            .as_ref()
+            .and_then(|google| google.interval_secs)
+            .filter(|_| !self.read_only)
+            .map(|secs| {
+                let timer_tx = action_tx.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(Duration::from_secs(secs.max(1)));
+                    loop {
+                        interval.tick().await;
+                        if timer_tx.send(Action::SyncGoogleTasks).is_err() {
+                            break;
+                        }
+                    }
+                })
+            });
+
+        let config_watch = tokio::spawn(config::watch(
+            crate::utils::get_config_dir(),
+            action_tx.clone(),
+        ));
+
+        let jira_fetch = self
+            .config
+            .jira
+            .clone()
+            .filter(|_| !self.read_only)
+            .map(|jira_config| {
+                let jira_tx = action_tx.clone();
+                tokio::spawn(async move {
+                    let action = match jira::fetch(&jira_config).await {
+                        Ok(issues) => Action::MergeJiraIssues(issues),
+                        Err(e) => Action::Error(format!("Fetching Jira issues failed: {e:?}")),
+                    };
+                    let _ = jira_tx.send(action);
+                })
+            });
+
+        #[cfg(target_os = "linux")]
+        let dbus_task = (!self.read_only).then(|| {
+            let dbus_tx = action_tx.clone();
+            let dbus_rx = todos_rx.clone();
+            tokio::spawn(async move {
+                if let Err(e) = crate::dbus::serve(dbus_tx, dbus_rx).await {
+                    log::warn!("D-Bus service unavailable: {e:?}");
+                }
+            })
+        });
+
+        let mut quit_signals = QuitSignals::new()?;
         loop {
-            if let Some(e) = tui.next().await {
+            let event = tokio::select! {
+                event = tui.next() => event,
+                () = quit_signals.recv() => {
+                    // The terminal may be gone already, so save before anything is drawn to it
+                    log::info!("Saving and quitting on a signal");
+                    for component in self.components.iter_mut() {
+                        if let Err(e) = component.teardown() {
+                            log::error!("Failed to save on a signal: {e:?}");
+                        }
+                    }
+                    break;
+                }
+            };
+            if let Some(e) = event {
                 match e {
                     tui::Event::Quit => action_tx.send(Action::Quit)?,
                     tui::Event::Tick => action_tx.send(Action::Tick)?,
                     tui::Event::Render => action_tx.send(Action::Render)?,
                     tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                    tui::Event::Key(key) => {
-                        if let Some(keymap) = self.config.keybindings.get(&self.mode) {
-                            if let Some(action) = keymap.get(&vec![key]) {
-                                log::info!("Got action: {action:?}");
-                                action_tx.send(action.clone())?;
-                            } else {
-                                // If the key was not handled as a single key action,
-                                // then consider it for multi-key combinations.
-                                self.last_tick_key_events.push(key);
-
-                                // Check for multi-key combinations
-                                if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                                    log::info!("Got action: {action:?}");
-                                    action_tx.send(action.clone())?;
-                                }
-                            }
-                        };
-                    }
+                    tui::Event::Key(key) => self.handle_key(key, &action_tx)?,
                     _ => {}
                 }
-                for component in self.components.iter_mut() {
+                for (i, component) in self.components.iter_mut().enumerate() {
+                    if matches!(e, tui::Event::Key(_))
+                        && !takes_keys(self.focus, &self.placements, i)
+                    {
+                        continue;
+                    }
                     if let Some(action) = component.handle_events(Some(e.clone()))? {
                         action_tx.send(action)?;
                     }
@@ -97,14 +569,51 @@ impl App {
             }
 
             while let Ok(action) = action_rx.try_recv() {
-                if action != Action::Tick && action != Action::Render {
+                // Decoded images are left out, being megabytes of escapes
+                if !matches!(
+                    action,
+                    Action::Tick | Action::Render | Action::ImageDecoded(..)
+                ) {
                     log::debug!("{action:?}");
                 }
                 match action {
-                    Action::Tick => {
-                        self.last_tick_key_events.drain(..);
-                    }
+                    Action::Tick => match self.pending_keys_since {
+                        None => {
+                            self.last_tick_key_events.drain(..);
+                        }
+                        Some(since) => {
+                            let waiting = self
+                                .config
+                                .keybindings
+                                .chords(self.key_mode())
+                                .walk(&self.last_tick_key_events)
+                                .and_then(KeyTrie::action)
+                                .cloned();
+                            match waiting {
+                                Some(action) if since.elapsed() >= CHORD_TIMEOUT => {
+                                    action_tx.send(action)?;
+                                    self.clear_pending_keys(&action_tx)?;
+                                }
+                                _ if !self.key_hints_shown && since.elapsed() >= KEY_HINT_DELAY => {
+                                    let hints = self
+                                        .config
+                                        .keybindings
+                                        .continuations(self.key_mode(), &self.last_tick_key_events);
+                                    self.key_hints_shown = true;
+                                    action_tx.send(Action::KeyHints(hints))?;
+                                }
+                                _ => {}
+                            }
+                        }
+                    },
                     Action::Quit => {
+                        let mut held = false;
+                        for component in self.components.iter_mut() {
+                            held |= component.hold_quit()?;
+                        }
+                        if held {
+                            continue;
+                        }
                         for component in self.components.iter_mut() {
                             let r = component.teardown();
                             if let Err(e) = r {
@@ -113,32 +622,154 @@ impl App {
                         }
                         self.should_quit = true
                     }
+                    Action::TodosChanged(ref todos) => {
+                        todos_tx.send_replace(todos.clone());
+                    }
+                    Action::FocusNext => {
+                        let next = next_focus(&self.placements, self.focus);
+                        for (i, focused) in [(self.focus, false), (next, true)] {
+                            if let Some(component) = i.and_then(|i| self.components.get_mut(i)) {
+                                component.set_focus(focused)?;
+                            }
+                        }
+                        self.focus = next;
+                    }
+                    Action::SyncGoogleTasks if !self.read_only => {
+                        self.sync_google_tasks(todos_rx.borrow().clone(), action_tx.clone());
+                    }
+                    Action::ReloadConfig => {
+                        let toast = match self.reload_config() {
+                            Ok(()) => "Config reloaded".to_string(),
+                            Err(e) => format!("Config not reloaded: {e}"),
+                        };
+                        action_tx.send(Action::Toast(toast))?;
+                    }
+                    Action::FasterTicks | Action::SlowerTicks => {
+                        self.tick_rate = scale(self.tick_rate, action == Action::FasterTicks);
+                        tui.tick_rate = self.tick_rate;
+                        tui.start();
+                        action_tx.send(Action::Toast(tr_with(
+                            "{rate} ticks per second",
+                            &[("rate", &self.tick_rate)],
+                        )))?;
+                    }
+                    Action::FasterFrames | Action::SlowerFrames => {
+                        self.frame_rate = scale(self.frame_rate, action == Action::FasterFrames);
+                        tui.frame_rate = self.frame_rate;
+                        tui.start();
+                        action_tx.send(Action::Toast(tr_with(
+                            "{rate} frames per second",
+                            &[("rate", &self.frame_rate)],
+                        )))?;
+                    }
+                    Action::CompleteJiraIssue(ref key) | Action::ReopenJiraIssue(ref key) => {
+                        if let Some(jira_config) = self.config.jira.clone() {
+                            let key = key.clone();
+                            let done = matches!(action, Action::CompleteJiraIssue(_));
+                            let jira_tx = action_tx.clone();
+                            tokio::spawn(async move {
+                                let (moved, doing) = if done {
+                                    (jira::complete(&jira_config, &key).await, "Completing")
+                                } else {
+                                    (jira::reopen(&jira_config, &key).await, "Reopening")
+                                };
+                                if let Err(e) = moved {
+                                    let _ = jira_tx.send(Action::Error(format!(
+                                        "{doing} {key} in Jira failed: {e:?}"
+                                    )));
+                                }
+                            });
+                        }
+                    }
+                    Action::OpenEditor(ref path) => {
+                        tui.exit()?;
+                        let edited = crate::utils::run_editor(path);
+                        tui = tui::Tui::new()?
+                            .tick_rate(self.tick_rate)
+                            .frame_rate(self.frame_rate);
+                        tui.enter()?;
+                        tui.clear()?;
+                        self.image = None;
+                        self.links.clear();
+                        action_tx.send(match edited {
+                            Ok(()) => Action::EditorClosed(path.clone()),
+                            Err(e) => Action::Error(format!("Editing stopped: {e}")),
+                        })?;
+                    }
+                    Action::ImageDecoded(ref path, (x, y, width, height), ref escape) => {
+                        let key = (path.clone(), Rect::new(x, y, width, height));
+                        if self.decoding.as_ref() == Some(&key) {
+                            self.decoding = None;
+                        }
+                        if !self.decoded.contains_key(&key) && self.decoded.len() >= IMAGE_CACHE {
+                            if let Some(oldest) = self.drawn.pop_front() {
+                                self.decoded.remove(&oldest);
+                            }
+                        }
+                        self.decoded.insert(key.clone(), escape.clone());
+                        self.use_decoded(key);
+                        action_tx.send(Action::Render)?;
+                    }
                     Action::Suspend => self.should_suspend = true,
                     Action::Resume => self.should_suspend = false,
                     Action::Resize(w, h) => {
                         tui.resize(Rect::new(0, 0, w, h))?;
+                        self.links.clear();
+                        if self.image.take().is_some() {
+                            if let Some(protocol) = images::protocol() {
+                                write!(tui.backend_mut(), "{}", images::clear(protocol))?;
+                            }
+                        }
+                        let mut links = Vec::new();
                         tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
+                            let areas = areas(&self.placements, f.size());
+                            for (i, component) in self.components.iter_mut().enumerate() {
+                                let area = areas.get(i).copied().unwrap_or(f.size());
+                                let r = component.draw(f, area);
                                 if let Err(e) = r {
                                     action_tx
                                         .send(Action::Error(format!("Failed to draw: {:?}", e)))
                                         .unwrap();
                                 }
                             }
+                            adapt(f.buffer_mut(), &self.config, self.depth);
+                            if self.hyperlinks {
+                                let targets: Vec<hyperlinks::Target> = self
+                                    .components
+                                    .iter()
+                                    .flat_map(|component| component.links())
+                                    .collect();
+                                links = hyperlinks::find(f.buffer_mut(), &targets);
+                            }
                         })?;
+                        self.show_links(&mut tui, links)?;
+                        self.show_image(&mut tui, &action_tx)?;
                     }
                     Action::Render => {
+                        let mut links = Vec::new();
                         tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
+                            let areas = areas(&self.placements, f.size());
+                            for (i, component) in self.components.iter_mut().enumerate() {
+                                let area = areas.get(i).copied().unwrap_or(f.size());
+                                let r = component.draw(f, area);
                                 if let Err(e) = r {
                                     action_tx
                                         .send(Action::Error(format!("Failed to draw: {:?}", e)))
                                         .unwrap();
                                 }
                             }
+                            adapt(f.buffer_mut(), &self.config, self.depth);
+                            if self.hyperlinks {
+                                let targets: Vec<hyperlinks::Target> = self
+                                    .components
+                                    .iter()
+                                    .flat_map(|component| component.links())
+                                    .collect();
+                                links = hyperlinks::find(f.buffer_mut(), &targets);
+                            }
                         })?;
+                        self.show_links(&mut tui, links)?;
+                        self.show_image(&mut tui, &action_tx)?;
                     }
                     _ => {}
                 }
@@ -162,7 +793,64 @@ impl App {
                 break;
             }
         }
+        if let Some(ipc_task) = ipc_task {
+            ipc_task.abort();
+        }
+        if let Some(mqtt_task) = mqtt_task {
+            mqtt_task.abort();
+        }
+        if let Some(google_tasks_timer) = google_tasks_timer {
+            google_tasks_timer.abort();
+        }
+        config_watch.abort();
+        if let Some(jira_fetch) = jira_fetch {
+            jira_fetch.abort();
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(dbus_task) = dbus_task {
+            dbus_task.abort();
+        }
         tui.exit()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_scale_rates() {
+        assert_eq!(scale(60.0, false), 30.0);
+        assert_eq!(scale(1.0, true), 2.0);
+        assert_eq!(scale(100.0, true), 120.0);
+        assert_eq!(scale(0.25, false), 0.25);
+    }
+
+    #[test]
+    fn test_panes_beside_the_list() {
+        let placements = [
+            Placement::Main,
+            Placement::Pane(Constraint::Length(30)),
+            Placement::Main,
+            Placement::Pane(Constraint::Length(10)),
+        ];
+        assert_eq!(
+            areas(&placements, Rect::new(0, 0, 100, 20)),
+            vec![
+                Rect::new(0, 0, 60, 20),
+                Rect::new(60, 0, 30, 20),
+                Rect::new(0, 0, 60, 20),
+                Rect::new(90, 0, 10, 20),
+            ]
+        );
+        assert_eq!(next_focus(&placements, None), Some(1));
+        assert_eq!(next_focus(&placements, Some(1)), Some(3));
+        assert_eq!(next_focus(&placements, Some(3)), None);
+        assert!(takes_keys(None, &placements, 2));
+        assert!(!takes_keys(None, &placements, 1));
+        assert!(!takes_keys(Some(1), &placements, 0));
+    }
+}
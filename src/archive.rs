@@ -0,0 +1,129 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, Utc};
+use color_eyre::eyre::Result;
+use uuid::Uuid;
+
+use crate::{
+    crdt::Document,
+    todo::{self, TodoItem},
+};
+
+const LEVEL: i32 = 19;
+
+/// The archive of todos completed during `year`, kept alongside the data file.
+pub fn path(data_file: &Path, year: i32) -> PathBuf {
+    todo::state_path(data_file).with_file_name(format!("archive-{year}.json.zst"))
+}
+
+/// Reads an archive, treating a missing file as empty.
+pub fn read(path: &Path) -> Result<Vec<TodoItem>> {
+    match File::open(path) {
+        Ok(file) => Ok(serde_json::from_reader(zstd::Decoder::new(file)?)?),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn write(path: &Path, todos: &[TodoItem]) -> Result<()> {
+    let json = serde_json::to_vec(todos)?;
+    let tmp = path.with_extension("zst.tmp");
+    fs::write(&tmp, zstd::encode_all(json.as_slice(), LEVEL)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Moves todos completed before `cutoff` out of the document and into the archive for the year
+/// they were completed in, returning how many were moved.
+///
+/// The entries stay behind as tombstones deleted at `at`, so that joining in a copy from a
+/// device that hasn't archived them yet doesn't bring them back.
+pub fn roll(
+    document: &mut Document,
+    data_file: &Path,
+    cutoff: DateTime<Utc>,
+    at: DateTime<Utc>,
+) -> Result<usize> {
+    let old: Vec<Uuid> = document
+        .items
+        .iter()
+        .filter(|(_, entry)| !entry.deleted.value && entry.done.value && entry.done.at < cutoff)
+        .map(|(id, _)| *id)
+        .collect();
+    if old.is_empty() {
+        return Ok(0);
+    }
+
+    let todos: BTreeMap<Uuid, TodoItem> = document
+        .todos()
+        .into_iter()
+        .map(|todo| (todo.id, todo))
+        .collect();
+    let mut by_year: BTreeMap<i32, Vec<TodoItem>> = BTreeMap::new();
+    for id in &old {
+        let year = document.items[id].done.at.year();
+        by_year.entry(year).or_default().push(todos[id].clone());
+    }
+    for (year, archived) in by_year {
+        let path = path(data_file, year);
+        let mut existing = read(&path)?;
+        existing.retain(|todo| !archived.iter().any(|new| new.id == todo.id));
+        existing.extend(archived);
+        write(&path, &existing)?;
+    }
+    for id in &old {
+        if let Some(entry) = document.items.get_mut(id) {
+            entry.deleted.set(true, at);
+        }
+    }
+    Ok(old.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_roll_old_completed_todos() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-archive-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir)?;
+        let data_file = dir.join("home.json");
+
+        let now = Utc::now();
+        let mut old = TodoItem::new("old".into());
+        old.done = true;
+        old.updated = now - Duration::days(400);
+        let mut recent = TodoItem::new("recent".into());
+        recent.done = true;
+        let mut document =
+            Document::from_todos(&[old.clone(), recent, TodoItem::new("open".into())]);
+        let stale = document.clone();
+
+        assert_eq!(
+            roll(&mut document, &data_file, now - Duration::days(30), now)?,
+            1
+        );
+        // A device that hasn't archived it yet doesn't bring it back
+        document.join(&stale);
+        assert_eq!(
+            roll(&mut document, &data_file, now - Duration::days(30), now)?,
+            0
+        );
+        let titles: Vec<String> = document
+            .todos()
+            .into_iter()
+            .map(|todo| todo.title)
+            .collect();
+        assert_eq!(titles, vec!["recent", "open"]);
+        let archived = read(&path(&data_file, old.updated.year()))?;
+        assert_eq!(archived[0].title, "old");
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use chrono::{Days, NaiveDate};
+use color_eyre::eyre::Result;
+use ratatui::{backend::TestBackend, Terminal};
+
+use crate::{
+    action::Action,
+    components::{home::Home, Component},
+    todo::TodoItem,
+};
+
+/// How many todos the list is measured with.
+pub const SIZES: [usize; 3] = [10, 1_000, 100_000];
+
+/// The terminal size frames are drawn at.
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 40;
+
+/// What a round of the update loop goes through: moving around the list and starring a todo,
+/// which reorders it, and unstarring it again.
+pub const ROUND: [Action; 4] = [
+    Action::BrowseListDown,
+    Action::ToggleStar(0),
+    Action::BrowseListUp,
+    Action::ToggleStar(0),
+];
+
+/// `n` todos with a mix of tags, projects, due dates and finished ones.
+pub fn todos(n: usize) -> Vec<TodoItem> {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default();
+    (0..n)
+        .map(|i| {
+            let due = start + Days::new((i % 60) as u64);
+            let mut todo = TodoItem::parse(&format!(
+                "todo number {i} #tag{} +project{} due:{due}",
+                i % 7,
+                i % 5
+            ));
+            todo.done = i % 4 == 0;
+            todo
+        })
+        .collect()
+}
+
+/// The list of `n` todos, browsing.
+pub fn home(n: usize) -> Result<Home> {
+    let mut home = Home::with_todos(todos(n));
+    home.update(Action::EnterBrowseMode)?;
+    Ok(home)
+}
+
+/// A terminal frames are drawn to in memory.
+pub fn terminal() -> Result<Terminal<TestBackend>> {
+    Ok(Terminal::new(TestBackend::new(WIDTH, HEIGHT))?)
+}
+
+/// Draws one frame of `home`.
+pub fn draw(home: &mut Home, terminal: &mut Terminal<TestBackend>) -> Result<()> {
+    let mut result = Ok(());
+    terminal.draw(|f| result = home.draw(f, f.size()))?;
+    result
+}
+
+/// Runs `home` through one `ROUND` of actions, as the event loop would.
+pub fn update(home: &mut Home) -> Result<()> {
+    for action in ROUND {
+        home.update(action)?;
+    }
+    Ok(())
+}
+
+/// Times drawing `frames` frames and as many update rounds for each of `SIZES`, for
+/// `--bench-render`.
+pub fn report(frames: u32) -> Result<String> {
+    let mut out = format!("{:>8}  {:>12}  {:>14}\n", "todos", "draw", "updates/s");
+    for n in SIZES {
+        let mut home = home(n)?;
+        let mut terminal = terminal()?;
+        let started = Instant::now();
+        for _ in 0..frames {
+            draw(&mut home, &mut terminal)?;
+        }
+        let drawing = started.elapsed() / frames.max(1);
+        let started = Instant::now();
+        for _ in 0..frames {
+            update(&mut home)?;
+        }
+        let updating = started.elapsed().max(Duration::from_nanos(1));
+        let rate = f64::from(frames) * ROUND.len() as f64 / updating.as_secs_f64();
+        out.push_str(&format!("{n:>8}  {drawing:>12.2?}  {rate:>14.0}\n"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_report() -> Result<()> {
+        let report = report(1)?;
+        let sizes: Vec<&str> = report
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        assert_eq!(sizes, vec!["10", "1000", "100000"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::borrow::Cow;
+
+use unicode_bidi::ParagraphBidiInfo;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// What the input box shows for a value being typed: the text in the order it appears on screen,
+/// how far it is scrolled and the column the cursor sits on, both in cells from the left.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Field {
+    pub text: String,
+    pub scroll: usize,
+    pub cursor: usize,
+}
+
+/// `text` in the order its characters appear on screen, since terminals draw every line left to
+/// right. Text without right-to-left characters comes back as it is.
+pub fn visual(text: &str) -> Cow<'_, str> {
+    ParagraphBidiInfo::new(text, None).reorder_line(0..text.len())
+}
+
+/// `text` on screen in at most `width` cells. Right-to-left text starts on the right, so it loses
+/// its end on the left behind an ellipsis; left-to-right text is left for the widget to cut off.
+pub fn fit(text: &str, width: usize) -> Cow<'_, str> {
+    let visual = visual(text);
+    if visual.width() <= width || !ParagraphBidiInfo::new(text, None).paragraph_level.is_rtl() {
+        return visual;
+    }
+    let mut kept = Vec::new();
+    let mut used = 0;
+    for c in visual.chars().rev() {
+        used += c.width().unwrap_or(0);
+        if used + 1 > width {
+            break;
+        }
+        kept.push(c);
+    }
+    Cow::Owned(std::iter::once('…').chain(kept.into_iter().rev()).collect())
+}
+
+/// Lays out `value`, with the cursor before its `index`th character, in a box `width` cells wide.
+/// The cursor sits on the character it is before, which for right-to-left runs is the cell to
+/// the left of where the next character goes. Right-to-left values are right aligned, keep the
+/// spare cell for the cursor at their end on the left, and when they don't fit are scrolled to
+/// show their start unless the cursor is further along.
+pub fn field(value: &str, index: usize, width: usize) -> Field {
+    let info = ParagraphBidiInfo::new(value, None);
+    let chars: Vec<char> = value.chars().collect();
+    let order: Vec<usize> = if info.is_pure_ltr {
+        (0..chars.len()).collect()
+    } else {
+        ParagraphBidiInfo::reorder_visual(&info.reordered_levels_per_char(0..value.len()))
+    };
+    let mut text = String::new();
+    let mut columns = vec![0; chars.len()];
+    let mut at = 0;
+    for i in order {
+        columns[i] = at;
+        text.push(chars[i]);
+        at += chars[i].width().unwrap_or(0);
+    }
+    if !info.paragraph_level.is_rtl() {
+        let cursor = columns.get(index).copied().unwrap_or(at);
+        return Field {
+            text,
+            scroll: (cursor + 1).saturating_sub(width),
+            cursor,
+        };
+    }
+    let lead = width.saturating_sub(at + 1) + 1;
+    let cursor = columns.get(index).map_or(lead - 1, |column| lead + column);
+    Field {
+        text: format!("{}{text}", " ".repeat(lead)),
+        scroll: (lead + at).saturating_sub(width).min(cursor),
+        cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_rtl_layout() {
+        assert_eq!(visual("buy milk"), "buy milk");
+        assert_eq!(visual("שלום 12"), "12 םולש");
+        assert_eq!(fit("שלום עולם", 6), "… םולש");
+        assert_eq!(fit("hello world", 6), "hello world");
+
+        assert_eq!(
+            field("ab", 1, 10),
+            Field {
+                text: "ab".into(),
+                scroll: 0,
+                cursor: 1
+            }
+        );
+        // Typed at the end, the cursor is left of the whole value
+        let typing = field("אבג", 3, 6);
+        assert_eq!(typing.text, "   גבא");
+        assert_eq!((typing.scroll, typing.cursor), (0, 2));
+        // Before the second letter, the cursor is on it
+        assert_eq!(field("אבג", 1, 6).cursor, 4);
+        let long = field("אבגדהוזח", 0, 6);
+        assert_eq!((long.scroll, long.cursor), (3, 8));
+        assert_eq!(field("אבגדהוזח", 8, 6).scroll, 0);
+    }
+}
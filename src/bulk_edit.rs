@@ -0,0 +1,176 @@
+use uuid::Uuid;
+
+use crate::{
+    intern::Name,
+    todo::{EventKind, TodoItem},
+};
+
+/// Explains the file format at the top of the file handed to the editor.
+const HEADER: &str = "\
+# One todo per line, as you would type it in the input box. Lines starting with `# ` are
+# ignored. Edit a line to change its todo, delete it to delete the todo, and add lines without
+# an id to add todos.
+";
+
+/// What applying an edited file changed, for telling the user.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub added: usize,
+    pub edited: usize,
+    pub deleted: usize,
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} edited, {} deleted",
+            self.added, self.edited, self.deleted
+        )
+    }
+}
+
+/// The start of an id that marks which todo a line belongs to.
+fn short_id(id: Uuid) -> String {
+    id.simple().to_string()[..8].to_string()
+}
+
+/// The file to edit: each todo in `listed` on its own line, after its short id.
+pub fn render(todos: &[TodoItem], listed: &[Uuid]) -> String {
+    let mut out = HEADER.to_string();
+    for todo in listed
+        .iter()
+        .filter_map(|id| todos.iter().find(|todo| todo.id == *id))
+    {
+        out.push_str(&format!("{} {}\n", short_id(todo.id), todo.quick_add()));
+    }
+    out
+}
+
+/// Sets `field` to `after` if the line changed it from `before`.
+fn take_changed<T: PartialEq>(field: &mut T, before: T, after: T) {
+    if after != before {
+        *field = after;
+    }
+}
+
+/// Applies an edited file to `todos`. Todos in `listed` whose line is gone are deleted, changed
+/// lines update their todo and lines without a known id are added, in `project` unless they
+/// name one.
+pub fn apply(
+    todos: &mut Vec<TodoItem>,
+    listed: &[Uuid],
+    text: &str,
+    project: Option<&str>,
+) -> Summary {
+    let mut summary = Summary::default();
+    let mut kept = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line == "#" || line.starts_with("# ") {
+            continue;
+        }
+        let known = line.split_once(' ').and_then(|(prefix, rest)| {
+            let id = listed.iter().find(|id| short_id(**id) == prefix)?;
+            Some((*id, rest))
+        });
+        match known {
+            Some((id, rest)) => {
+                kept.push(id);
+                let Some(todo) = todos.iter_mut().find(|todo| todo.id == id) else {
+                    continue;
+                };
+                if todo.quick_add() == rest.trim() {
+                    continue;
+                }
+                // Not everything reads back the way it was written, such as attachments with
+                // spaces or titles with `#` words, so only what changed on the line is taken
+                let before = TodoItem::parse(&todo.quick_add());
+                let after = TodoItem::parse(rest);
+                take_changed(&mut todo.title, before.title, after.title);
+                take_changed(&mut todo.due, before.due, after.due);
+                take_changed(&mut todo.estimate, before.estimate, after.estimate);
+                take_changed(&mut todo.attachments, before.attachments, after.attachments);
+                take_changed(&mut todo.project, before.project, after.project);
+                take_changed(&mut todo.context, before.context, after.context);
+                take_changed(&mut todo.assignee, before.assignee, after.assignee);
+                take_changed(&mut todo.tags, before.tags, after.tags);
+                todo.touch();
+                todo.log(EventKind::Edited);
+                summary.edited += 1;
+            }
+            None => {
+                let mut todo = TodoItem::parse(line);
+                if todo.project.is_none() {
+                    todo.project = project.map(Name::from);
+                }
+                todo.log(EventKind::Created);
+                todos.push(todo);
+                summary.added += 1;
+            }
+        }
+    }
+    let before = todos.len();
+    todos.retain(|todo| !listed.contains(&todo.id) || kept.contains(&todo.id));
+    summary.deleted = before - todos.len();
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_apply() {
+        let mut todos = vec![
+            TodoItem::parse("water plants due:2024-05-01 +home #chores"),
+            TodoItem::parse("call mum"),
+            TodoItem::parse("hidden +work"),
+        ];
+        let listed = vec![todos[0].id, todos[1].id];
+        let text = render(&todos, &listed);
+        assert!(text.ends_with(&format!(
+            "{} water plants due:2024-05-01 +home #chores\n{} call mum\n",
+            short_id(todos[0].id),
+            short_id(todos[1].id)
+        )));
+        assert_eq!(apply(&mut todos, &listed, &text, None), Summary::default());
+
+        let edited = text.replace("water plants", "water all plants").replace(
+            &format!("{} call mum\n", short_id(todos[1].id)),
+            "buy milk #errands\n",
+        );
+        let summary = apply(&mut todos, &listed, &edited, Some("home"));
+        assert_eq!(
+            summary,
+            Summary {
+                added: 1,
+                edited: 1,
+                deleted: 1
+            }
+        );
+        let titles: Vec<&str> = todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["water all plants", "hidden", "buy milk"]);
+        assert_eq!(todos[0].tags, vec!["chores"]);
+        assert_eq!(todos[2].project.as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn test_keep_what_does_not_read_back() {
+        let mut todo = TodoItem::parse("file taxes +admin");
+        todo.title = "fix #3 for C++".into();
+        todo.attachments = vec!["/home/me/tax return.pdf".into()];
+        let mut todos = vec![todo];
+        let listed = vec![todos[0].id];
+        let text = render(&todos, &listed);
+        let edited = text.replace("+admin", "+admin due:2024-04-15");
+        let summary = apply(&mut todos, &listed, &edited, None);
+        assert_eq!(summary.edited, 1);
+        assert_eq!(todos[0].title, "fix #3 for C++");
+        assert_eq!(todos[0].attachments, vec!["/home/me/tax return.pdf"]);
+        assert_eq!(todos[0].due, NaiveDate::from_ymd_opt(2024, 4, 15));
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::{BufRead, Write};
+
+use color_eyre::eyre::Result;
+
+use crate::{
+    ipc,
+    todo::{self, EventKind, TodoItem},
+};
+
+/// Asks for one todo on a single line. An empty answer, or none at all, captures nothing.
+pub fn ask(input: &mut impl BufRead, output: &mut impl Write) -> Result<Option<String>> {
+    write!(output, "New todo: ")?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let raw = line.trim();
+    Ok((!raw.is_empty()).then(|| raw.to_string()))
+}
+
+/// Prompts for a todo and adds it, handing it to a running session if there is one so that the
+/// session does not write over it, and exits straight away.
+pub async fn run() -> Result<()> {
+    let Some(raw) = ask(&mut std::io::stdin().lock(), &mut std::io::stdout())? else {
+        return Ok(());
+    };
+    if ipc::send(&ipc::Request::Add(raw.clone())).await.is_err() {
+        todo::update(&todo::data_file(), |todos| {
+            let mut todo = TodoItem::parse(&raw);
+            todo.log(EventKind::Created);
+            todos.push(todo);
+        })?;
+    }
+    println!("Captured \"{}\"", TodoItem::parse(&raw).title);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_ask_one_line() -> Result<()> {
+        let mut output = Vec::new();
+        let raw = ask(
+            &mut "  call bank due:tomorrow \nignored\n".as_bytes(),
+            &mut output,
+        )?;
+        assert_eq!(raw.as_deref(), Some("call bank due:tomorrow"));
+        assert_eq!(String::from_utf8(output)?, "New todo: ");
+        assert_eq!(ask(&mut "\n".as_bytes(), &mut Vec::new())?, None);
+        assert_eq!(ask(&mut "".as_bytes(), &mut Vec::new())?, None);
+        Ok(())
+    }
+}
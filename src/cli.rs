@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use crate::utils::version;
 
@@ -18,4 +18,117 @@ pub struct Cli {
     default_value_t = 4.0
   )]
   pub frame_rate: f64,
+
+  #[arg(long, help = "Print the remaining todos to stdout after exiting")]
+  pub summary: bool,
+
+  #[arg(long, help = "Only include todos due today in the exit summary")]
+  pub due_today: bool,
+
+  #[arg(long, help = "Use the usual data file even inside a project with its own .doit.json")]
+  pub global: bool,
+
+  #[arg(long, hide = true, help = "Time drawing and updating lists of generated todos, then exit")]
+  pub bench_render: bool,
+
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Run headless, sending desktop notifications as todos come due
+  Daemon,
+  /// Open the TUI with step-by-step prompts for learning the basics
+  Tutorial,
+  /// Add a todo without opening the TUI
+  Add {
+    #[arg(long, help = "Insert into the running session instead of the data file")]
+    running: bool,
+
+    #[arg(required = true, help = "Todo text, accepting the same due:<date> syntax as the TUI")]
+    text: Vec<String>,
+  },
+  /// Ask for one todo, add it and exit, for binding to a desktop-wide hotkey
+  Capture,
+  /// Print how many todos are open, quickly enough to run in a shell prompt
+  Count {
+    #[arg(long, value_enum, help = "Only count todos with these due dates")]
+    due: Option<crate::count::Due>,
+    #[arg(long, default_value = "{n}", help = "What to print, with {n} standing for the count")]
+    format: String,
+  },
+  /// Print a short colored summary for a tmux status bar or a starship custom module
+  StatusLine {
+    #[arg(long, help = "Write colors the way tmux expects instead of as terminal escapes")]
+    tmux: bool,
+  },
+  /// Print the status line without colors, for polybar and other bars that poll a command
+  Status {
+    #[arg(long, help = "Print a Waybar custom module as JSON, with the due todos as its tooltip")]
+    waybar: bool,
+  },
+  /// Print a digest of open and overdue todos
+  Digest {
+    #[arg(long, help = "Send the digest through the configured SMTP relay instead of printing it")]
+    email: bool,
+  },
+  /// Exchange encrypted copies of the todos with other devices through the configured remote
+  Sync {
+    #[arg(long, help = "Sync with Google Tasks instead, authorizing on first use")]
+    google: bool,
+    #[arg(long, help = "Fetch the issues matched by the configured Jira queries instead")]
+    jira: bool,
+    #[arg(long, help = "Sync with the configured Notion database instead")]
+    notion: bool,
+  },
+  /// Show open todos due in the coming days, grouped by date
+  Agenda {
+    #[arg(long, default_value_t = 7, help = "How many days ahead to include")]
+    days: u64,
+    #[arg(long, help = "Lay the agenda out in numbered pages for a printer")]
+    print: bool,
+    #[arg(long, value_name = "FILE", help = "Also write the printable pages to a PDF file")]
+    pdf: Option<PathBuf>,
+  },
+  /// Show a project or a single todo as a QR code to scan onto a phone
+  Qr {
+    #[arg(long, help = "Share the open todos of this project")]
+    project: Option<String>,
+    #[arg(help = "Share the todo at this position in the list")]
+    index: Option<usize>,
+  },
+  /// Import todos exported from another app
+  Import {
+    #[command(subcommand)]
+    source: ImportSource,
+  },
+  /// Export todos for use in another app
+  Export {
+    #[command(subcommand)]
+    format: ExportFormat,
+  },
+  /// Browse a data file, a backup of one or an org export without being able to change it
+  View {
+    #[arg(help = "The file to browse, the data file unless given")]
+    file: Option<PathBuf>,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+  /// A JSON array of reminders dumped from Apple Reminders via EventKit
+  Reminders { file: PathBuf },
+  /// TODO and DONE headings from an Emacs org file
+  Org { file: PathBuf },
+  /// A Trello board's "Export as JSON" file
+  Trello { file: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportFormat {
+  /// An Emacs org file, written to stdout unless a file is given
+  Org { file: Option<PathBuf> },
+  /// A standalone HTML report for sharing, written to stdout unless a file is given
+  Html { file: Option<PathBuf> },
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::layout::Rect;
@@ -6,10 +8,15 @@ use tokio::sync::mpsc::UnboundedSender;
 use crate::{
     action::Action,
     config::Config,
+    hyperlinks::Target,
+    mode::Mode,
     tui::{Event, Frame},
 };
 
+pub mod habits;
 pub mod home;
+pub mod key_hints;
+pub mod tutorial;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
@@ -120,6 +127,51 @@ pub trait Component {
     ///
     /// * `Result<()>` - An Ok result or an error.
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()>;
+    /// Take or lose the keys, when drawn in a pane of its own
+    ///
+    /// # Arguments
+    ///
+    /// * `focused` - Whether the component now takes the keys.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    #[allow(unused_variables)]
+    fn set_focus(&mut self, focused: bool) -> Result<()> {
+        Ok(())
+    }
+    /// The keybindings section for the mode the component is in, if it has modes
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Mode>` - The section, or none to leave it to another component.
+    fn key_mode(&self) -> Option<Mode> {
+        None
+    }
+    /// An image file to draw over part of the frame, on terminals that can draw images
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(PathBuf, Rect)>` - The file and the area it fills, as of the last draw.
+    fn image(&self) -> Option<(PathBuf, Rect)> {
+        None
+    }
+    /// Text the component drew that links somewhere
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Target>` - The text, its URL and the area it was drawn in, as of the last draw.
+    fn links(&self) -> Vec<Target> {
+        Vec::new()
+    }
+    /// Hold back quitting while the user has something to settle first
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - Whether to keep running for now.
+    fn hold_quit(&mut self) -> Result<bool> {
+        Ok(false)
+    }
     /// Save the current saveable states to json, write to output directory
     ///
     /// # Returns
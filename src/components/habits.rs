@@ -0,0 +1,188 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use chrono::{Days, Local, NaiveDate};
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    config::Config,
+    i18n::{tr, tr_with},
+    todo, utils,
+};
+
+/// How many days the check-off grid goes back, today included.
+const DAYS: u64 = 30;
+
+/// The days each habit was kept, by habit name.
+type Checks = BTreeMap<String, BTreeSet<NaiveDate>>;
+
+/// Daily habits from the config, checked off day by day in their own pane rather than living in
+/// the todo list.
+#[derive(Default)]
+pub struct Habits {
+    names: Vec<String>,
+    checks: Checks,
+    visible: bool,
+    row: usize,
+}
+
+/// Where checked-off days are kept, with the global todos, as habits aren't a project's.
+fn path() -> PathBuf {
+    todo::global_file().with_file_name("habits.json")
+}
+
+/// One cell per day, oldest first and ending on `today`.
+fn grid(days: Option<&BTreeSet<NaiveDate>>, today: NaiveDate) -> String {
+    (0..DAYS)
+        .rev()
+        .filter_map(|back| today.checked_sub_days(Days::new(back)))
+        .map(|day| {
+            if days.is_some_and(|days| days.contains(&day)) {
+                '■'
+            } else {
+                '·'
+            }
+        })
+        .collect()
+}
+
+impl Habits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks a day off for the selected habit, or clears it if it was already checked.
+    fn toggle(&mut self, day: NaiveDate) {
+        let Some(name) = self.names.get(self.row) else {
+            return;
+        };
+        let days = self.checks.entry(name.clone()).or_default();
+        if !days.remove(&day) {
+            days.insert(day);
+        }
+    }
+}
+
+impl Component for Habits {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.names = config.habits;
+        self.row = self.row.min(self.names.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.visible {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.row = (self.row + 1).min(self.names.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.row = self.row.saturating_sub(1),
+            KeyCode::Char(' ') => {
+                self.toggle(Local::now().date_naive());
+                utils::write_json(&path(), &self.checks)?;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ToggleHabits => self.visible = !self.visible,
+            // The pane belongs to normal mode, so make way for any other one
+            Action::EnterCommandMode
+            | Action::EnterBrowseMode
+            | Action::EnterHelpMode
+            | Action::EnterTagsMode
+            | Action::EnterProjectsMode
+            | Action::EnterStatsMode
+            | Action::EnterMatrixMode
+            | Action::EnterThemesMode => self.visible = false,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let today = Local::now().date_naive();
+        let width = self.names.iter().map(String::len).max().unwrap_or(0);
+        let rows: Vec<ListItem> = if self.names.is_empty() {
+            vec![ListItem::new(
+                "No habits yet, list some under `habits` in the config",
+            )]
+        } else {
+            self.names
+                .iter()
+                .map(|name| {
+                    ListItem::new(format!(
+                        "{name:width$}  {}",
+                        grid(self.checks.get(name), today)
+                    ))
+                })
+                .collect()
+        };
+        let popup_width = ((width + DAYS as usize + 6) as u16).max(40).min(area.width);
+        let height = (rows.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - popup_width) / 2,
+            area.y + (area.height - height) / 2,
+            popup_width,
+            height,
+        );
+        let mut state = ListState::default().with_selected(Some(self.row));
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(
+            List::new(rows)
+                .block(Block::default().borders(Borders::ALL).title(tr_with(
+                    "Habits, last {days} days (Space checks today off)",
+                    &[("days", &DAYS)],
+                )))
+                .highlight_style(Style::new().on_dark_gray()),
+            popup,
+            &mut state,
+        );
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn buildup(&mut self) -> Result<()> {
+        self.checks = utils::read_json(&path())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_check_off_grid() {
+        let mut habits = Habits::new();
+        habits.names = vec!["stretch".into()];
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        habits.toggle(today);
+        habits.toggle(today - Days::new(2));
+        let line = grid(habits.checks.get("stretch"), today);
+        assert_eq!(line.chars().count(), 30);
+        assert!(line.ends_with("■·■"));
+
+        habits.toggle(today);
+        assert!(grid(habits.checks.get("stretch"), today).ends_with("■··"));
+    }
+}
@@ -1,14 +1,16 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{self, write},
     fs::File,
     io::{BufWriter, Read, Write},
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
 
+use chrono::{DateTime, Local, Utc};
 use clap::builder::Str;
 use color_eyre::eyre::{Ok, Result};
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use log::*;
 use ratatui::{prelude::*, widgets::*};
 use serde::{Deserialize, Serialize};
@@ -16,30 +18,37 @@ use serde_json::Value;
 use strum::Display;
 use tokio::sync::mpsc::UnboundedSender;
 use tui_input::{backend::crossterm::EventHandler, Input};
+use uuid::Uuid;
 
 use super::{Component, Frame};
 use crate::{
     action::Action,
-    config::{Config, KeyBindings},
+    archive, bidi, bulk_edit,
+    config::{parse_style, Config, KeyBindings, Keymap, Styles},
+    crdt::Document,
+    duplicates, highlight,
+    hyperlinks::{self, Target},
+    i18n::{tr, tr_with},
+    images,
+    intern::Name,
+    jira, links, markdown, matrix, stats, streaks,
+    table::{self, Column},
+    tags, template,
+    text::{self, Truncation},
+    themes,
+    todo::{self, ChecklistItem, Comment, EventKind, TodoItem},
     trace_dbg,
+    undo::Changes,
+    urgency, vault,
 };
 
-#[derive(Default, Clone, Serialize, Deserialize)]
-pub struct TodoItem {
-    title: String,
-}
-
-impl TodoItem {
-    pub fn new(title: String) -> Self {
-        Self { title: title }
-    }
-}
-
-impl Into<Text<'_>> for TodoItem {
-    fn into(self) -> Text<'static> {
-        Text::raw(self.title)
-    }
-}
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// How many todos the recently completed panel lists.
+const RECENTLY_COMPLETED: usize = 10;
+/// The time windows the burndown chart in the stats view cycles through, in days.
+const BURNDOWN_WINDOWS: [u64; 3] = [7, 14, 30];
+/// How many operations can be undone.
+const UNDO_LIMIT: usize = 100;
 
 #[derive(Default)]
 enum Mode {
@@ -48,16 +57,29 @@ enum Mode {
     Editing,
     Browse,
     Help,
+    Tags,
+    Projects,
+    History,
+    Stats,
+    Matrix,
+    Themes,
 }
 
 impl fmt::Display for Mode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Mode::Normal => write!(f, "Normal"),
-            Mode::Editing => write!(f, "Editing"),
-            Mode::Browse => write!(f, "Browsing"),
-            Mode::Help => write!(f, "Help"),
-        }
+        let name = match self {
+            Mode::Normal => "Normal",
+            Mode::Editing => "Editing",
+            Mode::Browse => "Browsing",
+            Mode::Help => "Help",
+            Mode::Tags => "Tags",
+            Mode::Projects => "Projects",
+            Mode::History => "History",
+            Mode::Stats => "Stats",
+            Mode::Matrix => "Matrix",
+            Mode::Themes => "Themes",
+        };
+        write!(f, "{}", tr(name))
     }
 }
 
@@ -72,288 +94,3664 @@ pub struct Home {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
     todos: Vec<TodoItem>,
+    /// The replicated document `todos` was derived from, merged with the file on every save.
+    document: Document,
+    /// The documents of the shared files from the config, by name, whose todos are listed too.
+    shared: BTreeMap<String, Document>,
+    /// When the data file and then each shared file were last read or written here, so that
+    /// writes by other processes are picked up while running.
+    synced: Vec<Option<SystemTime>>,
+    /// The list as it was last read from or written to disk, to tell when it has changed since.
+    saved: Vec<TodoItem>,
+    /// Whether the list differs from `saved`, shown as `[+]` next to the mode.
+    dirty: bool,
     input: Input,
     input_mode: Mode,
     cursor_row: i64,
+    /// A short message shown next to the mode indicator, and when it was raised.
+    toast: Option<(String, Instant)>,
+    /// The digits typed after `:`, while a jump to that index is being entered, or `w` to save.
+    jump: Option<String>,
+    /// Text being typed for the selected todo while browsing, and what it is for.
+    prompt: Option<(Prompt, String)>,
+    /// An open todo much like the one being added, shown in a popup before adding another.
+    duplicate_of: Option<Uuid>,
+    /// A save held back because the data file changed on disk since it was last read here.
+    conflict: Option<Conflict>,
+    /// Whether the list is only being browsed, so that nothing may change it.
+    read_only: bool,
+    /// Subtasks entered so far while splitting the selected todo.
+    subtasks: Vec<String>,
+    /// The todo copied with `yy`, pasted after the selection with `p`.
+    yanked: Option<TodoItem>,
+    /// Which attachment or checklist step of the selected todo is picked in the detail pane,
+    /// wrapping around them.
+    detail_row: usize,
+    /// Each operation that changed the list, newest last, with what it changed.
+    undo: Vec<(String, DateTime<Local>, Changes)>,
+    /// The selected row in the undo history, counting back from the newest operation.
+    history_row: usize,
+    /// The selected quadrant in the Eisenhower matrix and the row within it.
+    matrix_quadrant: usize,
+    matrix_row: usize,
+    /// The selected preset in the theme picker.
+    theme_row: usize,
+    /// The styles from before the theme picker opened, put back if it is left without picking.
+    styles_before: Option<Styles>,
+    /// Which of `BURNDOWN_WINDOWS` the stats view charts.
+    burndown_window: usize,
+    /// Whether the pane with everything about the selected todo is showing.
+    show_detail: bool,
+    /// Whether the recently completed panel is showing.
+    show_completed: bool,
+    /// Only todos in this project are listed, and new ones land in it.
+    active_project: Option<String>,
+    /// Only todos in this context are listed, and new ones land in it.
+    active_context: Option<String>,
+    /// Only todos assigned to this person are listed, and new ones are assigned to them.
+    active_assignee: Option<String>,
+    /// The image attachment previewed in the detail pane and the area it fills, as last drawn.
+    image: Option<(PathBuf, Rect)>,
+    /// The links in the list and the detail pane, where they were last drawn.
+    links: Vec<Target>,
+    /// The column the list was last sorted by and whether descending, marked in the table header.
+    sorted_by: Option<(Column, bool)>,
+    /// The selected row on the project switcher, where the first row stands for every project.
+    project_row: usize,
+    /// The name being typed for a new project.
+    new_project: Option<String>,
+    /// The highlighted entry in the tag and project completions while typing a todo.
+    completion_row: usize,
+    /// The selected row on the tags screen.
+    tag_row: usize,
+    /// The new name being typed for the selected tag.
+    renaming: Option<String>,
+    /// Named marks, held by id so they follow their todo as the list moves around.
+    marks: HashMap<char, Uuid>,
+    /// `q`, `@`, `d`, `m` or `'`, with its count, while waiting for the key that completes it.
+    pending: Option<(char, usize)>,
+    /// Digits typed before a command, such as the 3 in `3@a`.
+    count: Option<usize>,
+    /// The register being recorded into and the actions captured so far, with their counts.
+    recording: Option<(char, Vec<(Action, usize)>)>,
+    registers: HashMap<char, Vec<(Action, usize)>>,
+    /// The last action that changed a todo and its count, repeated on the selection by `.`.
+    last_change: Option<(Action, usize)>,
+    /// The file handed to the editor and what to do with it once saved.
+    external_edit: Option<(PathBuf, ExternalEdit)>,
+}
+
+/// How much of a todo's checklist is ticked off, such as `[2/5]`.
+fn progress(todo: &TodoItem) -> String {
+    let done = todo.checklist.iter().filter(|step| step.done).count();
+    format!("[{done}/{}]", todo.checklist.len())
+}
+
+/// What text typed while browsing is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+enum Prompt {
+    /// A title for a pasted copy of the yanked todo
+    #[strum(serialize = "paste as")]
+    PasteAs,
+    /// A path or link to attach
+    Attach,
+    /// A checklist step to add
+    Step,
+    /// A comment to leave
+    Comment,
+    /// One more subtask to split the todo into, after those in `subtasks`
+    Subtask,
+}
+
+/// A save waiting on the user, as the data file was written by someone else in the meantime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Conflict {
+    /// Whether to quit once it is settled.
+    quit: bool,
+    /// The path being typed, after choosing to save elsewhere.
+    save_as: Option<String>,
+}
+
+/// What a file open in the editor is being edited for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExternalEdit {
+    /// The listed todos, by id, as in `bulk_edit`.
+    Bulk(Vec<Uuid>),
+    /// The notes of one todo.
+    Notes(Uuid),
 }
 
 impl Home {
     pub fn new() -> Self {
         Self::default()
     }
-}
 
-impl Component for Home {
-    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
-        self.command_tx = Some(tx);
-        Ok(())
+    /// A list of `todos` to browse only, never written anywhere.
+    pub fn viewer(todos: Vec<TodoItem>) -> Self {
+        Self {
+            todos,
+            read_only: true,
+            input_mode: Mode::Browse,
+            ..Self::default()
+        }
     }
 
-    fn register_config_handler(&mut self, config: Config) -> Result<()> {
-        self.config = config;
-        Ok(())
+    /// A list of `todos` that was never loaded from or saved to a file, for benchmarks.
+    pub fn with_todos(todos: Vec<TodoItem>) -> Self {
+        Self {
+            todos,
+            ..Self::default()
+        }
     }
 
-    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
-        let action = match self.input_mode {
-            Mode::Normal => match key.code {
-                KeyCode::Char('i') => Action::EnterCommandMode,
-                KeyCode::Char('v') => Action::EnterBrowseMode,
-                KeyCode::Char('h') => Action::EnterHelpMode,
-                _ => return Ok(None),
-            },
-            Mode::Editing => match key.code {
-                KeyCode::Enter => {
-                    if let Some(sender) = &self.command_tx {
-                        if let Err(e) = sender.send(Action::AddTodo) {
-                            error!("Failed to send action: {:?}", e);
-                        }
-                    }
-                    Action::ExitCurrentMode
+    /// A style from the config, or `fallback` when the theme leaves it out.
+    fn style(&self, name: &str, fallback: Style) -> Style {
+        self.config
+            .styles
+            .get(&crate::mode::Mode::Home)
+            .and_then(|styles| styles.get(name))
+            .copied()
+            .unwrap_or(fallback)
+    }
+
+    /// What a key does while browsing, apart from counts and macros.
+    fn browse_action(&self, key: KeyEvent) -> Option<Action> {
+        let action = match (self.config.keymap, key.code) {
+            (_, KeyCode::Down) => Action::BrowseListDown,
+            (_, KeyCode::Up) => Action::BrowseListUp,
+            (Keymap::Vim, KeyCode::Char('j')) => Action::BrowseListDown,
+            (Keymap::Vim, KeyCode::Char('k')) => Action::BrowseListUp,
+            (Keymap::Emacs, KeyCode::Char('n'))
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::BrowseListDown
+            }
+            (Keymap::Emacs, KeyCode::Char('p'))
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::BrowseListUp
+            }
+            (_, KeyCode::Char('x')) => Action::CompleteTodo(self.selected()),
+            (_, KeyCode::Char('s')) => Action::ToggleStar(self.selected()),
+            (_, KeyCode::Char('c')) => Action::ToggleCompletedPanel,
+            (_, KeyCode::Enter) => Action::ToggleDetailPane,
+            (_, KeyCode::Char('N')) if self.show_detail => Action::EditNotes(self.selected()),
+            (_, KeyCode::Char('O')) if self.show_detail => {
+                let todo = self.todos.get(self.selected())?;
+                let attachment = todo.attachments.get(self.detail_row(todo)?)?;
+                if let Some(project) = attachment.strip_prefix(tags::PROJECT_LINK) {
+                    return Some(Action::OpenProject(project.to_string()));
                 }
-                _ => {
-                    self.input.handle_event(&crossterm::event::Event::Key(key));
-                    Action::Refresh
+                if let Err(e) = open::that_detached(attachment) {
+                    return Some(Action::Error(format!("Could not open {attachment}: {e}")));
                 }
-            },
-            Mode::Browse => match key.code {
-                KeyCode::Char('j') => Action::BrowseListDown,
-                KeyCode::Char('k') => Action::BrowseListUp,
-                _ => return Ok(None),
-            },
-            Mode::Help => match key.code {
-                KeyCode::Char('h') => Action::ExitCurrentMode,
-                _ => return Ok(None),
-            },
+                return None;
+            }
+            (_, KeyCode::Char(' ')) if self.show_detail => {
+                let todo = self.todos.get(self.selected())?;
+                let step = self.detail_row(todo)?.checked_sub(todo.attachments.len())?;
+                Action::ToggleChecklistStep(self.selected(), step)
+            }
+            (_, KeyCode::Char('p')) => Action::PasteTodo(None),
+            (_, KeyCode::Char('+')) => Action::PromoteToProject(self.selected()),
+            (_, KeyCode::Char('u')) => Action::Undo(1),
+            (_, KeyCode::Char('H')) => Action::EnterHistoryMode,
+            (_, KeyCode::Char('U')) => {
+                let latest = self.recently_completed().first().copied()?;
+                Action::ReopenTodo(latest)
+            }
+            (_, KeyCode::Char('o')) => {
+                // The todo's own link first, then any written into its title or notes
+                let url = self.todos.get(self.selected()).and_then(|todo| {
+                    todo.url.as_deref().or_else(|| {
+                        links::find(&todo.title)
+                            .chain(todo.notes.iter().flat_map(|notes| links::find(notes)))
+                            .next()
+                    })
+                });
+                if let Some(url) = url {
+                    if let Err(e) = open::that_detached(url) {
+                        error!("Failed to open {}: {:?}", url, e);
+                    }
+                }
+                return None;
+            }
+            _ => return None,
         };
-        Ok(Some(action))
+        Some(action)
     }
 
-    fn buildup(&mut self) -> Result<()> {
-        let file = File::open("./.data/home.json");
-
-        match file {
-            serde::__private::Ok(_) => {
-                let mut buffer = String::new();
-                file?.read_to_string(&mut buffer)?;
-                let v: Vec<TodoItem> = serde_json::from_str(&buffer)?;
-
-                if v.len() > 0 {
-                    for todo_item in v.iter() {
-                        let new_todo: TodoItem = TodoItem::new(todo_item.title.to_string());
-                        self.todos.push(new_todo);
-                    }
-                }
+    /// Where in `todos` each listed row is, leaving out those in other projects.
+    fn visible(&self) -> Vec<usize> {
+        (0..self.todos.len())
+            .filter(|&i| {
+                (self.active_project.is_none()
+                    || self.todos[i].project.as_deref() == self.active_project.as_deref())
+                    && (self.active_context.is_none()
+                        || self.todos[i].context.as_deref() == self.active_context.as_deref())
+                    && (self.active_assignee.is_none()
+                        || self.todos[i].assignee.as_deref() == self.active_assignee.as_deref())
+            })
+            .collect()
+    }
 
-                Ok(())
-            }
-            Err(_) => return Ok(()),
-        }
+    /// The index in `todos` of a listed row, or one past the end when there is no such row.
+    fn row_index(&self, row: usize) -> usize {
+        self.visible().get(row).copied().unwrap_or(self.todos.len())
     }
 
-    fn teardown(&mut self) -> Result<()> {
-        let file: File = File::create("./.data/home.json")?;
-        let mut writer: BufWriter<File> = BufWriter::new(file);
-        serde_json::to_writer(&mut writer, &self.todos)?;
-        writer.flush()?;
-        Ok(())
+    /// The index in `todos` of the selected row.
+    fn selected(&self) -> usize {
+        self.row_index(self.cursor_row as usize)
     }
 
-    fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        match self.input_mode {
-            Mode::Normal => match action {
-                Action::EnterCommandMode => {
-                    self.input_mode = Mode::Editing;
-                }
-                Action::EnterBrowseMode => {
-                    self.input_mode = Mode::Browse;
-                }
-                Action::EnterHelpMode => {
-                    self.input_mode = Mode::Help;
-                }
-                _ => {}
-            },
-            Mode::Editing => match action {
-                Action::ExitCurrentMode => {
-                    self.input_mode = Mode::Normal;
-                }
-                Action::AddTodo => {
-                    let new_todo: TodoItem = TodoItem::new(self.input.value().into());
-                    self.input.reset();
-                    self.todos.push(new_todo);
-                    self.input_mode = Mode::Editing;
-                }
-                _ => {}
-            },
-            Mode::Browse => match action {
-                Action::ExitCurrentMode => {
-                    self.input_mode = Mode::Normal;
-                }
-                Action::BrowseListUp => {
-                    self.cursor_row -= 1;
-                    self.cursor_row = self.cursor_row.max(0);
-                }
-                Action::BrowseListDown => {
-                    self.cursor_row += 1;
-                    self.cursor_row = self.cursor_row.min((self.todos.len() as i64) - 1);
-                }
-                _ => {}
-            },
-            Mode::Help => match action {
-                Action::ExitCurrentMode => {
-                    self.input_mode = Mode::Normal;
+    /// Moves the cursor onto the row showing the todo with `id`, if it is listed.
+    fn select_id(&mut self, id: Uuid) -> Option<usize> {
+        let row = self
+            .visible()
+            .into_iter()
+            .position(|i| self.todos[i].id == id)?;
+        self.cursor_row = row as i64;
+        Some(row)
+    }
+
+    /// Keeps the cursor on a listed row after the list shrinks.
+    fn clamp_cursor(&mut self) {
+        let rows = self.visible().len() as i64;
+        self.cursor_row = self.cursor_row.min(rows - 1).max(0);
+    }
+
+    /// What an action would change, for the undo history, or nothing if it leaves the list be.
+    fn describe(&self, action: &Action) -> Option<String> {
+        let title = |index: &usize| self.todos.get(*index).map(|todo| todo.title.clone());
+        // The change made to the todo at `index`, told by a message with its `{title}`
+        let to = |text, index: &usize| Some(tr_with(text, &[("title", &title(index)?)]));
+        match action {
+            Action::InsertTodo(raw) => Some(tr_with("Add \"{title}\"", &[("title", raw)])),
+            Action::AddTodo if self.input_mode == Mode::Editing => Some(tr_with(
+                "Add \"{title}\"",
+                &[("title", &self.input.value())],
+            )),
+            Action::CompleteTodo(index) => to("Complete \"{title}\"", index),
+            Action::ReopenTodo(index) => to("Reopen \"{title}\"", index),
+            Action::DeleteTodo(index) => to("Delete \"{title}\"", index),
+            Action::ToggleStar(index) => to("Star \"{title}\"", index),
+            Action::MoveToQuadrant(index, quadrant) => Some(tr_with(
+                "Move \"{title}\" to {quadrant}",
+                &[
+                    ("title", &title(index)?),
+                    ("quadrant", matrix::QUADRANTS.get(*quadrant)?),
+                ],
+            )),
+            Action::SortByUrgency => Some(tr("Sort by urgency").to_string()),
+            Action::CycleSort => Some(tr("Sort by the next column").to_string()),
+            Action::Attach(index, attachment) => Some(tr_with(
+                "Attach {attachment} to \"{title}\"",
+                &[("attachment", attachment), ("title", &title(index)?)],
+            )),
+            Action::Comment(index, _) => to("Comment on \"{title}\"", index),
+            Action::PromoteToProject(index) => to("Promote \"{title}\"", index),
+            Action::SplitTodo(index, subtasks) => Some(tr_with(
+                "Split \"{title}\" into {count} subtasks",
+                &[("title", &title(index)?), ("count", &subtasks.len())],
+            )),
+            Action::PasteTodo(title) => {
+                let yanked = &self.yanked.as_ref()?.title;
+                Some(tr_with(
+                    "Paste \"{title}\"",
+                    &[("title", title.as_ref().unwrap_or(yanked))],
+                ))
+            }
+            Action::AddChecklistStep(index, text) => Some(tr_with(
+                "Add \"{step}\" to \"{title}\"",
+                &[("step", text), ("title", &title(index)?)],
+            )),
+            Action::ToggleChecklistStep(index, step) => {
+                let todo = self.todos.get(*index)?;
+                Some(tr_with(
+                    "Tick \"{step}\"",
+                    &[("step", &todo.checklist.get(*step)?.text)],
+                ))
+            }
+            Action::EditorClosed(_) => match &self.external_edit {
+                Some((_, ExternalEdit::Bulk(_))) => Some(tr("Bulk edit").to_string()),
+                Some((_, ExternalEdit::Notes(id))) => {
+                    let todo = self.todos.iter().find(|todo| todo.id == *id)?;
+                    Some(tr_with(
+                        "Edit notes of \"{title}\"",
+                        &[("title", &todo.title)],
+                    ))
                 }
-                _ => {}
+                None => None,
             },
+            Action::RenameTag(from, to) => Some(tr_with(
+                "Rename #{from} to #{to}",
+                &[("from", from), ("to", to)],
+            )),
+            Action::DeleteTag(tag) => Some(tr_with("Delete #{tag}", &[("tag", tag)])),
+            _ => None,
         }
-        Ok(None)
     }
 
-    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        // Helper function for drawing the help box
-        fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
-            let popup_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Percentage((100 - percent_y) / 2),
-                    Constraint::Percentage(percent_y),
-                    Constraint::Percentage((100 - percent_y) / 2),
-                ])
-                .split(r);
+    /// Days in a row with a completion, when streaks are switched on.
+    fn streak(&self) -> Option<u32> {
+        self.config.streaks.as_ref()?;
+        Some(streaks::current(&self.todos, Local::now().date_naive()))
+    }
 
-            Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage((100 - percent_x) / 2),
-                    Constraint::Percentage(percent_x),
-                    Constraint::Percentage((100 - percent_x) / 2),
-                ])
-                .split(popup_layout[1])[1]
+    /// Carries whether `todo` is done back to where it came from: the checkbox in the vault it
+    /// was read from and the Jira issue it was fetched from.
+    fn write_back(&self, todo: &TodoItem) -> Result<()> {
+        if let Some(vault) = &self.config.vault {
+            if let Err(e) = vault::write_done(&vault.dir, todo) {
+                error!("Failed to update {:?}: {:?}", todo.source, e);
+            }
+        }
+        let issue = self
+            .config
+            .jira
+            .as_ref()
+            .and_then(|jira_config| jira::issue_key(jira_config, todo));
+        if let (Some(tx), Some(key)) = (&self.command_tx, issue) {
+            let key = key.to_string();
+            tx.send(if todo.done {
+                Action::CompleteJiraIssue(key)
+            } else {
+                Action::ReopenJiraIssue(key)
+            })?;
         }
+        Ok(())
+    }
 
-        if self.input_mode == Mode::Help {
-            f.render_widget(
-                Block::default().borders(Borders::all()).title("Help Menu"),
-                centered_rect(f.size(), 35, 35),
-            );
+    /// Raises a toast when a completion just took the streak to a milestone.
+    fn celebrate(&mut self, before: Option<u32>) {
+        let (Some(before), Some(after), Some(config)) =
+            (before, self.streak(), &self.config.streaks)
+        else {
+            return;
+        };
+        if after > before && config.milestones.contains(&after) {
+            self.toast = Some((
+                tr_with("{days}-day streak, keep it going!", &[("days", &after)]),
+                Instant::now(),
+            ));
         }
+    }
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(2)
-            .constraints(
-                [
-                    Constraint::Min(5),
-                    Constraint::Length(1),
-                    Constraint::Length(3),
-                    Constraint::Max(1),
-                ]
-                .as_ref(),
-            )
-            .split(f.size());
+    /// The links in `todo`'s details drawn in `area`: the web links in its text and its
+    /// attachments, files opening from their `file://` URLs.
+    fn link_targets(todo: &TodoItem, area: Rect) -> Vec<Target> {
+        let text = [Some(&todo.title), todo.url.as_ref(), todo.notes.as_ref()]
+            .into_iter()
+            .flatten()
+            .chain(todo.comments.iter().map(|comment| &comment.text));
+        let web = text
+            .flat_map(|text| links::find(text))
+            .map(|url| (url.to_string(), url.to_string()));
+        let attachments = todo.attachments.iter().filter_map(|attachment| {
+            let url = match links::url(attachment) {
+                Some(url) => url.to_string(),
+                None => hyperlinks::file_url(attachment)?,
+            };
+            Some((attachment.clone(), url))
+        });
+        web.chain(attachments)
+            .map(|(text, url)| Target { area, text, url })
+            .collect()
+    }
 
-        let (msg, style) = match self.input_mode {
-            Mode::Normal => (
-                vec![
-                    Span::raw("Press "),
-                    Span::styled("CTRL+C", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to exit, "),
-                    Span::styled("i", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to insert todo."),
-                ],
-                Style::default().add_modifier(Modifier::RAPID_BLINK),
-            ),
-            Mode::Editing => (
-                vec![
-                    Span::raw("Press "),
-                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to stop editing, "),
-                    Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to record the todo"),
-                ],
-                Style::default(),
+    /// Everything about a todo for the detail pane, ending with its history.
+    fn detail(&self, todo: &TodoItem) -> Text<'static> {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![Line::styled(todo.title.clone(), bold)];
+        let fields = [
+            (tr("Project"), todo.project.as_deref().map(str::to_string)),
+            (tr("Assignee"), todo.assignee.as_deref().map(str::to_string)),
+            (tr("File"), todo.origin.clone()),
+            (
+                tr("Tags"),
+                Some(todo.tags.join(", ")).filter(|tags| !tags.is_empty()),
             ),
-            Mode::Browse => (
-                vec![
-                    Span::raw("Press "),
-                    Span::styled("j", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to scroll down, "),
-                    Span::styled("k", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to scroll up, "),
-                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(" to exit browse mode "),
-                ],
-                Style::default(),
+            (tr("Due"), todo.due.map(|due| due.to_string())),
+            (
+                tr("Urgency"),
+                (!todo.done).then(|| {
+                    format!(
+                        "{:.1}",
+                        urgency::score(todo, Local::now().date_naive(), &self.config.urgency,)
+                    )
+                }),
             ),
-            Mode::Help => (vec![], Style::default()),
-        };
+            (tr("Link"), todo.url.clone()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{name}: "), bold),
+                    Span::raw(value),
+                ]));
+            }
+        }
+        let selected = self.detail_row(todo);
+        let marker = |i: usize| if Some(i) == selected { "▸ " } else { "  " };
+        if !todo.attachments.is_empty() {
+            lines.push(Line::styled("Attachments:", bold));
+            lines.extend(
+                todo.attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, attachment)| Line::raw(format!("{}{attachment}", marker(i)))),
+            );
+        }
+        if !todo.checklist.is_empty() {
+            lines.push(Line::styled(format!("Checklist {}:", progress(todo)), bold));
+            lines.extend(todo.checklist.iter().enumerate().map(|(i, step)| {
+                let check = if step.done { 'x' } else { ' ' };
+                let i = todo.attachments.len() + i;
+                Line::raw(format!("{}- [{check}] {}", marker(i), step.text))
+            }));
+        }
+        if let Some(notes) = &todo.notes {
+            lines.push(Line::styled("Notes:", bold));
+            let styles = markdown::Styles {
+                words: &|text, style| self.words(text, style),
+                link: self.style(
+                    "link",
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::UNDERLINED),
+                ),
+                code: self.style("code", Style::default().fg(Color::Cyan)),
+                code_theme: highlight::theme(
+                    self.config.code_theme.as_deref(),
+                    self.config.theme.as_deref(),
+                ),
+            };
+            lines.extend(markdown::lines(notes, &styles));
+        }
+        if !todo.comments.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Comments", bold));
+            for comment in &todo.comments {
+                let at = comment.at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+                let mut spans = vec![Span::raw(format!("{at} "))];
+                spans.extend(self.words(&comment.text, Style::default()));
+                lines.push(Line::from(spans));
+            }
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("History", bold));
+        for event in todo.history.iter().rev() {
+            let at = event.at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            lines.push(Line::raw(format!("{at} {}", event.kind)));
+        }
+        Text::from(lines)
+    }
 
-        let mut text = Text::from(Line::from(msg));
-        text.patch_style(style);
-        let help_message = Paragraph::new(text);
-        f.render_widget(help_message, chunks[1]);
+    /// Indices of completed todos, most recently completed first.
+    fn recently_completed(&self) -> Vec<usize> {
+        let mut done: Vec<usize> = (0..self.todos.len())
+            .filter(|&i| self.todos[i].done)
+            .collect();
+        done.sort_by_key(|&i| {
+            let todo = &self.todos[i];
+            std::cmp::Reverse(todo.completed.unwrap_or(todo.updated))
+        });
+        done.truncate(RECENTLY_COMPLETED);
+        done
+    }
 
-        let width = chunks[1].width.max(3) - 3; // keep 2 for borders and 1 for cursor
+    /// The picked row among a todo's attachments followed by its checklist steps, if it has any.
+    fn detail_row(&self, todo: &TodoItem) -> Option<usize> {
+        let rows = todo.attachments.len() + todo.checklist.len();
+        (rows > 0).then(|| self.detail_row % rows)
+    }
 
-        let scroll = self.input.visual_scroll(width as usize);
-        let input = Paragraph::new(self.input.value())
-            .style(match self.input_mode {
-                Mode::Normal | Mode::Browse | Mode::Help => Style::default(),
-                Mode::Editing => Style::default().fg(Color::Yellow),
-            })
-            .scroll((0, scroll as u16))
-            .block(Block::default().borders(Borders::ALL).title("Input"));
-        f.render_widget(input, chunks[2]);
+    /// The image attachment of `todo` to preview: the one picked in the detail pane, or else the
+    /// first. Files that aren't there are left out.
+    fn preview(&self, todo: &TodoItem) -> Option<String> {
+        let previewable = |attachment: &&String| {
+            images::is_image(attachment) && Path::new(attachment.as_str()).is_file()
+        };
+        self.detail_row(todo)
+            .and_then(|row| todo.attachments.get(row))
+            .filter(previewable)
+            .or_else(|| todo.attachments.iter().find(previewable))
+            .cloned()
+    }
 
-        match self.input_mode {
-            Mode::Normal | Mode::Browse | Mode::Help =>
-                // Hide the cursor. `Frame` does this by default, so we don't need to do anything here
-                {}
+    /// How many parents up the todo's subtask chain goes, stopping at a missing parent.
+    fn depth(&self, todo: &TodoItem) -> usize {
+        let mut depth = 0;
+        let mut parent = todo.parent;
+        while let Some(todo) = parent.and_then(|id| self.todos.iter().find(|t| t.id == id)) {
+            depth += 1;
+            // A parent chain that loops after a bad merge is cut off here
+            if depth > self.todos.len() {
+                break;
+            }
+            parent = todo.parent;
+        }
+        depth
+    }
 
-            Mode::Editing => {
-                // Make the cursor visible and ask tui-rs to put it at the specified coordinates after rendering
-                f.set_cursor(
-                    // Put cursor past the end of the input text
-                    chunks[2].x + ((self.input.visual_cursor()).max(scroll) - scroll) as u16 + 1,
-                    // Move one line down, from the border to the input line
-                    chunks[2].y + 1,
-                )
+    /// Whether the todo is a subtask of `ancestor`, however deep.
+    fn is_under(&self, todo: &TodoItem, ancestor: Uuid) -> bool {
+        let mut parent = todo.parent;
+        for _ in 0..self.todos.len() {
+            match parent {
+                Some(id) if id == ancestor => return true,
+                Some(id) => {
+                    parent = self
+                        .todos
+                        .iter()
+                        .find(|t| t.id == id)
+                        .and_then(|t| t.parent)
+                }
+                None => return false,
             }
         }
+        false
+    }
 
-        // Creates the todo list
-        let todos: Vec<ListItem> = self
-            .todos
-            .iter()
-            .enumerate()
-            .map(|(i, m)| {
-                let content = vec![Line::from(Span::raw(format!("{}: {}", i, m.title)))];
-                ListItem::new(content)
-            })
-            .collect();
-        let todos = List::new(todos)
-            .block(Block::default().borders(Borders::ALL).title("Todo's"))
-            .highlight_style(Style::new().on_dark_gray())
-            .highlight_spacing(HighlightSpacing::Always)
-            .highlight_symbol(">>");
-        let mut state = ListState::default();
+    /// Points an action that changes a todo at the selected one instead.
+    fn at_cursor(&self, action: Action) -> Action {
+        match action {
+            Action::CompleteTodo(_) => Action::CompleteTodo(self.selected()),
+            Action::DeleteTodo(_) => Action::DeleteTodo(self.selected()),
+            Action::ToggleStar(_) => Action::ToggleStar(self.selected()),
+            action => action,
+        }
+    }
 
-        match self.input_mode {
-            Mode::Editing | Mode::Normal | Mode::Help => {
-                state.select(None);
+    /// Replays the actions recorded under `register`, `times` times over. Actions that act on
+    /// the selected todo follow the cursor as the replay moves it.
+    fn replay(&mut self, register: char, times: usize) -> Result<Option<Action>> {
+        let Some(actions) = self.registers.get(&register).cloned() else {
+            return Ok(Some(Action::Toast(tr_with(
+                "Nothing recorded in @{register}",
+                &[("register", &register)],
+            ))));
+        };
+        for _ in 0..times {
+            for (action, count) in &actions {
+                self.repeat(action.clone(), *count)?;
             }
-            Mode::Browse => {
-                state.select(Some(self.cursor_row as usize));
+        }
+        Ok(None)
+    }
+
+    /// Applies `action` to the selection `count` times straight away, passing on what follows
+    /// from it. Completing covers the todos below the selection, while everything else is simply
+    /// done again from wherever the previous step left the cursor.
+    fn repeat(&mut self, action: Action, count: usize) -> Result<()> {
+        for step in 0..count {
+            let action = match self.at_cursor(action.clone()) {
+                Action::CompleteTodo(_) => {
+                    Action::CompleteTodo(self.row_index(self.cursor_row as usize + step))
+                }
+                action => action,
+            };
+            if let (Some(followup), Some(tx)) = (self.update(action)?, &self.command_tx) {
+                tx.send(followup)?;
             }
         }
+        Ok(())
+    }
+
+    /// Carries out a browsing command typed with `count`, remembering it for `.` and the macro
+    /// being recorded.
+    fn perform(&mut self, action: Action, count: usize) -> Result<Option<Action>> {
+        let action = self.at_cursor(action);
+        if matches!(
+            action,
+            Action::CompleteTodo(_)
+                | Action::DeleteTodo(_)
+                | Action::ToggleStar(_)
+                | Action::PasteTodo(_)
+        ) {
+            self.last_change = Some((action.clone(), count));
+        }
+        if let Some((_, actions)) = &mut self.recording {
+            actions.push((action.clone(), count));
+        }
+        if count == 1 {
+            return Ok(Some(action));
+        }
+        self.repeat(action, count)?;
+        Ok(None)
+    }
 
-        f.render_stateful_widget(todos, chunks[0], &mut state);
+    /// Moves through the theme picker, previewing each preset, and keeps the one picked with
+    /// Enter.
+    fn themes_key(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let names = themes::names();
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.theme_row = (self.theme_row + 1).min(names.len() - 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.theme_row = self.theme_row.saturating_sub(1),
+            KeyCode::Enter => {
+                let name = names[self.theme_row];
+                themes::save(&self.config.config._config_dir, name)?;
+                self.config.theme = Some(name.to_string());
+                self.styles_before = None;
+                self.input_mode = Mode::Normal;
+                return Ok(None);
+            }
+            _ => return Ok(None),
+        }
+        self.preview_theme();
+        Ok(None)
+    }
 
-        let mode_indicator_text = self.input_mode.to_string();
-        let mode_indicator_widget = Paragraph::new(Text::from(Line::from(mode_indicator_text)));
-        f.render_widget(mode_indicator_widget, chunks[3]);
+    /// Shows the selected preset over the styles from before the picker opened.
+    fn preview_theme(&mut self) {
+        if let Some(styles) = &self.styles_before {
+            self.config.styles = styles.clone();
+        }
+        themes::apply(&mut self.config.styles, themes::names()[self.theme_row]);
+    }
 
-        // Return OK
+    /// What a key does on the tags screen. Moving and coloring happen here, while renames and
+    /// deletes go out as actions since they change todos.
+    fn tags_key(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let tags = tags::counts(&self.todos);
+        let Some((tag, _)) = tags.get(self.tag_row) else {
+            return Ok(None);
+        };
+        if let Some(name) = &mut self.renaming {
+            match key.code {
+                KeyCode::Char(' ') => {}
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter => {
+                    let name = self.renaming.take().unwrap_or_default();
+                    if !name.is_empty() && name != *tag {
+                        return Ok(Some(Action::RenameTag(tag.clone(), name)));
+                    }
+                }
+                _ => self.renaming = None,
+            }
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.tag_row = (self.tag_row + 1).min(tags.len() - 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.tag_row = self.tag_row.saturating_sub(1),
+            KeyCode::Char('r') => self.renaming = Some(String::new()),
+            KeyCode::Char('d') => return Ok(Some(Action::DeleteTag(tag.clone()))),
+            KeyCode::Char('c') => {
+                let colors = &mut self.config.tag_colors;
+                match tags::next_color(colors.get(tag).map(String::as_str)) {
+                    Some(color) => colors.insert(tag.clone(), color.to_string()),
+                    None => colors.remove(tag),
+                };
+                tags::save_colors(&self.config.config._config_dir, colors)?;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Open listed todos in each quadrant of the Eisenhower matrix, as indices into `todos`.
+    fn matrix(&self) -> [Vec<usize>; 4] {
+        let today = Local::now().date_naive();
+        let mut quadrants: [Vec<usize>; 4] = Default::default();
+        for i in self.visible() {
+            let todo = &self.todos[i];
+            if todo.done {
+                continue;
+            }
+            let quadrant = matrix::quadrant(todo, today);
+            if let Some(q) = matrix::QUADRANTS.iter().position(|q| *q == quadrant) {
+                quadrants[q].push(i);
+            }
+        }
+        quadrants
+    }
+
+    /// What a key does in the Eisenhower matrix. The number keys move the selected todo to that
+    /// quadrant.
+    fn matrix_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let quadrants = self.matrix();
+        let rows = quadrants[self.matrix_quadrant].len();
+        match key.code {
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.matrix_quadrant = (self.matrix_quadrant + 1) % 4;
+                self.matrix_row = 0;
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.matrix_quadrant = (self.matrix_quadrant + 3) % 4;
+                self.matrix_row = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.matrix_row = (self.matrix_row + 1).min(rows.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.matrix_row = self.matrix_row.saturating_sub(1),
+            KeyCode::Char(digit @ '1'..='4') => {
+                let index = *quadrants[self.matrix_quadrant].get(self.matrix_row)?;
+                let quadrant = digit.to_digit(10)? as usize - 1;
+                self.matrix_row = self.matrix_row.min(rows.saturating_sub(2));
+                return Some(Action::MoveToQuadrant(index, quadrant));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// What a key does on the project switcher.
+    fn projects_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let projects = tags::projects(&self.todos);
+        if let Some(name) = &mut self.new_project {
+            match key.code {
+                KeyCode::Char(' ') => {}
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter => {
+                    let name = self.new_project.take().filter(|name| !name.is_empty())?;
+                    return Some(self.switch_project(Some(name)));
+                }
+                _ => self.new_project = None,
+            }
+            return None;
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.project_row = (self.project_row + 1).min(projects.len());
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.project_row = self.project_row.saturating_sub(1);
+            }
+            KeyCode::Char('n') => self.new_project = Some(String::new()),
+            KeyCode::Enter => {
+                let project = self
+                    .project_row
+                    .checked_sub(1)
+                    .and_then(|i| projects.get(i))
+                    .map(|(project, _)| project.clone());
+                return Some(self.switch_project(project));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn switch_project(&mut self, project: Option<String>) -> Action {
+        self.active_project = project;
+        self.cursor_row = 0;
+        Action::ExitCurrentMode
+    }
+
+    /// Tags or projects finishing the word at the end of the input.
+    fn completions(&self) -> Vec<String> {
+        let word = self.input.value().rsplit(' ').next().unwrap_or_default();
+        tags::complete(&self.todos, word)
+    }
+
+    /// The color set for a tag on the tags screen, if any.
+    fn tag_style(&self, tag: &str) -> Option<Style> {
+        self.config
+            .tag_colors
+            .get(tag)
+            .map(|color| parse_style(color))
+    }
+
+    /// Splits text into one span per word, coloring tags and links.
+    fn words(&self, text: &str, style: Style) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for (j, word) in text.split(' ').enumerate() {
+            if j > 0 {
+                spans.push(Span::styled(" ", style));
+            }
+            let word_style = if links::url(word).is_some() {
+                style.patch(
+                    self.style(
+                        "link",
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::UNDERLINED),
+                    ),
+                )
+            } else {
+                word.strip_prefix('#')
+                    .and_then(|tag| self.tag_style(tag))
+                    .map_or(style, |tag_style| style.patch(tag_style))
+            };
+            spans.push(Span::styled(word.to_string(), word_style));
+        }
+        spans
+    }
+
+    /// Moves starred todos above the rest, keeping the order within each group.
+    fn pin_starred(&mut self) {
+        self.todos.sort_by_key(|todo| !todo.starred);
+    }
+
+    /// The todos of the data file followed by those of each shared file.
+    fn merged(&self) -> Vec<TodoItem> {
+        let mut todos = self.document.todos();
+        for (name, document) in &self.shared {
+            todos.extend(document.todos().into_iter().map(|todo| TodoItem {
+                origin: Some(name.clone()),
+                ..todo
+            }));
+        }
+        todos
+    }
+
+    /// Records the list in the documents of the files its todos came from.
+    fn record(&mut self, at: DateTime<Utc>) {
+        self.document
+            .record(&todo::from_origin(&self.todos, None), at);
+        for (name, document) in &mut self.shared {
+            document.record(&todo::from_origin(&self.todos, Some(name)), at);
+        }
+    }
+
+    /// When `path`, taken as the data file, and each shared file were last written.
+    fn stamps(&self, path: &Path) -> Vec<Option<SystemTime>> {
+        std::iter::once(todo::modified(path))
+            .chain(self.config.shared_files.values().map(|p| todo::modified(p)))
+            .collect()
+    }
+
+    /// Merges in the data file and the shared files if another process wrote to any of them
+    /// since they were last read or written here, keeping every change made on both sides.
+    fn reload(&mut self, path: &Path) -> Result<Option<Action>> {
+        let stamps = self.stamps(path);
+        if stamps.iter().all(Option::is_none) || stamps == self.synced {
+            return Ok(None);
+        }
+        self.record(Utc::now());
+        self.document.join(&todo::load_document(path)?);
+        for (name, document) in &mut self.shared {
+            if let Some(path) = self.config.shared_files.get(name) {
+                document.join(&todo::load_document(path)?);
+            }
+        }
+        self.todos = self.merged();
+        self.pin_starred();
+        // Changes from elsewhere are on disk already, unlike any made here that are still unsaved
+        if !self.dirty {
+            self.saved = self.todos.clone();
+        }
+        self.synced = stamps;
+        Ok(self.snapshot())
+    }
+
+    /// The marker in front of the selected row of any list, when it needs more than a highlight.
+    fn selection_symbol(&self) -> Option<&'static str> {
+        self.config.high_contrast.then_some("██▶ ")
+    }
+
+    /// The mode and, while browsing, which todo is selected, spelled out for screen readers.
+    fn announcement(&self) -> String {
+        let mode = tr_with("{mode} mode", &[("mode", &self.input_mode)]);
+        match self.todos.get(self.selected()) {
+            Some(todo) if self.input_mode == Mode::Browse => {
+                let position = tr_with(
+                    "todo {n} of {total}",
+                    &[
+                        ("n", &(self.cursor_row + 1)),
+                        ("total", &self.visible().len()),
+                    ],
+                );
+                let done = if todo.done { tr(", done") } else { "" };
+                format!("{mode}, {position}: {todo}{done}")
+            }
+            _ => mode,
+        }
+    }
+
+    /// Whether the data file was written by someone else since it was last read or written here.
+    fn changed_on_disk(&self) -> bool {
+        self.synced
+            .first()
+            .is_some_and(|synced| *synced != todo::modified(&todo::data_file()))
+    }
+
+    /// Settles a save held back by a change on disk: merging keeps the changes of both sides,
+    /// overwriting keeps only the list here, and saving elsewhere leaves the data file as the
+    /// other writer left it.
+    fn conflict_key(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let Some(conflict) = &mut self.conflict else {
+            return Ok(None);
+        };
+        let path = todo::data_file();
+        let Some(target) = &mut conflict.save_as else {
+            match key.code {
+                KeyCode::Char('m') => {
+                    self.teardown()?;
+                    return self
+                        .settle(tr_with("Merged into {path}", &[("path", &path.display())]));
+                }
+                KeyCode::Char('o') => {
+                    self.record(Utc::now());
+                    todo::write_document(&path, &self.document)?;
+                    self.teardown()?;
+                    return self.settle(tr_with("Overwrote {path}", &[("path", &path.display())]));
+                }
+                KeyCode::Char('s') => conflict.save_as = Some(String::new()),
+                KeyCode::Esc => self.conflict = None,
+                _ => {}
+            }
+            return Ok(None);
+        };
+        match key.code {
+            KeyCode::Char(c) => target.push(c),
+            KeyCode::Backspace => {
+                target.pop();
+            }
+            KeyCode::Esc => conflict.save_as = None,
+            KeyCode::Enter if !target.is_empty() => {
+                let target = PathBuf::from(target.as_str());
+                self.record(Utc::now());
+                todo::write_document(&target, &self.document)?;
+                // Carry on from the data file as the other writer left it
+                self.document = todo::load_document(&path)?;
+                self.todos = self.merged();
+                self.pin_starred();
+                self.synced = self.stamps(&path);
+                self.saved = self.todos.clone();
+                self.dirty = false;
+                return self.settle(tr_with("Saved to {path}", &[("path", &target.display())]));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Clears a settled conflict, going on to quit if that is what was held back.
+    fn settle(&mut self, message: String) -> Result<Option<Action>> {
+        let quit = self.conflict.take().is_some_and(|conflict| conflict.quit);
+        Ok(Some(if quit {
+            Action::Quit
+        } else {
+            Action::Toast(message)
+        }))
+    }
+
+    /// `[RO]` while only browsing, or `[+]` while there are unsaved changes.
+    fn modified(&self) -> &'static str {
+        if self.read_only {
+            "[RO]"
+        } else if self.dirty {
+            "[+]"
+        } else {
+            ""
+        }
+    }
+
+    /// The todos completed today towards the daily goal, and the goal.
+    fn goal(&self) -> Option<(usize, usize)> {
+        let goal = self.config.daily_goal?;
+        let today = Local::now().date_naive();
+        let done = stats::completions_per_day(&self.todos)
+            .get(&today)
+            .copied()
+            .unwrap_or_default();
+        Some((done, goal))
+    }
+
+    /// The bar under the list as it is unless the config gives a template for it.
+    fn built_in_status_bar(&self) -> String {
+        let mut text = if self.config.screen_reader {
+            self.announcement()
+        } else {
+            self.input_mode.to_string()
+        };
+        let modified = self.modified();
+        if !modified.is_empty() {
+            text.push_str(&format!(" {modified}"));
+        }
+        if let Some((done, goal)) = self.goal() {
+            text.push_str(&format!(" | goal {done}/{goal}"));
+        }
+        if let Some(streak) = self.streak().filter(|streak| *streak > 0) {
+            text.push_str(&format!(" | streak {streak}d"));
+        }
+        text
+    }
+
+    /// The bar under the list from the template in `status_bar`, filled in as things are now.
+    fn status_bar(&self, format: &str) -> String {
+        let listed = self.visible();
+        let done = listed.iter().filter(|&&i| self.todos[i].done).count();
+        let filter: Vec<String> = [
+            self.active_project
+                .as_ref()
+                .map(|project| format!("+{project}")),
+            self.active_context
+                .as_ref()
+                .map(|context| format!("@{context}")),
+            self.active_assignee
+                .as_ref()
+                .map(|assignee| format!("assign:{assignee}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mode = if self.config.screen_reader {
+            self.announcement()
+        } else {
+            self.input_mode.to_string()
+        };
+        template::fill(format, &|name| {
+            Some(match name {
+                "mode" => mode.clone(),
+                "modified" => self.modified().to_string(),
+                "project" => self.active_project.clone().unwrap_or_default(),
+                "context" => self.active_context.clone().unwrap_or_default(),
+                "assignee" => self.active_assignee.clone().unwrap_or_default(),
+                "filter" => filter.join(" "),
+                "pending" => (listed.len() - done).to_string(),
+                "done" => done.to_string(),
+                "total" => listed.len().to_string(),
+                "goal" => self
+                    .goal()
+                    .map(|(done, goal)| format!("{done}/{goal}"))
+                    .unwrap_or_default(),
+                "streak" => self
+                    .streak()
+                    .map(|streak| streak.to_string())
+                    .unwrap_or_default(),
+                _ => return None,
+            })
+        })
+    }
+
+    /// The row for `todo`, listed `index`th, from the template in `list_item`.
+    fn list_item(&self, format: &str, index: usize, todo: &TodoItem) -> String {
+        template::fill(format, &|name| {
+            Some(match name {
+                "index" => index.to_string(),
+                "title" => todo.title.clone(),
+                "project" => todo.project.as_deref().unwrap_or_default().to_string(),
+                "context" => todo.context.as_deref().unwrap_or_default().to_string(),
+                "tags" => todo
+                    .tags
+                    .iter()
+                    .map(|tag| format!("#{tag}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                "due" => todo.due.map(|due| due.to_string()).unwrap_or_default(),
+                "estimate" => todo
+                    .estimate
+                    .map(|estimate| estimate.to_string())
+                    .unwrap_or_default(),
+                "priority" => if todo.flagged { "⚑" } else { "" }.to_string(),
+                "todo" => todo.to_string(),
+                _ => return None,
+            })
+        })
+    }
+
+    /// The listed todos as a table of the columns in `table`, fitted into `width` cells, with a
+    /// header that marks the column the list was sorted by.
+    fn table(&self, width: usize) -> Table<'static> {
+        let columns = &self.config.table;
+        let spacing = columns.len().saturating_sub(1) as u16;
+        let widths: Vec<u16> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(columns.iter().map(|column| column.width()))
+            .split(Rect::new(0, 0, (width as u16).saturating_sub(spacing), 1))
+            .iter()
+            .map(|cell| cell.width)
+            .collect();
+        let header = Row::new(columns.iter().zip(&widths).map(|(&column, &width)| {
+            let mut header = column.header().to_string();
+            match self.sorted_by {
+                Some((sorted, false)) if sorted == column => header.push_str(" ▲"),
+                Some((sorted, true)) if sorted == column => header.push_str(" ▼"),
+                _ => {}
+            }
+            Cell::from(text::truncate(&header, width as usize, Truncation::End))
+        }))
+        .style(Style::new().add_modifier(Modifier::BOLD));
+        let today = Local::now().date_naive();
+        let rows: Vec<Row> = self
+            .visible()
+            .into_iter()
+            .map(|i| {
+                let todo = &self.todos[i];
+                let style = if todo.done {
+                    self.style("done", Style::default())
+                        .add_modifier(Modifier::CROSSED_OUT)
+                } else {
+                    Style::default()
+                };
+                Row::new(columns.iter().zip(&widths).map(|(&column, &width)| {
+                    let mut text = column.cell(todo, today, &self.config.urgency);
+                    if column == Column::Title {
+                        let indent = match self.depth(todo) {
+                            0 => String::new(),
+                            depth => format!("{}↳ ", "  ".repeat(depth - 1)),
+                        };
+                        let star = if todo.starred { "★ " } else { "" };
+                        text = format!("{star}{indent}{}", bidi::visual(&text));
+                    }
+                    Cell::from(text::truncate(
+                        &text,
+                        width as usize,
+                        self.config.truncation,
+                    ))
+                }))
+                .style(style)
+            })
+            .collect();
+        Table::new(rows, widths.into_iter().map(Constraint::Length)).header(header)
+    }
+
+    /// Whether `action` leaves the list as it is, as every action must while it is only browsed.
+    fn viewable(action: &Action) -> bool {
+        matches!(
+            action,
+            Action::Tick
+                | Action::Render
+                | Action::Resize(..)
+                | Action::Suspend
+                | Action::Resume
+                | Action::Quit
+                | Action::Refresh
+                | Action::Error(_)
+                | Action::Help
+                | Action::SaveAndClose
+                | Action::ToggleCompletedPanel
+                | Action::ToggleDetailPane
+                | Action::EnterStatsMode
+                | Action::EnterMatrixMode
+                | Action::CycleContext
+                | Action::CycleAssignee
+                | Action::SortByUrgency
+                | Action::CycleSort
+                | Action::OpenProject(_)
+                | Action::TodosChanged(_)
+                | Action::ReloadConfig
+                | Action::FasterTicks
+                | Action::SlowerTicks
+                | Action::FasterFrames
+                | Action::SlowerFrames
+                | Action::KeyHints(_)
+                | Action::Toast(_)
+                | Action::Custom(..)
+                | Action::EnterBrowseMode
+                | Action::EnterHelpMode
+                | Action::EnterProjectsMode
+                | Action::ExitCurrentMode
+                | Action::BrowseListUp
+                | Action::BrowseListDown
+                | Action::BrowseListJump(_)
+        )
+    }
+
+    /// Publishes the current list so that integrations outside the component see every change.
+    fn snapshot(&self) -> Option<Action> {
+        Some(Action::TodosChanged(self.todos.clone()))
+    }
+
+    /// Carries out `action`.
+    fn apply(&mut self, action: Action) -> Result<Option<Action>> {
+        // Changes requested from outside the TUI land regardless of the current mode
+        match &action {
+            Action::InsertTodo(raw) => {
+                let mut todo = TodoItem::parse(raw);
+                todo.log(EventKind::Created);
+                self.todos.push(todo);
+                return Ok(self.snapshot());
+            }
+            Action::UpsertTodos(items) => {
+                todo::upsert(&mut self.todos, items.clone());
+                return Ok(self.snapshot());
+            }
+            Action::RemoveTodos(ids) => {
+                self.todos.retain(|todo| !ids.contains(&todo.id));
+                self.clamp_cursor();
+                return Ok(self.snapshot());
+            }
+            Action::MergeJiraIssues(issues) => {
+                jira::merge(&mut self.todos, issues.clone());
+                return Ok(self.snapshot());
+            }
+            // Left alone while a conflict is settled, so the choice is about the file as it is shown
+            Action::Tick if self.read_only || self.conflict.is_some() => return Ok(None),
+            Action::Tick => return self.reload(&todo::data_file()),
+            Action::TodosChanged(todos) => {
+                self.dirty = *todos != self.saved;
+                return Ok(None);
+            }
+            Action::Save if self.changed_on_disk() => {
+                self.conflict = Some(Conflict {
+                    quit: false,
+                    save_as: None,
+                });
+                return Ok(None);
+            }
+            Action::Save => {
+                self.teardown()?;
+                self.pin_starred();
+                self.saved = self.todos.clone();
+                return Ok(Some(Action::Toast(tr_with(
+                    "Saved to {path}",
+                    &[("path", &todo::data_file().display())],
+                ))));
+            }
+            Action::Toast(message) | Action::Error(message) => {
+                let first_line = message.lines().next().unwrap_or_default();
+                self.toast = Some((first_line.to_string(), Instant::now()));
+                return Ok(None);
+            }
+            Action::CompleteTodo(index) => {
+                let streak_before = self.streak();
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    if !todo.done {
+                        todo.completed = Some(Utc::now());
+                        todo.log(EventKind::Completed);
+                    }
+                    todo.done = true;
+                    todo.touch();
+                }
+                if let Some(todo) = self.todos.get(*index) {
+                    self.write_back(todo)?;
+                }
+                self.celebrate(streak_before);
+                return Ok(self.snapshot());
+            }
+            Action::ReopenTodo(index) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    todo.done = false;
+                    todo.completed = None;
+                    todo.touch();
+                    todo.log(EventKind::Reopened);
+                }
+                if let Some(todo) = self.todos.get(*index) {
+                    self.write_back(todo)?;
+                }
+                return Ok(self.snapshot());
+            }
+            Action::ToggleCompletedPanel => {
+                self.show_completed = !self.show_completed;
+                return Ok(None);
+            }
+            Action::Undo(steps) => {
+                let keep = self.undo.len().saturating_sub(*steps);
+                let restored: Vec<_> = self.undo.drain(keep..).collect();
+                if !restored.is_empty() {
+                    let undone = self.todos.clone();
+                    for (_, _, changes) in restored.into_iter().rev() {
+                        changes.revert(&mut self.todos);
+                    }
+                    for todo in &self.todos {
+                        let before = undone.iter().find(|before| before.id == todo.id);
+                        if before.is_some_and(|before| before.done != todo.done) {
+                            self.write_back(todo)?;
+                        }
+                    }
+                    self.clamp_cursor();
+                }
+                if self.input_mode == Mode::History {
+                    self.input_mode = Mode::Browse;
+                }
+                return Ok(self.snapshot());
+            }
+            Action::MoveToQuadrant(index, quadrant) => {
+                let today = Local::now().date_naive();
+                if let (Some(todo), Some(quadrant)) =
+                    (self.todos.get_mut(*index), matrix::QUADRANTS.get(*quadrant))
+                {
+                    matrix::move_to(todo, *quadrant, today);
+                    todo.touch();
+                    todo.log(EventKind::Edited);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::ToggleDetailPane => {
+                self.show_detail = !self.show_detail;
+                return Ok(None);
+            }
+            Action::ToggleStar(index) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    todo.starred = !todo.starred;
+                    todo.touch();
+                    todo.log(EventKind::Edited);
+                    let id = todo.id;
+                    self.pin_starred();
+                    self.select_id(id);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::BulkEdit => {
+                let listed: Vec<Uuid> = self
+                    .visible()
+                    .into_iter()
+                    .map(|i| self.todos[i].id)
+                    .collect();
+                let path =
+                    crate::utils::temp_file(".txt", &bulk_edit::render(&self.todos, &listed))?;
+                self.external_edit = Some((path.clone(), ExternalEdit::Bulk(listed)));
+                return Ok(Some(Action::OpenEditor(path)));
+            }
+            Action::Attach(index, attachment) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    todo.attachments.push(attachment.clone());
+                    todo.touch();
+                    todo.log(EventKind::Edited);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::AddChecklistStep(index, text) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    todo.checklist.push(ChecklistItem {
+                        text: text.clone(),
+                        done: false,
+                    });
+                    todo.touch();
+                    todo.log(EventKind::Edited);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::ToggleChecklistStep(index, step) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    if let Some(item) = todo.checklist.get_mut(*step) {
+                        item.done = !item.done;
+                        todo.touch();
+                        todo.log(EventKind::Edited);
+                    }
+                }
+                return Ok(self.snapshot());
+            }
+            Action::PasteTodo(title) => {
+                let Some(yanked) = &self.yanked else {
+                    return Ok(Some(Action::Toast(
+                        tr("Nothing to paste, yank a todo with yy first").to_string(),
+                    )));
+                };
+                let mut copy = yanked.duplicate();
+                if let Some(title) = title {
+                    copy.title = title.clone();
+                }
+                let id = copy.id;
+                let at = (self.selected() + 1).min(self.todos.len());
+                self.todos.insert(at, copy);
+                self.pin_starred();
+                self.select_id(id);
+                return Ok(self.snapshot());
+            }
+            Action::PromoteToProject(index) => {
+                let Some(project) = tags::promote(&mut self.todos, *index) else {
+                    return Ok(None);
+                };
+                self.clamp_cursor();
+                if let Some(tx) = &self.command_tx {
+                    tx.send(Action::Toast(tr_with(
+                        "Promoted to +{project}, O on its link goes there",
+                        &[("project", &project)],
+                    )))?;
+                }
+                return Ok(self.snapshot());
+            }
+            Action::OpenProject(project) => {
+                self.active_project = Some(project.clone());
+                self.cursor_row = 0;
+                self.clamp_cursor();
+                return Ok(None);
+            }
+            Action::SplitTodo(index, subtasks) => {
+                let Some(parent) = self.todos.get(*index).cloned() else {
+                    return Ok(None);
+                };
+                // After the parent and any subtasks it already has
+                let mut at = *index + 1;
+                while self
+                    .todos
+                    .get(at)
+                    .is_some_and(|todo| self.is_under(todo, parent.id))
+                {
+                    at += 1;
+                }
+                for (offset, title) in subtasks.iter().enumerate() {
+                    let mut subtask = TodoItem::parse(title);
+                    subtask.parent = Some(parent.id);
+                    subtask.project = subtask.project.or(parent.project.clone());
+                    subtask.context = subtask.context.or(parent.context.clone());
+                    subtask.assignee = subtask.assignee.or(parent.assignee.clone());
+                    subtask.origin = parent.origin.clone();
+                    subtask.log(EventKind::Created);
+                    self.todos.insert(at + offset, subtask);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::Comment(index, text) => {
+                if let Some(todo) = self.todos.get_mut(*index) {
+                    todo.comments.push(Comment {
+                        at: Utc::now(),
+                        text: text.clone(),
+                    });
+                    todo.touch();
+                    todo.log(EventKind::Edited);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::EditNotes(index) => {
+                let Some(todo) = self.todos.get(*index) else {
+                    return Ok(None);
+                };
+                let path = crate::utils::temp_file(
+                    "-notes.md",
+                    todo.notes.as_deref().unwrap_or_default(),
+                )?;
+                self.external_edit = Some((path.clone(), ExternalEdit::Notes(todo.id)));
+                return Ok(Some(Action::OpenEditor(path)));
+            }
+            Action::EditorClosed(path) => {
+                let Some((_, edit)) = self.external_edit.take() else {
+                    return Ok(None);
+                };
+                let text = std::fs::read_to_string(path)?;
+                let _ = std::fs::remove_file(path);
+                let listed = match edit {
+                    ExternalEdit::Bulk(listed) => listed,
+                    ExternalEdit::Notes(id) => {
+                        let Some(todo) = self.todos.iter_mut().find(|todo| todo.id == id) else {
+                            return Ok(None);
+                        };
+                        let notes = Some(text.trim_end().to_string()).filter(|n| !n.is_empty());
+                        if notes != todo.notes {
+                            todo.notes = notes;
+                            todo.touch();
+                            todo.log(EventKind::Edited);
+                        }
+                        return Ok(self.snapshot());
+                    }
+                };
+                let summary = bulk_edit::apply(
+                    &mut self.todos,
+                    &listed,
+                    &text,
+                    self.active_project.as_deref(),
+                );
+                self.pin_starred();
+                self.clamp_cursor();
+                if let Some(tx) = &self.command_tx {
+                    tx.send(Action::Toast(tr_with(
+                        "Bulk edit: {summary}",
+                        &[("summary", &summary)],
+                    )))?;
+                }
+                return Ok(self.snapshot());
+            }
+            Action::CycleContext => {
+                let contexts = tags::contexts(&self.todos);
+                let next = match &self.active_context {
+                    Some(context) => contexts.iter().skip_while(|c| *c != context).nth(1),
+                    None => contexts.first(),
+                };
+                self.active_context = next.cloned();
+                self.clamp_cursor();
+                return Ok(None);
+            }
+            Action::CycleAssignee => {
+                let assignees = tags::assignees(&self.todos, &self.config.team);
+                let next = match &self.active_assignee {
+                    Some(assignee) => assignees.iter().skip_while(|a| *a != assignee).nth(1),
+                    None => assignees.first(),
+                };
+                self.active_assignee = next.cloned();
+                self.clamp_cursor();
+                return Ok(None);
+            }
+            Action::SortByUrgency => {
+                let id = self.todos.get(self.selected()).map(|todo| todo.id);
+                urgency::sort(
+                    &mut self.todos,
+                    Local::now().date_naive(),
+                    &self.config.urgency,
+                );
+                self.pin_starred();
+                self.sorted_by = Some((Column::Urgency, true));
+                if let Some(id) = id {
+                    self.select_id(id);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::CycleSort => {
+                let Some((column, descending)) =
+                    table::next_sort(&self.config.table, self.sorted_by)
+                else {
+                    return Ok(None);
+                };
+                let id = self.todos.get(self.selected()).map(|todo| todo.id);
+                table::sort(
+                    &mut self.todos,
+                    column,
+                    descending,
+                    Local::now().date_naive(),
+                    &self.config.urgency,
+                );
+                self.pin_starred();
+                self.sorted_by = Some((column, descending));
+                if let Some(id) = id {
+                    self.select_id(id);
+                }
+                return Ok(self.snapshot());
+            }
+            Action::RenameTag(from, to) => {
+                tags::rename(&mut self.todos, from, to);
+                return Ok(self.snapshot());
+            }
+            Action::DeleteTag(tag) => {
+                tags::delete(&mut self.todos, tag);
+                let count = tags::counts(&self.todos).len();
+                self.tag_row = self.tag_row.min(count.saturating_sub(1));
+                return Ok(self.snapshot());
+            }
+            Action::DeleteTodo(index) => {
+                if *index < self.todos.len() {
+                    self.todos.remove(*index);
+                    self.clamp_cursor();
+                }
+                return Ok(self.snapshot());
+            }
+            _ => {}
+        }
+
+        match self.input_mode {
+            Mode::Normal => match action {
+                Action::EnterCommandMode => {
+                    self.input_mode = Mode::Editing;
+                }
+                Action::EnterBrowseMode => {
+                    self.input_mode = Mode::Browse;
+                }
+                Action::EnterHelpMode => {
+                    self.input_mode = Mode::Help;
+                }
+                Action::EnterTagsMode => {
+                    self.input_mode = Mode::Tags;
+                    self.tag_row = 0;
+                }
+                Action::EnterProjectsMode => {
+                    self.input_mode = Mode::Projects;
+                    self.project_row = 0;
+                }
+                Action::EnterStatsMode => {
+                    self.input_mode = Mode::Stats;
+                }
+                Action::EnterMatrixMode => {
+                    self.input_mode = Mode::Matrix;
+                    self.matrix_quadrant = 0;
+                    self.matrix_row = 0;
+                }
+                Action::EnterThemesMode => {
+                    self.input_mode = Mode::Themes;
+                    self.theme_row = self
+                        .config
+                        .theme
+                        .as_deref()
+                        .and_then(|theme| themes::names().iter().position(|name| *name == theme))
+                        .unwrap_or(0);
+                    self.styles_before = Some(self.config.styles.clone());
+                    self.preview_theme();
+                }
+                _ => {}
+            },
+            Mode::Editing => match action {
+                Action::ExitCurrentMode => {
+                    self.input_mode = Mode::Normal;
+                    self.duplicate_of = None;
+                }
+                Action::AddTodo => {
+                    let mut new_todo: TodoItem = TodoItem::parse(self.input.value());
+                    if new_todo.project.is_none() {
+                        new_todo.project = self.active_project.as_deref().map(Name::from);
+                    }
+                    if new_todo.context.is_none() {
+                        new_todo.context = self.active_context.as_deref().map(Name::from);
+                    }
+                    if new_todo.assignee.is_none() {
+                        new_todo.assignee = self.active_assignee.as_deref().map(Name::from);
+                    }
+                    new_todo.log(EventKind::Created);
+                    self.input.reset();
+                    self.todos.push(new_todo);
+                    self.input_mode = Mode::Editing;
+                    return Ok(self.snapshot());
+                }
+                _ => {}
+            },
+            Mode::Browse => match action {
+                Action::ExitCurrentMode => {
+                    self.input_mode = Mode::Normal;
+                }
+                Action::EnterHistoryMode => {
+                    self.input_mode = Mode::History;
+                    self.history_row = 0;
+                }
+                Action::BrowseListUp => {
+                    self.cursor_row -= 1;
+                    self.cursor_row = self.cursor_row.max(0);
+                }
+                Action::BrowseListDown => {
+                    self.cursor_row += 1;
+                    self.clamp_cursor();
+                }
+                Action::BrowseListJump(row) => {
+                    self.cursor_row = row as i64;
+                    self.clamp_cursor();
+                }
+                _ => {}
+            },
+            Mode::Help => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Normal;
+                }
+            }
+            Mode::Tags => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Normal;
+                    self.renaming = None;
+                }
+            }
+            Mode::Projects => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Normal;
+                    self.new_project = None;
+                }
+            }
+            Mode::History => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Browse;
+                }
+            }
+            Mode::Stats | Mode::Matrix => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Normal;
+                }
+            }
+            Mode::Themes => {
+                if action == Action::ExitCurrentMode {
+                    self.input_mode = Mode::Normal;
+                    if let Some(styles) = self.styles_before.take() {
+                        self.config.styles = styles;
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Component for Home {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if self.conflict.is_some() {
+            return self.conflict_key(key);
+        }
+        let action = match self.input_mode {
+            Mode::Normal => match key.code {
+                KeyCode::Char('i') => Action::EnterCommandMode,
+                KeyCode::Char('v') => Action::EnterBrowseMode,
+                KeyCode::Char('h') => Action::EnterHelpMode,
+                KeyCode::Char('t') => Action::EnterTagsMode,
+                KeyCode::Char('p') => Action::EnterProjectsMode,
+                KeyCode::Char('c') => Action::ToggleCompletedPanel,
+                KeyCode::Char('S') => Action::EnterStatsMode,
+                KeyCode::Char('b') => Action::ToggleHabits,
+                KeyCode::Char('e') => Action::EnterMatrixMode,
+                KeyCode::Char('r') => Action::SortByUrgency,
+                KeyCode::Char('s') if !self.config.table.is_empty() => Action::CycleSort,
+                KeyCode::Char('E') => Action::BulkEdit,
+                KeyCode::Char('@') => Action::CycleContext,
+                KeyCode::Char('a') => Action::CycleAssignee,
+                KeyCode::Char('T') => Action::EnterThemesMode,
+                _ => return Ok(None),
+            },
+            Mode::Editing => {
+                // Left behind if the todo went away since, by a reload or from outside
+                let duplicate = self
+                    .duplicate_of
+                    .take()
+                    .filter(|id| self.todos.iter().any(|todo| todo.id == *id));
+                if let Some(id) = duplicate {
+                    match key.code {
+                        KeyCode::Enter => {
+                            self.input.reset();
+                            self.input_mode = Mode::Browse;
+                            if self.select_id(id).is_none() {
+                                self.active_project = None;
+                                self.active_context = None;
+                                self.active_assignee = None;
+                                self.select_id(id);
+                            }
+                        }
+                        KeyCode::Char('a') => {
+                            if let Some(sender) = &self.command_tx {
+                                sender.send(Action::AddTodo)?;
+                            }
+                            return Ok(Some(Action::ExitCurrentMode));
+                        }
+                        _ => {}
+                    }
+                    return Ok(Some(Action::Refresh));
+                }
+                let completions = self.completions();
+                match key.code {
+                    KeyCode::Enter => {
+                        let title = TodoItem::parse(self.input.value()).title;
+                        if let Some(index) = duplicates::find(&self.todos, &title) {
+                            self.duplicate_of = Some(self.todos[index].id);
+                            return Ok(Some(Action::Refresh));
+                        }
+                        if let Some(sender) = &self.command_tx {
+                            if let Err(e) = sender.send(Action::AddTodo) {
+                                error!("Failed to send action: {:?}", e);
+                            }
+                        }
+                        Action::ExitCurrentMode
+                    }
+                    KeyCode::Down if !completions.is_empty() => {
+                        self.completion_row = (self.completion_row + 1) % completions.len();
+                        Action::Refresh
+                    }
+                    KeyCode::Up if !completions.is_empty() => {
+                        self.completion_row =
+                            (self.completion_row + completions.len() - 1) % completions.len();
+                        Action::Refresh
+                    }
+                    KeyCode::Tab if !completions.is_empty() => {
+                        let value = self.input.value();
+                        let stem = value.rsplit_once(' ').map_or("", |(stem, _)| stem);
+                        let word = &completions[self.completion_row.min(completions.len() - 1)];
+                        let value = match stem {
+                            "" => format!("{word} "),
+                            stem => format!("{stem} {word} "),
+                        };
+                        self.input = Input::new(value);
+                        self.completion_row = 0;
+                        Action::Refresh
+                    }
+                    _ => {
+                        self.input.handle_event(&crossterm::event::Event::Key(key));
+                        self.completion_row = 0;
+                        Action::Refresh
+                    }
+                }
+            }
+            Mode::Browse => {
+                if let Some((prompt, text)) = &mut self.prompt {
+                    match key.code {
+                        KeyCode::Char(c) => text.push(c),
+                        KeyCode::Backspace => {
+                            text.pop();
+                        }
+                        KeyCode::Enter if *prompt == Prompt::Subtask => {
+                            let subtask = std::mem::take(text).trim().to_string();
+                            if !subtask.is_empty() {
+                                self.subtasks.push(subtask);
+                                return Ok(None);
+                            }
+                            self.prompt = None;
+                            let subtasks = std::mem::take(&mut self.subtasks);
+                            if !subtasks.is_empty() {
+                                return Ok(Some(Action::SplitTodo(self.selected(), subtasks)));
+                            }
+                        }
+                        KeyCode::Enter => {
+                            let prompt = *prompt;
+                            let text = text.trim().to_string();
+                            self.prompt = None;
+                            if !text.is_empty() {
+                                let index = self.selected();
+                                let action = match prompt {
+                                    Prompt::PasteAs => {
+                                        return self.perform(Action::PasteTodo(Some(text)), 1)
+                                    }
+                                    Prompt::Attach => Action::Attach(index, text),
+                                    Prompt::Step => Action::AddChecklistStep(index, text),
+                                    Prompt::Comment => Action::Comment(index, text),
+                                    // Entered a line at a time above
+                                    Prompt::Subtask => return Ok(None),
+                                };
+                                return Ok(Some(action));
+                            }
+                        }
+                        _ => {
+                            self.prompt = None;
+                            self.subtasks.clear();
+                        }
+                    }
+                    return Ok(None);
+                }
+                if let Some(jump) = &mut self.jump {
+                    match key.code {
+                        KeyCode::Char(digit @ '0'..='9') => jump.push(digit),
+                        KeyCode::Char('w') if jump.is_empty() => jump.push('w'),
+                        KeyCode::Backspace => {
+                            jump.pop();
+                        }
+                        KeyCode::Enter if jump == "w" => {
+                            self.jump = None;
+                            return Ok(Some(Action::Save));
+                        }
+                        KeyCode::Enter => {
+                            let index = jump.parse().ok();
+                            self.jump = None;
+                            if let Some(index) = index {
+                                return self.perform(Action::BrowseListJump(index), 1);
+                            }
+                        }
+                        _ => self.jump = None,
+                    }
+                    return Ok(None);
+                }
+                if let Some((prefix, count)) = self.pending.take() {
+                    return match (prefix, key.code) {
+                        ('d', KeyCode::Char('d')) => {
+                            self.perform(Action::DeleteTodo(self.selected()), count)
+                        }
+                        ('@', KeyCode::Char(register)) => self.replay(register, count),
+                        ('m', KeyCode::Char(name)) => {
+                            if let Some(todo) = self.todos.get(self.selected()) {
+                                self.marks.insert(name, todo.id);
+                            }
+                            Ok(None)
+                        }
+                        ('\'', KeyCode::Char(name)) => {
+                            let row = self.marks.get(&name).and_then(|id| {
+                                self.visible()
+                                    .into_iter()
+                                    .position(|i| self.todos[i].id == *id)
+                            });
+                            match row {
+                                Some(row) => self.perform(Action::BrowseListJump(row), 1),
+                                None => Ok(Some(Action::Toast(tr_with(
+                                    "Mark '{name} is not set",
+                                    &[("name", &name)],
+                                )))),
+                            }
+                        }
+                        ('q', KeyCode::Char(register)) => {
+                            self.recording = Some((register, Vec::new()));
+                            Ok(None)
+                        }
+                        ('y', KeyCode::Char('y')) => {
+                            let Some(todo) = self.todos.get(self.selected()) else {
+                                return Ok(None);
+                            };
+                            self.yanked = Some(todo.clone());
+                            Ok(Some(Action::Toast(tr_with(
+                                "Yanked \"{title}\"",
+                                &[("title", &todo.title)],
+                            ))))
+                        }
+                        _ => Ok(None),
+                    };
+                }
+                let count = match key.code {
+                    KeyCode::Char(digit @ '0'..='9') if digit != '0' || self.count.is_some() => {
+                        let digit = digit.to_digit(10).unwrap_or_default() as usize;
+                        self.count = Some(self.count.unwrap_or(0) * 10 + digit);
+                        return Ok(None);
+                    }
+                    _ => self.count.take(),
+                };
+                if key.code == KeyCode::Char('q') {
+                    if let Some((register, actions)) = self.recording.take() {
+                        self.registers.insert(register, actions);
+                        return Ok(None);
+                    }
+                }
+                if key.code == KeyCode::Char(':') {
+                    self.jump = Some(String::new());
+                    return Ok(None);
+                }
+                if self.show_detail {
+                    match key.code {
+                        KeyCode::Char('A') => {
+                            self.prompt = Some((Prompt::Attach, String::new()));
+                            return Ok(None);
+                        }
+                        KeyCode::Char('C') => {
+                            self.prompt = Some((Prompt::Comment, String::new()));
+                            return Ok(None);
+                        }
+                        KeyCode::Char('L') => {
+                            self.prompt = Some((Prompt::Step, String::new()));
+                            return Ok(None);
+                        }
+                        KeyCode::Tab => {
+                            self.detail_row += 1;
+                            return Ok(None);
+                        }
+                        _ => {}
+                    }
+                }
+                if key.code == KeyCode::Char('B') && self.selected() < self.todos.len() {
+                    self.prompt = Some((Prompt::Subtask, String::new()));
+                    return Ok(None);
+                }
+                if key.code == KeyCode::Char('P') {
+                    if let Some(todo) = &self.yanked {
+                        self.prompt = Some((Prompt::PasteAs, todo.title.clone()));
+                    }
+                    return Ok(None);
+                }
+                if let KeyCode::Char(prefix @ ('q' | '@' | 'd' | 'm' | '\'' | 'y')) = key.code {
+                    self.pending = Some((prefix, count.unwrap_or(1)));
+                    return Ok(None);
+                }
+                let (action, count) = match (key.code, self.last_change.clone()) {
+                    (KeyCode::Char('.'), Some((action, last))) => (action, count.unwrap_or(last)),
+                    (KeyCode::Char('.'), None) => return Ok(None),
+                    _ => match self.browse_action(key) {
+                        Some(action) => (action, count.unwrap_or(1)),
+                        None => return Ok(None),
+                    },
+                };
+                return self.perform(action, count);
+            }
+            Mode::Help => match key.code {
+                KeyCode::Char('h') => Action::ExitCurrentMode,
+                _ => return Ok(None),
+            },
+            Mode::Tags => return self.tags_key(key),
+            Mode::Projects => return Ok(self.projects_key(key)),
+            Mode::Matrix => return Ok(self.matrix_key(key)),
+            Mode::Themes => return self.themes_key(key),
+            Mode::Stats => {
+                if key.code == KeyCode::Char('w') {
+                    self.burndown_window = (self.burndown_window + 1) % BURNDOWN_WINDOWS.len();
+                }
+                return Ok(None);
+            }
+            Mode::History => match key.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.history_row = (self.history_row + 1).min(self.undo.len().max(1) - 1);
+                    return Ok(None);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.history_row = self.history_row.saturating_sub(1);
+                    return Ok(None);
+                }
+                KeyCode::Enter => Action::Undo(self.history_row + 1),
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(action))
+    }
+
+    fn buildup(&mut self) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let path = todo::data_file();
+        self.document = todo::load_document(&path)?;
+        if let Some(archive) = &self.config.archive {
+            let now = Utc::now();
+            let cutoff = now - chrono::Duration::days(archive.after_days as i64);
+            if archive::roll(&mut self.document, &path, cutoff, now)? > 0 {
+                todo::write_document(&path, &self.document)?;
+            }
+        }
+        for (name, path) in &self.config.shared_files {
+            self.shared.insert(name.clone(), todo::load_document(path)?);
+        }
+        self.synced = self.stamps(&path);
+        self.todos = self.merged();
+        if let Some(vault) = &self.config.vault {
+            let items = vault::scan(&vault.dir).unwrap_or_else(|e| {
+                error!("Failed to scan the vault: {:?}", e);
+                Vec::new()
+            });
+            todo::upsert(&mut self.todos, items);
+        }
+        self.pin_starred();
+        self.saved = self.todos.clone();
+        if let (Some(tx), Some(action)) = (&self.command_tx, self.snapshot()) {
+            tx.send(action)?;
+        }
+        Ok(())
+    }
+
+    fn key_mode(&self) -> Option<crate::mode::Mode> {
+        Some(match self.input_mode {
+            Mode::Normal => crate::mode::Mode::Normal,
+            Mode::Editing => crate::mode::Mode::Editing,
+            // Typing into a prompt is editing too
+            Mode::Browse if self.prompt.is_some() => crate::mode::Mode::Editing,
+            Mode::Tags if self.renaming.is_some() => crate::mode::Mode::Editing,
+            Mode::Browse => crate::mode::Mode::Browse,
+            Mode::Help => crate::mode::Mode::Help,
+            Mode::Tags => crate::mode::Mode::Tags,
+            Mode::Projects => crate::mode::Mode::Projects,
+            Mode::History => crate::mode::Mode::History,
+            Mode::Stats => crate::mode::Mode::Stats,
+            Mode::Matrix => crate::mode::Mode::Matrix,
+            Mode::Themes => crate::mode::Mode::Themes,
+        })
+    }
+
+    fn image(&self) -> Option<(PathBuf, Rect)> {
+        self.image.clone()
+    }
+
+    fn links(&self) -> Vec<Target> {
+        self.links.clone()
+    }
+
+    fn hold_quit(&mut self) -> Result<bool> {
+        if self.conflict.is_none() && self.changed_on_disk() {
+            self.conflict = Some(Conflict {
+                quit: true,
+                save_as: None,
+            });
+        }
+        if let Some(conflict) = &mut self.conflict {
+            conflict.quit = true;
+        }
+        Ok(self.conflict.is_some())
+    }
+
+    fn teardown(&mut self) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        self.record(Utc::now());
+        let path = todo::data_file();
+        self.document = todo::save_document(&path, &self.document)?;
+        for (name, document) in &mut self.shared {
+            if let Some(path) = self.config.shared_files.get(name) {
+                *document = todo::save_document(path, document)?;
+            }
+        }
+        self.synced = self.stamps(&path);
+        self.todos = self.merged();
+        self.saved = self.todos.clone();
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if self.read_only && !Self::viewable(&action) {
+            return Ok(Some(Action::Toast(
+                tr("Only viewing, nothing can be changed").into(),
+            )));
+        }
+        let Some(description) = self.describe(&action) else {
+            return self.apply(action);
+        };
+        let before = self.todos.clone();
+        let next = self.apply(action)?;
+        let changes = Changes::between(&before, &self.todos);
+        self.undo.push((description, Local::now(), changes));
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        Ok(next)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        // Helper function for drawing the help box
+        fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
+            let popup_layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage((100 - percent_y) / 2),
+                    Constraint::Percentage(percent_y),
+                    Constraint::Percentage((100 - percent_y) / 2),
+                ])
+                .split(r);
+
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage((100 - percent_x) / 2),
+                    Constraint::Percentage(percent_x),
+                    Constraint::Percentage((100 - percent_x) / 2),
+                ])
+                .split(popup_layout[1])[1]
+        }
+
+        if self.input_mode == Mode::Help {
+            f.render_widget(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(tr("Help Menu")),
+                centered_rect(area, 35, 35),
+            );
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints(
+                [
+                    Constraint::Min(5),
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                    Constraint::Max(1),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let (down, up) = self.config.keymap.labels();
+        let (msg, style) = match self.input_mode {
+            Mode::Normal => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("CTRL+C", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to exit, ")),
+                    Span::styled("i", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to insert todo.")),
+                ],
+                Style::default().add_modifier(Modifier::RAPID_BLINK),
+            ),
+            Mode::Editing => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to stop editing, ")),
+                    Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to record the todo")),
+                ],
+                Style::default(),
+            ),
+            Mode::Browse => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled(down, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to scroll down, ")),
+                    Span::styled(up, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to scroll up, ")),
+                    Span::styled("x", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to complete, ")),
+                    Span::styled("s", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to star, ")),
+                    Span::styled(".", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to repeat it, ")),
+                    Span::styled("dd", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to delete, ")),
+                    Span::styled("u", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to undo, ")),
+                    Span::styled("H", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" for the undo history, ")),
+                    Span::styled(":N", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to jump to todo N, ")),
+                    Span::styled(":w", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to save, ")),
+                    Span::styled("m", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" and ")),
+                    Span::styled("'", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to set and return to a mark, ")),
+                    Span::styled("o", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to open its link, ")),
+                    Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to record a macro, ")),
+                    Span::styled("@", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to replay one, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to exit browse mode ")),
+                ],
+                Style::default(),
+            ),
+            Mode::Help => (vec![], Style::default()),
+            Mode::Tags => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("r", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to rename or merge, ")),
+                    Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to delete, ")),
+                    Span::styled("c", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to change color, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back")),
+                ],
+                Style::default(),
+            ),
+            Mode::Projects => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to switch, ")),
+                    Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to start a new project, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back")),
+                ],
+                Style::default(),
+            ),
+            Mode::Matrix => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("Tab", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to change quadrant, ")),
+                    Span::styled("1-4", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to move the selected todo there, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back")),
+                ],
+                Style::default(),
+            ),
+            Mode::Stats => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back")),
+                ],
+                Style::default(),
+            ),
+            Mode::Themes => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("j", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" and ")),
+                    Span::styled("k", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to preview, ")),
+                    Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to keep the theme, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back")),
+                ],
+                Style::default(),
+            ),
+            Mode::History => (
+                vec![
+                    Span::raw(tr("Press ")),
+                    Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to go back to before the selected change, ")),
+                    Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(tr(" to keep everything")),
+                ],
+                Style::default(),
+            ),
+        };
+
+        let mut text = Text::from(Line::from(msg));
+        text.patch_style(style);
+        let help_message = Paragraph::new(text);
+        f.render_widget(help_message, chunks[1]);
+
+        // Laid out as it appears on screen, so right-to-left input reads and scrolls the right way
+        let field = bidi::field(
+            self.input.value(),
+            self.input.cursor(),
+            chunks[2].width.saturating_sub(2) as usize, // keep 2 for borders
+        );
+        let input = Paragraph::new(field.text.as_str())
+            .style(match self.input_mode {
+                Mode::Normal
+                | Mode::Browse
+                | Mode::Help
+                | Mode::Tags
+                | Mode::Projects
+                | Mode::History
+                | Mode::Stats
+                | Mode::Matrix
+                | Mode::Themes => Style::default(),
+                Mode::Editing => self.style("input", Style::default().fg(Color::Yellow)),
+            })
+            .scroll((0, field.scroll as u16))
+            .block(Block::default().borders(Borders::ALL).title(tr("Input")));
+        f.render_widget(input, chunks[2]);
+
+        match self.input_mode {
+            Mode::Normal
+            | Mode::Browse
+            | Mode::Help
+            | Mode::Tags
+            | Mode::Projects
+            | Mode::History
+            | Mode::Stats
+            | Mode::Matrix
+            | Mode::Themes =>
+                // Hide the cursor. `Frame` does this by default, so we don't need to do anything here
+                {}
+
+            Mode::Editing => {
+                // Make the cursor visible and ask tui-rs to put it at the specified coordinates after rendering
+                f.set_cursor(
+                    // Put cursor where the next character goes
+                    chunks[2].x + (field.cursor - field.scroll) as u16 + 1,
+                    // Move one line down, from the border to the input line
+                    chunks[2].y + 1,
+                )
+            }
+        }
+
+        let panels = usize::from(self.show_detail) + usize::from(self.show_completed);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                std::iter::once(Constraint::Min(20))
+                    .chain(std::iter::repeat_n(Constraint::Percentage(30), panels))
+                    .collect::<Vec<_>>(),
+            )
+            .split(chunks[0]);
+        let selection = self.selection_symbol().unwrap_or(">>");
+        // Borders and the selection symbol take room from titles, which may need cutting short
+        let room = (columns[0].width.saturating_sub(2) as usize)
+            .saturating_sub(Span::raw(selection).width());
+
+        // Creates the todo list
+        let todos: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|i| &self.todos[i])
+            .enumerate()
+            .map(|(i, m)| {
+                let style = if m.done {
+                    self.style("done", Style::default())
+                        .add_modifier(Modifier::CROSSED_OUT)
+                } else {
+                    Style::default()
+                };
+                let star = if m.starred {
+                    Span::styled(
+                        "★ ",
+                        self.style("starred", Style::default().fg(Color::Yellow)),
+                    )
+                } else {
+                    Span::raw("")
+                };
+                let indent = match self.depth(m) {
+                    0 => String::new(),
+                    depth => format!("{}↳ ", "  ".repeat(depth - 1)),
+                };
+                let check = if m.done && self.config.high_contrast {
+                    Span::styled("✓ ", style)
+                } else {
+                    Span::raw("")
+                };
+                let (index, text) = match &self.config.list_item {
+                    Some(format) => (String::new(), self.list_item(format, i, m)),
+                    None => (format!("{}: ", i), m.to_string()),
+                };
+                let mut spans = vec![
+                    star,
+                    check,
+                    Span::styled(index, style),
+                    Span::styled(indent, style),
+                ];
+                let mut badges = Vec::new();
+                if !m.checklist.is_empty() {
+                    badges.push(Span::styled(format!(" {}", progress(m)), style));
+                }
+                if let Some(assignee) = &m.assignee {
+                    let color = tags::assignee_color(assignee, &self.config.team);
+                    badges.push(Span::raw(" "));
+                    badges.push(Span::styled(
+                        format!(" {assignee} "),
+                        parse_style(color).add_modifier(Modifier::REVERSED),
+                    ));
+                }
+                let used: usize = spans.iter().map(Span::width).sum();
+                let mut lines = Vec::new();
+                if self.config.wrap {
+                    for (j, line) in text::wrap(&text, room.saturating_sub(used))
+                        .iter()
+                        .enumerate()
+                    {
+                        if j > 0 {
+                            let hanging = vec![Span::raw(" ".repeat(used))];
+                            lines.push(Line::from(std::mem::replace(&mut spans, hanging)));
+                        }
+                        spans.extend(self.words(&bidi::visual(line), style));
+                    }
+                } else {
+                    let width = room
+                        .saturating_sub(used)
+                        .saturating_sub(badges.iter().map(Span::width).sum());
+                    let fitted = bidi::fit(&text, width);
+                    let text = text::truncate(&fitted, width, self.config.truncation);
+                    spans.extend(self.words(&text, style));
+                }
+                spans.extend(badges);
+                lines.push(Line::from(spans));
+                ListItem::new(lines)
+            })
+            .collect();
+        let heights: Vec<usize> = todos.iter().map(ListItem::height).collect();
+        let mut title = tr("Todo's").to_string();
+        if let Some(project) = &self.active_project {
+            title.push_str(&format!(" +{project}"));
+        }
+        if let Some(context) = &self.active_context {
+            title.push_str(&format!(" @{context}"));
+        }
+        if let Some(assignee) = &self.active_assignee {
+            title.push_str(&tr_with(" for {assignee}", &[("assignee", assignee)]));
+        }
+        let todos = List::new(todos)
+            .block(Block::default().borders(Borders::ALL).title(title.clone()))
+            .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_symbol(selection);
+        let mut state = ListState::default();
+
+        match self.input_mode {
+            Mode::Editing
+            | Mode::Normal
+            | Mode::Help
+            | Mode::Tags
+            | Mode::Projects
+            | Mode::History
+            | Mode::Stats
+            | Mode::Matrix
+            | Mode::Themes => {
+                state.select(None);
+            }
+            Mode::Browse => {
+                state.select(Some(self.cursor_row as usize));
+            }
+        }
+
+        let mut image = None;
+        let mut links = Vec::new();
+        if let Some(todo) = self
+            .todos
+            .get(self.selected())
+            .filter(|_| self.show_detail && self.input_mode == Mode::Browse)
+        {
+            let mut area = columns[1];
+            if let Some(path) = images::protocol().and_then(|_| self.preview(todo)) {
+                let parts = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Percentage(50)])
+                    .split(area);
+                area = parts[0];
+                let block = Block::default().borders(Borders::ALL).title(tr("Preview"));
+                image = Some((PathBuf::from(path), block.inner(parts[1])));
+                f.render_widget(block, parts[1]);
+            }
+            let block = Block::default().borders(Borders::ALL).title(tr(
+                "Details (N notes, C comment, A attach, L step, Tab picks, O/Space use)",
+            ));
+            links.extend(Self::link_targets(todo, block.inner(area)));
+            f.render_widget(
+                Paragraph::new(self.detail(todo))
+                    .wrap(Wrap { trim: false })
+                    .block(block),
+                area,
+            );
+        }
+        self.image = image;
+        if self.show_completed {
+            let today = Local::now().date_naive();
+            let completed: Vec<ListItem> = self
+                .recently_completed()
+                .into_iter()
+                .map(|i| {
+                    let todo = &self.todos[i];
+                    let at = todo.completed.unwrap_or(todo.updated).with_timezone(&Local);
+                    let at = if at.date_naive() == today {
+                        at.format("%H:%M").to_string()
+                    } else {
+                        at.format("%Y-%m-%d").to_string()
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{at:>10} "), Style::default().fg(Color::DarkGray)),
+                        Span::raw(todo.title.clone()),
+                    ]))
+                })
+                .collect();
+            f.render_widget(
+                List::new(completed).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(tr("Recently completed (U to reopen the latest)")),
+                ),
+                columns[panels],
+            );
+        }
+        let (offset, header) = if self.config.table.is_empty() {
+            f.render_stateful_widget(todos, columns[0], &mut state);
+            (state.offset(), 0)
+        } else {
+            let table = self
+                .table(room)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                .highlight_spacing(HighlightSpacing::Always)
+                .highlight_symbol(selection);
+            let mut state = TableState::default().with_selected(state.selected());
+            f.render_stateful_widget(table, columns[0], &mut state);
+            (state.offset(), 1)
+        };
+        // Where each listed todo was drawn, for the links in its title
+        let list = Block::default().borders(Borders::ALL).inner(columns[0]);
+        let mut top = list.top() + header;
+        for (i, todo) in self
+            .visible()
+            .into_iter()
+            .map(|i| &self.todos[i])
+            .enumerate()
+            .skip(offset)
+        {
+            if top >= list.bottom() {
+                break;
+            }
+            let height = if header == 0 { heights[i] as u16 } else { 1 };
+            let row = Rect::new(list.x, top, list.width, height.min(list.bottom() - top));
+            links.extend(links::find(&todo.title).map(|url| Target {
+                area: row,
+                text: url.to_string(),
+                url: url.to_string(),
+            }));
+            top += height;
+        }
+        self.links = links;
+        if self.config.screen_reader && self.input_mode == Mode::Browse {
+            let row: usize = heights
+                .get(offset..self.cursor_row as usize)
+                .map_or(0, |above| above.iter().sum());
+            f.set_cursor(columns[0].x + 1, columns[0].y + 1 + header + row as u16);
+        }
+
+        let completions = match self.input_mode {
+            Mode::Editing => self.completions(),
+            _ => Vec::new(),
+        };
+        if !completions.is_empty() {
+            let height = (completions.len() as u16).min(5) + 2;
+            let area = Rect::new(
+                chunks[2].x,
+                chunks[2].y.saturating_sub(height),
+                chunks[2].width.min(30),
+                height,
+            );
+            let items: Vec<ListItem> = completions.into_iter().map(ListItem::new).collect();
+            let mut state = ListState::default().with_selected(Some(self.completion_row));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(
+                List::new(items)
+                    .block(Block::default().borders(Borders::ALL))
+                    .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                    .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                area,
+                &mut state,
+            );
+        }
+
+        if let Some((Prompt::Subtask, text)) = &self.prompt {
+            let area = centered_rect(f.size(), 50, 40);
+            let mut lines: Vec<ListItem> = self
+                .subtasks
+                .iter()
+                .map(|subtask| ListItem::new(format!("  {subtask}")))
+                .collect();
+            lines.push(ListItem::new(format!("> {text}")));
+            f.render_widget(Clear, area);
+            f.render_widget(
+                List::new(lines).block(Block::default().borders(Borders::ALL).title(tr(
+                    "Subtasks (Enter adds a line, Enter on an empty one splits)",
+                ))),
+                area,
+            );
+        }
+
+        let duplicate = self
+            .duplicate_of
+            .and_then(|id| self.todos.iter().find(|todo| todo.id == id));
+        if let Some(todo) = duplicate {
+            let area = centered_rect(f.size(), 50, 20);
+            let bold = Style::default().add_modifier(Modifier::BOLD);
+            let text = vec![
+                Line::from(tr_with(
+                    "\"{title}\" is already on the list.",
+                    &[("title", &todo.title)],
+                )),
+                Line::raw(""),
+                Line::from(vec![
+                    Span::styled("Enter", bold),
+                    Span::raw(tr(" to go to it, ")),
+                    Span::styled("a", bold),
+                    Span::raw(tr(" to add anyway, any other key to keep editing")),
+                ]),
+            ];
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(text).wrap(Wrap { trim: true }).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Yellow))
+                        .title(tr("Possible duplicate")),
+                ),
+                area,
+            );
+        }
+
+        if let Some(conflict) = &self.conflict {
+            let area = centered_rect(f.size(), 50, 20);
+            let bold = Style::default().add_modifier(Modifier::BOLD);
+            let choice = match &conflict.save_as {
+                Some(target) => Line::from(format!("{}{target}", tr("Save as: "))),
+                None => Line::from(vec![
+                    Span::styled("m", bold),
+                    Span::raw(tr(" to merge both, ")),
+                    Span::styled("o", bold),
+                    Span::raw(tr(" to overwrite it, ")),
+                    Span::styled("s", bold),
+                    Span::raw(tr(" to save elsewhere, ")),
+                    Span::styled("Esc", bold),
+                    Span::raw(tr(" to go back")),
+                ]),
+            };
+            let text = vec![
+                Line::from(tr_with(
+                    "{path} was changed by another program since it was loaded.",
+                    &[("path", &todo::data_file().display())],
+                )),
+                Line::raw(""),
+                choice,
+            ];
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(text).wrap(Wrap { trim: true }).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Yellow))
+                        .title(tr("Changed on disk")),
+                ),
+                area,
+            );
+        }
+
+        if self.input_mode == Mode::Matrix {
+            let area = centered_rect(f.size(), 90, 80);
+            let halves = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            let cells: Vec<Rect> = halves
+                .iter()
+                .flat_map(|half| {
+                    Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(*half)
+                        .to_vec()
+                })
+                .collect();
+            f.render_widget(Clear, area);
+            for (q, indices) in self.matrix().iter().enumerate() {
+                let items: Vec<ListItem> = indices
+                    .iter()
+                    .map(|&i| ListItem::new(self.todos[i].to_string()))
+                    .collect();
+                let selected = q == self.matrix_quadrant;
+                // The border color alone would not tell which quadrant is selected
+                let marker = if selected && self.config.high_contrast {
+                    "▶ "
+                } else {
+                    ""
+                };
+                let border = if selected {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                let mut state =
+                    ListState::default().with_selected(selected.then_some(self.matrix_row));
+                f.render_stateful_widget(
+                    List::new(items)
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(border)
+                                .title(format!("{marker}{} {}", q + 1, matrix::QUADRANTS[q])),
+                        )
+                        .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                        .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                    cells[q],
+                    &mut state,
+                );
+            }
+        }
+
+        if self.input_mode == Mode::Stats {
+            let stats = stats::compute(
+                &self.todos,
+                Local::now().date_naive(),
+                self.config.daily_goal,
+            );
+            let today = Local::now().date_naive();
+            let project = self.active_project.as_deref();
+            let days = BURNDOWN_WINDOWS[self.burndown_window];
+            let mut lines = stats.lines();
+            let velocity = stats::velocity(&self.todos, project, today, days);
+            lines.push(tr_with(
+                "Velocity: {rate} per day over {days} days",
+                &[("rate", &format!("{velocity:.1}")), ("days", &days)],
+            ));
+            let area = centered_rect(f.size(), 70, 70);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(lines.len() as u16 + 2),
+                    Constraint::Min(5),
+                ])
+                .split(area);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(lines.join("\n"))
+                    .block(Block::default().borders(Borders::ALL).title(tr("Stats"))),
+                rows[0],
+            );
+            let burndown: Vec<(String, u64)> = stats::burndown(&self.todos, project, today, days)
+                .into_iter()
+                .map(|(day, remaining)| (day.format("%d").to_string(), u64::from(remaining)))
+                .collect();
+            let data: Vec<(&str, u64)> = burndown
+                .iter()
+                .map(|(day, remaining)| (day.as_str(), *remaining))
+                .collect();
+            let title = match project {
+                Some(project) => tr_with(
+                    "Burndown +{project}, last {days} days (w to change)",
+                    &[("project", &project), ("days", &days)],
+                ),
+                None => tr_with(
+                    "Burndown, last {days} days (w to change)",
+                    &[("days", &days)],
+                ),
+            };
+            let bar_width = (rows[1].width.saturating_sub(2) / days as u16).max(2) - 1;
+            f.render_widget(
+                BarChart::default()
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .data(&data)
+                    .bar_width(bar_width)
+                    .bar_gap(1),
+                rows[1],
+            );
+        }
+
+        if self.input_mode == Mode::History {
+            let changes: Vec<ListItem> = self
+                .undo
+                .iter()
+                .rev()
+                .map(|(description, at, _)| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{} ", at.format("%H:%M:%S")),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(description.clone()),
+                    ]))
+                })
+                .collect();
+            let area = centered_rect(f.size(), 50, 60);
+            let mut state = ListState::default().with_selected(Some(self.history_row));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(
+                List::new(changes)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(tr("Undo history")),
+                    )
+                    .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                    .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                area,
+                &mut state,
+            );
+        }
+
+        if self.input_mode == Mode::Projects {
+            let rows = std::iter::once((tr("All projects").to_string(), None)).chain(
+                tags::projects(&self.todos)
+                    .into_iter()
+                    .map(|(project, open)| (format!("+{project}"), Some(open))),
+            );
+            let mut projects: Vec<ListItem> = rows
+                .map(|(name, open)| {
+                    let open = open.map(|open| tr_with(" ({open} open)", &[("open", &open)]));
+                    ListItem::new(format!("{name}{}", open.unwrap_or_default()))
+                })
+                .collect();
+            if let Some(name) = &self.new_project {
+                projects.push(ListItem::new(tr_with("New: +{name}", &[("name", name)])));
+            }
+            let area = centered_rect(f.size(), 40, 60);
+            let mut state = ListState::default().with_selected(Some(self.project_row));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(
+                List::new(projects)
+                    .block(Block::default().borders(Borders::ALL).title(tr("Projects")))
+                    .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                    .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                area,
+                &mut state,
+            );
+        }
+
+        if self.input_mode == Mode::Themes {
+            let names: Vec<ListItem> = themes::names().into_iter().map(ListItem::new).collect();
+            let area = centered_rect(f.size(), 30, 40);
+            let mut state = ListState::default().with_selected(Some(self.theme_row));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(
+                List::new(names)
+                    .block(Block::default().borders(Borders::ALL).title(tr("Themes")))
+                    .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                    .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                area,
+                &mut state,
+            );
+        }
+
+        if self.input_mode == Mode::Tags {
+            let tags: Vec<ListItem> = tags::counts(&self.todos)
+                .into_iter()
+                .enumerate()
+                .map(|(i, (tag, count))| {
+                    let name = match &self.renaming {
+                        Some(name) if i == self.tag_row => format!("#{tag} -> #{name}"),
+                        _ => format!("#{tag}"),
+                    };
+                    let style = self.tag_style(&tag).unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::styled(name, style),
+                        Span::raw(format!(" ({count})")),
+                    ]))
+                })
+                .collect();
+            let area = centered_rect(f.size(), 40, 60);
+            let mut state = ListState::default().with_selected(Some(self.tag_row));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(
+                List::new(tags)
+                    .block(Block::default().borders(Borders::ALL).title(tr("Tags")))
+                    .highlight_style(self.style("highlight", Style::new().on_dark_gray()))
+                    .highlight_symbol(self.selection_symbol().unwrap_or_default()),
+                area,
+                &mut state,
+            );
+        }
+
+        let mut mode_indicator_text = match &self.config.status_bar {
+            Some(format) => self.status_bar(format),
+            None => self.built_in_status_bar(),
+        };
+        if let Some(jump) = &self.jump {
+            mode_indicator_text.push_str(&format!(" | :{jump}"));
+        }
+        if let Some((prompt, text)) = &self.prompt {
+            mode_indicator_text.push_str(&format!(" | {prompt}: {text}"));
+        }
+        if let Some((register, _)) = &self.recording {
+            mode_indicator_text.push_str(&tr_with(
+                " | recording @{register}",
+                &[("register", register)],
+            ));
+        }
+        if let Some((toast, _)) = self
+            .toast
+            .as_ref()
+            .filter(|(_, raised)| raised.elapsed() < TOAST_DURATION)
+        {
+            mode_indicator_text.push_str(&format!(" | {toast}"));
+        }
+        let mode_indicator_widget = Paragraph::new(Text::from(Line::from(mode_indicator_text)));
+        f.render_widget(mode_indicator_widget, chunks[3]);
+
+        // Return OK
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn press(home: &mut Home, code: KeyCode) -> Result<()> {
+        if let Some(action) = home.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE))? {
+            home.update(action)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_and_replay_macro() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c", "d", "e"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "qax".chars().map(KeyCode::Char).chain([KeyCode::Down]) {
+            press(&mut home, code)?;
+        }
+        press(&mut home, KeyCode::Char('q'))?;
+        for code in "2@a".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        let done: Vec<bool> = home.todos.iter().map(|todo| todo.done).collect();
+        assert_eq!(done, vec![true, true, true, false, false]);
+        assert_eq!(home.cursor_row, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot_repeats_last_change_on_selection() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        press(&mut home, KeyCode::Char('.'))?;
+        for code in [
+            KeyCode::Char('x'),
+            KeyCode::Down,
+            KeyCode::Down,
+            KeyCode::Char('.'),
+        ] {
+            press(&mut home, code)?;
+        }
+        let done: Vec<bool> = home.todos.iter().map(|todo| todo.done).collect();
+        assert_eq!(done, vec![true, false, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_prefixes() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c", "d", "e", "f", "g"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "3dd2x5j".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        let todos: Vec<(String, bool)> = home
+            .todos
+            .iter()
+            .map(|todo| (todo.title.clone(), todo.done))
+            .collect();
+        assert_eq!(
+            todos,
+            vec![
+                ("d".into(), true),
+                ("e".into(), true),
+                ("f".into(), false),
+                ("g".into(), false),
+            ]
+        );
+        assert_eq!(home.cursor_row, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_jump_to_index() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = (0..20).map(|i| TodoItem::new(i.to_string())).collect();
+        home.input_mode = Mode::Browse;
+        for code in ":12".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.cursor_row, 12);
+        for code in ":99".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.cursor_row, 19);
+        Ok(())
+    }
+
+    #[test]
+    fn test_marks_follow_their_todo() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c", "d"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "jjma".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        home.todos.swap(0, 2);
+        for code in "'a".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.cursor_row, 0);
+        assert_eq!(home.todos[0].title, "c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_starred_todos_pinned_to_top() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c", "d"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "jjsjs".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["c", "a", "b", "d"]);
+        assert_eq!(home.cursor_row, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_tag_from_tags_screen() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::parse("a #job"), TodoItem::parse("b #work")];
+        for code in "tjrjob".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(tags::counts(&home.todos), vec![("job".into(), 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_switch_project() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![
+            TodoItem::parse("a +home"),
+            TodoItem::parse("b +work"),
+            TodoItem::parse("c +work"),
+        ];
+        for code in "pjj".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.active_project.as_deref(), Some("work"));
+        for code in "vjx".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        assert!(home.todos[2].done);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reopen_latest_completion() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "xjjx".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        home.todos[0].completed = home.todos[0]
+            .completed
+            .map(|at| at - chrono::Duration::minutes(1));
+        assert_eq!(home.recently_completed(), vec![2, 0]);
+        press(&mut home, KeyCode::Char('U'))?;
+        let done: Vec<bool> = home.todos.iter().map(|todo| todo.done).collect();
+        assert_eq!(done, vec![true, false, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reopen_written_back_to_vault() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-reopen-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let note = dir.join("home.md");
+        std::fs::write(&note, "- [ ] fix sink\n")?;
+        let mut home = Home::new();
+        home.todos = vault::scan(&dir)?;
+        home.config.vault = Some(crate::config::VaultConfig { dir: dir.clone() });
+        home.input_mode = Mode::Browse;
+
+        press(&mut home, KeyCode::Char('x'))?;
+        assert_eq!(std::fs::read_to_string(&note)?, "- [x] fix sink\n");
+        press(&mut home, KeyCode::Char('U'))?;
+        assert_eq!(std::fs::read_to_string(&note)?, "- [ ] fix sink\n");
+        press(&mut home, KeyCode::Char('x'))?;
+        press(&mut home, KeyCode::Char('u'))?;
+        assert!(!home.todos[0].done);
+        assert_eq!(std::fs::read_to_string(&note)?, "- [ ] fix sink\n");
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_back_to_any_point() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = ["a", "b", "c"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        home.input_mode = Mode::Browse;
+        for code in "xjsddu".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["b", "a", "c"]);
+        let history: Vec<&str> = home
+            .undo
+            .iter()
+            .map(|(change, ..)| change.as_str())
+            .collect();
+        assert_eq!(history, vec!["Complete \"a\"", "Star \"b\""]);
+
+        for code in [KeyCode::Char('H'), KeyCode::Down, KeyCode::Enter] {
+            press(&mut home, code)?;
+        }
+        assert!(home.undo.is_empty());
+        assert!(!home.todos[0].done);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_notes_in_editor() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::new("a".into())];
+        home.input_mode = Mode::Browse;
+        press(&mut home, KeyCode::Char('N'))?;
+        assert_eq!(home.external_edit, None);
+        press(&mut home, KeyCode::Enter)?;
+        let action =
+            home.handle_key_events(KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE))?;
+        let Some(Action::OpenEditor(path)) = home.update(action.unwrap())? else {
+            panic!("expected the editor to open");
+        };
+        std::fs::write(&path, "first line\nsecond line\n\n")?;
+        home.update(Action::EditorClosed(path.clone()))?;
+        assert_eq!(
+            home.todos[0].notes.as_deref(),
+            Some("first line\nsecond line")
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            home.undo.last().map(|(change, ..)| change.as_str()),
+            Some("Edit notes of \"a\"")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_attach_from_detail_pane() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::parse("read spec attach:spec.pdf")];
+        home.input_mode = Mode::Browse;
+        for code in [KeyCode::Enter, KeyCode::Char('A')]
+            .into_iter()
+            .chain("~/notes.txt".chars().map(KeyCode::Char))
+            .chain([KeyCode::Enter, KeyCode::Tab])
+        {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.todos[0].attachments, vec!["spec.pdf", "~/notes.txt"]);
+        let lines: Vec<String> = home
+            .detail(&home.todos[0])
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        assert!(lines.contains(&"▸ ~/notes.txt".to_string()));
+        assert!(lines.contains(&"  spec.pdf".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_by_assignee() -> Result<()> {
+        let mut home = Home::new();
+        home.config.team = [("ana".to_string(), "green".to_string())].into();
+        home.todos = vec![
+            TodoItem::parse("a assign:sam"),
+            TodoItem::parse("b"),
+            TodoItem::parse("c assign:sam"),
+        ];
+        press(&mut home, KeyCode::Char('a'))?;
+        assert_eq!(home.visible(), Vec::<usize>::new());
+        press(&mut home, KeyCode::Char('a'))?;
+        assert_eq!(home.visible(), vec![0, 2]);
+        press(&mut home, KeyCode::Char('a'))?;
+        assert_eq!(home.visible(), vec![0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_from_detail_pane() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::new("a".into())];
+        home.input_mode = Mode::Browse;
+        for code in [KeyCode::Enter, KeyCode::Char('C')]
+            .into_iter()
+            .chain("half done".chars().map(KeyCode::Char))
+            .chain([KeyCode::Enter])
+        {
+            press(&mut home, code)?;
+        }
+        let comments: Vec<&str> = home.todos[0]
+            .comments
+            .iter()
+            .map(|comment| comment.text.as_str())
+            .collect();
+        assert_eq!(comments, vec!["half done"]);
+        assert_eq!(home.prompt, None);
+
+        let document = Document::from_todos(&home.todos);
+        assert_eq!(document.todos()[0].comments, home.todos[0].comments);
+        Ok(())
+    }
+
+    #[test]
+    fn test_checklist_in_detail_pane() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::parse("pack attach:list.txt")];
+        home.input_mode = Mode::Browse;
+        press(&mut home, KeyCode::Enter)?;
+        for step in ["socks", "charger"] {
+            press(&mut home, KeyCode::Char('L'))?;
+            for c in step.chars() {
+                press(&mut home, KeyCode::Char(c))?;
+            }
+            press(&mut home, KeyCode::Enter)?;
+        }
+        // Space does nothing while the attachment is picked
+        for code in [
+            KeyCode::Char(' '),
+            KeyCode::Tab,
+            KeyCode::Tab,
+            KeyCode::Char(' '),
+        ] {
+            press(&mut home, code)?;
+        }
+        let done: Vec<bool> = home.todos[0].checklist.iter().map(|s| s.done).collect();
+        assert_eq!(done, vec![false, true]);
+        assert_eq!(progress(&home.todos[0]), "[1/2]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_yank_and_paste() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![
+            TodoItem::parse("weekly report #work"),
+            TodoItem::new("b".into()),
+        ];
+        home.todos[0].done = true;
+        home.input_mode = Mode::Browse;
+        for code in "yy2p".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        press(&mut home, KeyCode::Char('P'))?;
+        for code in [KeyCode::Backspace; 6]
+            .into_iter()
+            .chain("memo".chars().map(KeyCode::Char))
+            .chain([KeyCode::Enter])
+        {
+            press(&mut home, code)?;
+        }
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "weekly report",
+                "weekly report",
+                "weekly report",
+                "weekly memo",
+                "b"
+            ]
+        );
+        assert!(home.todos[1..4]
+            .iter()
+            .all(|todo| !todo.done && todo.tags == ["work"]));
+        assert_eq!(home.cursor_row, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_warning_on_add() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![
+            TodoItem::new("a".into()),
+            TodoItem::new("Call the dentist".into()),
+        ];
+        home.input_mode = Mode::Editing;
+        home.input = Input::new("call the dentist +health".into());
+        press(&mut home, KeyCode::Enter)?;
+        assert_eq!(home.duplicate_of, Some(home.todos[1].id));
+        // A reload moving the todo in between still leads to it
+        home.todos.swap(0, 1);
+        press(&mut home, KeyCode::Enter)?;
+        assert!(home.input_mode == Mode::Browse);
+        assert_eq!(home.cursor_row, 0);
+        assert_eq!(home.todos.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_into_subtasks() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![
+            TodoItem::parse("move house +life"),
+            TodoItem::new("b".into()),
+        ];
+        home.input_mode = Mode::Browse;
+        press(&mut home, KeyCode::Char('B'))?;
+        for line in ["book van", "pack #boxes", ""] {
+            for c in line.chars() {
+                press(&mut home, KeyCode::Char(c))?;
+            }
+            press(&mut home, KeyCode::Enter)?;
+        }
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["move house", "book van", "pack", "b"]);
+        assert_eq!(home.todos[2].parent, Some(home.todos[0].id));
+        assert_eq!(home.todos[2].project.as_deref(), Some("life"));
+        assert_eq!(home.depth(&home.todos[2]), 1);
+
+        home.cursor_row = 1;
+        press(&mut home, KeyCode::Char('B'))?;
+        for code in "call movers"
+            .chars()
+            .map(KeyCode::Char)
+            .chain([KeyCode::Enter; 2])
+        {
+            press(&mut home, code)?;
+        }
+        home.cursor_row = 0;
+        press(&mut home, KeyCode::Char('B'))?;
+        for code in "tell bank"
+            .chars()
+            .map(KeyCode::Char)
+            .chain([KeyCode::Enter; 2])
+        {
+            press(&mut home, code)?;
+        }
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "move house",
+                "book van",
+                "call movers",
+                "pack",
+                "tell bank",
+                "b"
+            ]
+        );
+        assert_eq!(home.depth(&home.todos[2]), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reload_picks_up_other_writers() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("doit-reload-{}.json", Uuid::new_v4()));
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::parse("typed here")];
+        assert_eq!(home.reload(&path)?, None);
+
+        todo::update(&path, |todos| {
+            todos.push(TodoItem::parse("added elsewhere"))
+        })?;
+        assert!(home.reload(&path)?.is_some());
+        let mut titles: Vec<&str> = home.todos.iter().map(|t| t.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["added elsewhere", "typed here"]);
+        assert_eq!(home.reload(&path)?, None);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_files_keep_their_todos() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-shared-{}", Uuid::new_v4()));
+        let (own, team) = (dir.join("home.json"), dir.join("team.json"));
+        todo::update(&team, |todos| {
+            todos.push(TodoItem::parse("book the offsite"))
+        })?;
+        let mut home = Home::new();
+        home.config.shared_files.insert("team".into(), team.clone());
+        home.shared.insert("team".into(), Document::default());
+        home.todos = vec![TodoItem::parse("water plants")];
+        assert!(home.reload(&own)?.is_some());
+        let origins: Vec<(&str, Option<&str>)> = home
+            .todos
+            .iter()
+            .map(|todo| (todo.title.as_str(), todo.origin.as_deref()))
+            .collect();
+        assert_eq!(
+            origins,
+            vec![("water plants", None), ("book the offsite", Some("team"))]
+        );
+
+        home.todos[1].done = true;
+        home.record(Utc::now());
+        assert_eq!(home.document.todos().len(), 1);
+        assert!(home.shared["team"].todos()[0].done);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_theme_picker_previews_and_restores() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::EnterThemesMode)?;
+        let gruvbox = home.style("highlight", Style::default());
+        assert_ne!(gruvbox, Style::default());
+        press(&mut home, KeyCode::Char('j'))?;
+        assert_ne!(home.style("highlight", Style::default()), gruvbox);
+
+        home.update(Action::ExitCurrentMode)?;
+        assert!(home.input_mode == Mode::Normal);
+        assert_eq!(home.style("highlight", Style::default()), Style::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_announcement() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::parse("call bank"), TodoItem::parse("pay rent")];
+        assert_eq!(home.announcement(), "Normal mode");
+        home.update(Action::EnterBrowseMode)?;
+        home.update(Action::BrowseListDown)?;
+        assert_eq!(home.announcement(), "Browsing mode, todo 2 of 2: pay rent");
+        Ok(())
+    }
+
+    #[test]
+    fn test_high_contrast_marks() -> Result<()> {
+        let mut home = Home::new();
+        home.config.high_contrast = true;
+        home.todos = vec![TodoItem::parse("call bank"), TodoItem::parse("pay rent")];
+        home.todos[1].done = true;
+        home.update(Action::EnterBrowseMode)?;
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 12))?;
+        terminal.draw(|f| home.draw(f, f.size()).unwrap())?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("██▶ 0: call bank"));
+        assert!(screen.contains("✓ 1: pay rent"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsaved_changes_marked() -> Result<()> {
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::new("pay rent".into())];
+        home.saved = home.todos.clone();
+        home.input_mode = Mode::Browse;
+        if let Some(changed) = home.update(Action::CompleteTodo(0))? {
+            home.update(changed)?;
+        }
+        assert!(home.dirty);
+        home.update(Action::Undo(1))?;
+        if let Some(changed) = home.snapshot() {
+            home.update(changed)?;
+        }
+        assert!(!home.dirty);
+        let mut actions = Vec::new();
+        for code in ":w".chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+            let key = KeyEvent::new(code, KeyModifiers::NONE);
+            actions.extend(home.handle_key_events(key)?);
+        }
+        assert_eq!(actions, vec![Action::Save]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_conflict_saved_elsewhere() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-conflict-{}", Uuid::new_v4()));
+        let target = dir.join("mine.json");
+        let mut home = Home::new();
+        home.todos = vec![TodoItem::new("pay rent".into())];
+        home.conflict = Some(Conflict {
+            quit: true,
+            save_as: None,
+        });
+        assert!(home.hold_quit()?);
+        let mut actions = Vec::new();
+        for code in std::iter::once('s')
+            .chain(target.display().to_string().chars())
+            .map(KeyCode::Char)
+            .chain([KeyCode::Enter])
+        {
+            let key = KeyEvent::new(code, KeyModifiers::NONE);
+            actions.extend(home.handle_key_events(key)?);
+        }
+        assert_eq!(actions, vec![Action::Quit]);
+        assert_eq!(home.conflict, None);
+        let titles: Vec<String> = todo::load(&target)?
+            .into_iter()
+            .map(|todo| todo.title)
+            .collect();
+        assert_eq!(titles, vec!["pay rent"]);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_viewer_changes_nothing() -> Result<()> {
+        let todos = vec![
+            TodoItem::new("pay rent".into()),
+            TodoItem::new("call bank".into()),
+        ];
+        let mut home = Home::viewer(todos.clone());
+        for code in "jx".chars().map(KeyCode::Char) {
+            press(&mut home, code)?;
+        }
+        assert_eq!(home.cursor_row, 1);
+        assert_eq!(home.todos, todos);
+        assert!(matches!(
+            home.update(Action::InsertTodo("buy milk".into()))?,
+            Some(Action::Toast(_))
+        ));
+        assert_eq!(home.todos, todos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_bar_template() {
+        let mut home = Home::new();
+        home.todos = ["pay rent +home", "call bank +home", "write report +work"]
+            .map(TodoItem::parse)
+            .to_vec();
+        home.todos[0].done = true;
+        home.active_project = Some("home".into());
+        home.dirty = true;
+        assert_eq!(
+            home.status_bar("{mode} {modified} | {project} {pending}/{total} | {filter}"),
+            "Normal [+] | home 1/2 | +home"
+        );
+    }
+
+    #[test]
+    fn test_list_item_template() {
+        let home = Home::new();
+        let format = "{index}. {priority}{title}{?due} (due {due}){/due}{?tags} {tags}{/tags}";
+        let mut todo = TodoItem::parse("pay rent #home #bills");
+        todo.flagged = true;
+        assert_eq!(
+            home.list_item(format, 3, &todo),
+            "3. ⚑pay rent #home #bills"
+        );
+        let todo = TodoItem::parse("call bank due:2024-05-01");
+        assert_eq!(
+            home.list_item(format, 4, &todo),
+            "4. call bank (due 2024-05-01)"
+        );
+    }
+
+    #[test]
+    fn test_table_header_marks_sort_column() -> Result<()> {
+        let mut home = Home::new();
+        home.config.table = vec![Column::Title, Column::Urgency];
+        home.todos = vec![TodoItem::parse("pay rent")];
+        home.update(Action::SortByUrgency)?;
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 12))?;
+        terminal.draw(|f| home.draw(f, f.size()).unwrap())?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Urgency ▼"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_sort() -> Result<()> {
+        let mut home = Home::new();
+        home.config.table = vec![Column::Title, Column::Due];
+        home.todos = ["pay rent", "call bank", "book flights"]
+            .map(TodoItem::parse)
+            .to_vec();
+        home.update(Action::CycleSort)?;
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["book flights", "call bank", "pay rent"]);
+        home.update(Action::CycleSort)?;
+        let titles: Vec<&str> = home.todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["pay rent", "call bank", "book flights"]);
+        assert_eq!(home.sorted_by, Some((Column::Title, true)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrap_long_titles() -> Result<()> {
+        let mut home = Home::new();
+        home.config.wrap = true;
+        home.todos = vec![
+            TodoItem::parse("renew the passport before the summer trip to the coast"),
+            TodoItem::parse("pay rent"),
+        ];
+        home.update(Action::EnterBrowseMode)?;
+        home.update(Action::BrowseListDown)?;
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(40, 12))?;
+        terminal.draw(|f| home.draw(f, f.size()).unwrap())?;
+        let rows: Vec<String> = terminal
+            .backend()
+            .buffer()
+            .content
+            .chunks(40)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect();
+        assert!(rows[3].contains("  0: renew the passport before the"));
+        assert!(rows[4].contains("     summer trip to the coast "));
+        assert!(rows[5].contains(">>1: pay rent"));
         Ok(())
     }
 }
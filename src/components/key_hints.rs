@@ -0,0 +1,68 @@
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n::tr};
+
+/// A which-key style popup listing how a half-typed key sequence can be finished.
+#[derive(Default)]
+pub struct KeyHints {
+    hints: Vec<(String, String)>,
+}
+
+impl KeyHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Component for KeyHints {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::KeyHints(hints) = action {
+            self.hints = hints;
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if self.hints.is_empty() {
+            return Ok(());
+        }
+        let keys_width = self
+            .hints
+            .iter()
+            .map(|(keys, _)| keys.len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = self
+            .hints
+            .iter()
+            .map(|(keys, action)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{keys:<keys_width$}  "),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(action.clone()),
+                ])
+            })
+            .collect();
+        let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(area.right() - width, area.bottom() - height, width, height);
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(tr("Keys"))),
+            popup,
+        );
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn buildup(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
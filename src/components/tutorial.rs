@@ -0,0 +1,144 @@
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    config::{Config, Keymap},
+    i18n::{tr, tr_with},
+};
+
+/// One prompt and the action that completes it.
+struct Step {
+    prompt: &'static str,
+    done: fn(&Action) -> bool,
+}
+
+const STEPS: [Step; 6] = [
+    Step {
+        prompt: "Press i to start writing a todo.",
+        done: |action| *action == Action::EnterCommandMode,
+    },
+    Step {
+        prompt: "Type a todo, such as \"water the plants due:tomorrow +home\", then press Enter.",
+        done: |action| *action == Action::AddTodo,
+    },
+    Step {
+        prompt: "Press Esc to stop writing.",
+        done: |action| *action == Action::ExitCurrentMode,
+    },
+    Step {
+        prompt: "Press v to browse the list.",
+        done: |action| *action == Action::EnterBrowseMode,
+    },
+    Step {
+        prompt: "Move down the list with {down} and back up with {up}.",
+        done: |action| matches!(action, Action::BrowseListDown | Action::BrowseListUp),
+    },
+    Step {
+        prompt: "Press x to complete the selected todo.",
+        done: |action| matches!(action, Action::CompleteTodo(_)),
+    },
+];
+
+/// Walks a new user through the basics with prompts drawn over the real UI, moving on as each
+/// one is carried out.
+#[derive(Default)]
+pub struct Tutorial {
+    step: usize,
+    keymap: Keymap,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn prompt(&self) -> String {
+        let (down, up) = self.keymap.labels();
+        match STEPS.get(self.step) {
+            Some(step) => tr_with(step.prompt, &[("down", &down), ("up", &up)]),
+            None => {
+                tr("That's the basics! Press Esc to leave browsing and Ctrl-c to quit.").to_string()
+            }
+        }
+    }
+}
+
+impl Component for Tutorial {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.keymap = config.keymap;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if STEPS
+            .get(self.step)
+            .is_some_and(|step| (step.done)(&action))
+        {
+            self.step += 1;
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let width = area.width.min(48);
+        let height = 5.min(area.height);
+        let popup = Rect::new(area.right() - width, area.y, width, height);
+        let title = tr_with(
+            "Tutorial {step}/{steps}",
+            &[
+                ("step", &(self.step + 1).min(STEPS.len())),
+                ("steps", &STEPS.len()),
+            ],
+        );
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(self.prompt())
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Cyan))
+                        .title(title),
+                ),
+            popup,
+        );
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn buildup(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_steps_advance_on_matching_actions() -> Result<()> {
+        let mut tutorial = Tutorial::new();
+        tutorial.update(Action::EnterBrowseMode)?;
+        assert_eq!(tutorial.step, 0);
+        for action in [
+            Action::EnterCommandMode,
+            Action::AddTodo,
+            Action::ExitCurrentMode,
+            Action::EnterBrowseMode,
+            Action::BrowseListUp,
+        ] {
+            tutorial.update(action)?;
+        }
+        assert_eq!(tutorial.prompt(), "Press x to complete the selected todo.");
+        tutorial.update(Action::CompleteTodo(0))?;
+        assert!(tutorial.prompt().starts_with("That's the basics!"));
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, fmt, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+};
 
 use color_eyre::eyre::Result;
 use config::Value;
@@ -10,11 +14,23 @@ use serde::{
     Deserialize, Serialize,
 };
 use serde_json::Value as JsonValue;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{action::Action, mode::Mode, table::Column, text::Truncation};
 
-use crate::{action::Action, mode::Mode};
+pub mod validate;
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
+/// Every file name the config is read from, merged in this order.
+pub const CONFIG_FILES: [(&str, config::FileFormat); 5] = [
+    ("config.json5", config::FileFormat::Json5),
+    ("config.json", config::FileFormat::Json),
+    ("config.yaml", config::FileFormat::Yaml),
+    ("config.toml", config::FileFormat::Toml),
+    ("config.ini", config::FileFormat::Ini),
+];
+
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
     #[serde(default)]
@@ -31,6 +47,387 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    /// A built-in theme from `themes::PRESETS`, whose styles win over those set by hand.
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// The syntect theme that fenced code in notes is highlighted with, such as `InspiredGitHub`.
+    /// One going with `theme` is picked when unset.
+    #[serde(default)]
+    pub code_theme: Option<String>,
+    /// Draws borders and symbols in plain ASCII, for terminals and fonts that show box drawing
+    /// badly.
+    #[serde(default)]
+    pub ascii: bool,
+    /// Leaves out borders, spells out the mode and the selected todo on the status line and keeps
+    /// the cursor on the selection, so that a screen reader can follow along.
+    #[serde(default)]
+    pub screen_reader: bool,
+    /// Uses the high-contrast theme, draws a bigger selection marker and pairs every color that
+    /// means something with a symbol, for low vision.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Lets long todos go on over as many lines as they need in the list, lined up under where
+    /// they start, instead of being cut off.
+    #[serde(default)]
+    pub wrap: bool,
+    /// Where todos too long for the list or a table column are cut short when not wrapping,
+    /// `end` or `middle`.
+    #[serde(default)]
+    pub truncation: Truncation,
+    /// Turns off blinking text, for anyone who finds it distracting.
+    #[serde(default)]
+    pub reduced_motion: bool,
+    /// The language to show, such as `de`, instead of the one from the locale.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Where todos are saved, instead of `.data/home.json` under the working directory. Ending it
+    /// in `.zst` or `.gz` keeps it compressed.
+    #[serde(default)]
+    pub data_file: Option<PathBuf>,
+    #[serde(default)]
+    pub keymap: Keymap,
+    /// The key that `<leader>` stands for in keybindings, such as `<space>` or `<\>`. Bindings
+    /// that use `<leader>` do nothing until it is set.
+    #[serde(default)]
+    pub leader: Option<String>,
+    #[serde(default)]
+    pub exit_summary: ExitSummary,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+    #[serde(default)]
+    pub google_tasks: Option<GoogleTasksConfig>,
+    #[serde(default)]
+    pub vault: Option<VaultConfig>,
+    #[serde(default)]
+    pub jira: Option<JiraConfig>,
+    #[serde(default)]
+    pub notion: Option<NotionConfig>,
+    #[serde(default)]
+    pub archive: Option<ArchiveConfig>,
+    #[serde(default)]
+    pub streaks: Option<StreaksConfig>,
+    /// Names of daily habits to check off, kept apart from the todos.
+    #[serde(default)]
+    pub habits: Vec<String>,
+    /// How many todos to complete each day.
+    #[serde(default)]
+    pub daily_goal: Option<usize>,
+    #[serde(default)]
+    pub urgency: UrgencyConfig,
+    /// Colors for tags in the list, by tag name, as set on the tags screen.
+    #[serde(default)]
+    pub tag_colors: BTreeMap<String, String>,
+    /// People todos can be assigned to on a shared list, with the color of their badge.
+    #[serde(default)]
+    pub team: BTreeMap<String, String>,
+    /// More data files, such as one shared with a team, listed together with the data file and
+    /// each saved on its own, by name.
+    #[serde(default)]
+    pub shared_files: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    pub status_line: StatusLineConfig,
+    /// The bar under the list as a template, instead of the built-in one. It may hold `{mode}`,
+    /// `{modified}` (`[+]` or `[RO]`), `{project}`, `{context}`, `{assignee}`, `{filter}`,
+    /// `{pending}`, `{done}` and `{total}` (of the todos listed), `{goal}` and `{streak}`, and
+    /// sections as in `list_item`. Prompts and messages are still added after it.
+    #[serde(default)]
+    pub status_bar: Option<String>,
+    /// How each todo in the list reads, instead of its index and the todo. It may hold
+    /// `{index}`, `{title}`, `{project}`, `{context}`, `{tags}`, `{due}`, `{estimate}`,
+    /// `{priority}` (`⚑` when flagged) and `{todo}` (all of it), and `{?due} (due {due}){/due}`
+    /// sections that are left out when what they name is empty.
+    #[serde(default)]
+    pub list_item: Option<String>,
+    /// The columns to list todos in as a table, out of `title`, `due`, `priority`, `tags`, `age`
+    /// and `urgency`. The list is drawn as a list when there are none.
+    #[serde(default)]
+    pub table: Vec<Column>,
+}
+
+/// Which keys move through the list while browsing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Keymap {
+    /// `j` and `k`
+    #[default]
+    Vim,
+    /// `Ctrl-n` and `Ctrl-p`
+    Emacs,
+    /// The arrow keys alone
+    Arrows,
+}
+
+impl Keymap {
+    /// How the keys for moving down and up are written in prompts.
+    pub fn labels(self) -> (&'static str, &'static str) {
+        match self {
+            Keymap::Vim => ("j", "k"),
+            Keymap::Emacs => ("Ctrl-n", "Ctrl-p"),
+            Keymap::Arrows => ("Down", "Up"),
+        }
+    }
+}
+
+/// Controls the reminder of open todos printed to stdout after the TUI closes.
+#[derive(Clone, Debug, Deserialize, Default)]
+pub struct ExitSummary {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub due_today_only: bool,
+}
+
+/// Settings for the headless reminder daemon.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DaemonConfig {
+    /// Days before the due date at which to send a reminder; `0` reminds on the day itself.
+    #[serde(default = "DaemonConfig::default_lead_days")]
+    pub lead_days: Vec<u64>,
+    /// Seconds between re-reads of the todo file.
+    #[serde(default = "DaemonConfig::default_poll_secs")]
+    pub poll_secs: u64,
+}
+
+impl DaemonConfig {
+    fn default_lead_days() -> Vec<u64> {
+        vec![1, 0]
+    }
+
+    fn default_poll_secs() -> u64 {
+        60
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            lead_days: Self::default_lead_days(),
+            poll_secs: Self::default_poll_secs(),
+        }
+    }
+}
+
+/// Broker to publish todo lifecycle events to; publishing is off unless this section is present.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "MqttConfig::default_port")]
+    pub port: u16,
+    #[serde(default = "MqttConfig::default_client_id")]
+    pub client_id: String,
+    /// Prefix for the `events`, `open` and `due_today` topics.
+    #[serde(default = "MqttConfig::default_topic")]
+    pub topic: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl MqttConfig {
+    fn default_port() -> u16 {
+        1883
+    }
+
+    fn default_client_id() -> String {
+        env!("CARGO_PKG_NAME").to_string()
+    }
+
+    fn default_topic() -> String {
+        "doit".to_string()
+    }
+}
+
+/// SMTP relay used by `digest --email`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: String,
+}
+
+/// Encrypted sync through a shared directory, used by the `sync` command.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SyncConfig {
+    /// Directory every device reads and writes its encrypted document to.
+    pub remote: PathBuf,
+    /// The shared key, defaulting to `sync.key` in the config directory.
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+/// Google Tasks list to sync with, authorized through a "Desktop app" OAuth client.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GoogleTasksConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default = "GoogleTasksConfig::default_tasklist")]
+    pub tasklist: String,
+    /// Sync in the background this often while the TUI runs.
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+impl GoogleTasksConfig {
+    fn default_tasklist() -> String {
+        "@default".to_string()
+    }
+}
+
+/// A folder of Markdown notes, such as an Obsidian vault, whose `- [ ]` checkboxes show up as
+/// todos.
+#[derive(Clone, Debug, Deserialize)]
+pub struct VaultConfig {
+    pub dir: PathBuf,
+}
+
+/// A Jira Cloud site, authenticated with an API token.
+#[derive(Clone, Debug, Deserialize)]
+pub struct JiraConfig {
+    pub base_url: String,
+    pub email: String,
+    pub api_token: String,
+    /// JQL queries keyed by the project their issues are filed under.
+    #[serde(default)]
+    pub projects: BTreeMap<String, String>,
+}
+
+/// A Notion database to sync with, through an internal integration shared with it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NotionConfig {
+    pub token: String,
+    pub database_id: String,
+    #[serde(default = "NotionConfig::default_title_property")]
+    pub title_property: String,
+    #[serde(default = "NotionConfig::default_done_property")]
+    pub done_property: String,
+    #[serde(default = "NotionConfig::default_due_property")]
+    pub due_property: String,
+}
+
+impl NotionConfig {
+    fn default_title_property() -> String {
+        "Name".to_string()
+    }
+
+    fn default_done_property() -> String {
+        "Done".to_string()
+    }
+
+    fn default_due_property() -> String {
+        "Due".to_string()
+    }
+}
+
+/// Moves completed todos into compressed yearly archives once they are old enough.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArchiveConfig {
+    #[serde(default = "ArchiveConfig::default_after_days")]
+    pub after_days: u64,
+}
+
+impl ArchiveConfig {
+    fn default_after_days() -> u64 {
+        90
+    }
+}
+
+/// Counts days in a row with something completed, celebrating the milestones.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StreaksConfig {
+    #[serde(default = "StreaksConfig::default_milestones")]
+    pub milestones: Vec<u32>,
+}
+
+impl StreaksConfig {
+    fn default_milestones() -> Vec<u32> {
+        vec![3, 7, 14, 30, 50, 100, 365]
+    }
+}
+
+/// How much each part of a todo counts towards its urgency score. The defaults follow
+/// Taskwarrior's.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct UrgencyConfig {
+    /// For a todo a week or more overdue, tapering off to a fifth two weeks before it is due.
+    pub due: f64,
+    /// For a flagged todo.
+    pub priority: f64,
+    pub starred: f64,
+    /// For a todo a year or more old, less for younger ones.
+    pub age: f64,
+    /// For a todo with three or more tags, less for fewer.
+    pub tags: f64,
+    pub project: f64,
+    /// Added for each of these tags a todo has, by tag name. Negative boosts sink a tag.
+    pub tag_boosts: BTreeMap<String, f64>,
+}
+
+impl Default for UrgencyConfig {
+    fn default() -> Self {
+        Self {
+            due: 12.0,
+            priority: 6.0,
+            starred: 15.0,
+            age: 2.0,
+            tags: 1.0,
+            project: 1.0,
+            tag_boosts: BTreeMap::new(),
+        }
+    }
+}
+
+/// What `doit status-line` prints.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StatusLineConfig {
+    /// Counts are written as `{done}` (today), `{open}`, `{overdue}` and `{flagged}`, and
+    /// colors as `{red}`, `{green}`, `{yellow}`, `{blue}`, `{magenta}`, `{cyan}` and `{reset}`.
+    pub format: String,
+}
+
+impl Default for StatusLineConfig {
+    fn default() -> Self {
+        Self {
+            format: "{green}✓{done} {yellow}●{open} {red}⚑{flagged}{reset}".to_string(),
+        }
+    }
+}
+
+/// Asks for a reload whenever a config file is created, changed or removed. Polls modification
+/// times, which is cheap for a handful of files and works the same on every platform.
+pub async fn watch(config_dir: PathBuf, tx: UnboundedSender<Action>) {
+    let stamps = || {
+        CONFIG_FILES.map(|(file, _)| {
+            std::fs::metadata(config_dir.join(file))
+                .and_then(|meta| meta.modified())
+                .ok()
+        })
+    };
+    let mut last = stamps();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        let current = stamps();
+        if current != last {
+            last = current;
+            if tx.send(Action::ReloadConfig).is_err() {
+                break;
+            }
+        }
+    }
 }
 
 impl Config {
@@ -42,15 +439,8 @@ impl Config {
             .set_default("_data_dir", data_dir.to_str().unwrap())?
             .set_default("_config_dir", config_dir.to_str().unwrap())?;
 
-        let config_files = [
-            ("config.json5", config::FileFormat::Json5),
-            ("config.json", config::FileFormat::Json),
-            ("config.yaml", config::FileFormat::Yaml),
-            ("config.toml", config::FileFormat::Toml),
-            ("config.ini", config::FileFormat::Ini),
-        ];
         let mut found_config = false;
-        for (file, format) in &config_files {
+        for (file, format) in &CONFIG_FILES {
             builder = builder.add_source(
                 config::File::from(config_dir.join(file))
                     .format(*format)
@@ -60,11 +450,24 @@ impl Config {
                 found_config = true
             }
         }
+        for file in [crate::tags::COLORS_FILE, crate::themes::FILE] {
+            builder = builder.add_source(
+                config::File::from(config_dir.join(file))
+                    .format(config::FileFormat::Json)
+                    .required(false),
+            );
+        }
         if !found_config {
             log::error!("No configuration file found. Application may not behave as expected");
         }
 
         let mut cfg: Self = builder.build()?.try_deserialize()?;
+        if let Some(theme) = &cfg.theme {
+            crate::themes::apply(&mut cfg.styles, theme);
+        }
+        if cfg.high_contrast {
+            crate::themes::apply(&mut cfg.styles, crate::themes::HIGH_CONTRAST);
+        }
 
         for (mode, default_bindings) in default_config.keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
@@ -74,12 +477,15 @@ impl Config {
                     .or_insert_with(|| cmd.clone());
             }
         }
+        cfg.keybindings
+            .resolve_leader(cfg.leader.as_deref())
+            .map_err(config::ConfigError::Message)?;
         for (mode, default_styles) in default_config.styles.iter() {
             let user_styles = cfg.styles.entry(*mode).or_default();
             for (style_key, style) in default_styles.iter() {
                 user_styles
                     .entry(style_key.clone())
-                    .or_insert_with(|| style.clone());
+                    .or_insert_with(|| *style);
             }
         }
 
@@ -112,6 +518,118 @@ impl<'de> Deserialize<'de> for KeyBindings {
     }
 }
 
+/// Where `<leader>` was written in a binding, until it is swapped for the configured key.
+pub const LEADER: KeyEvent = KeyEvent::new(KeyCode::Null, KeyModifiers::NONE);
+
+impl KeyBindings {
+    /// Puts the `leader` key in place of `<leader>` in every binding, or drops the bindings that
+    /// use it when no leader is set.
+    pub fn resolve_leader(&mut self, leader: Option<&str>) -> Result<(), String> {
+        let leader = match leader.map(parse_key_sequence).transpose()?.as_deref() {
+            None => None,
+            Some([key]) if *key != LEADER => Some(*key),
+            Some(_) => return Err("the leader should be one key, like <space>".to_string()),
+        };
+        for bindings in self.values_mut() {
+            *bindings = std::mem::take(bindings)
+                .into_iter()
+                .filter_map(|(keys, action)| {
+                    let keys = keys
+                        .into_iter()
+                        .map(|key| if key == LEADER { leader } else { Some(key) })
+                        .collect::<Option<Vec<_>>>()?;
+                    Some((keys, action))
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// The bindings in effect in `mode`: those under `Home` and then those of `mode` itself,
+    /// which win where both bind the same keys. While typing, in `Editing`, the `Home` ones that
+    /// start with a key that types something are left out, so that a title such as "buy a car"
+    /// doesn't run `<space>a`.
+    pub fn effective(&self, mode: Mode) -> HashMap<&[KeyEvent], &Action> {
+        let typing = mode == Mode::Editing;
+        let home = self
+            .get(&Mode::Home)
+            .into_iter()
+            .flatten()
+            .filter(move |(keys, _)| !(typing && keys.first().is_some_and(types_text)));
+        home.chain(self.get(&mode).into_iter().flatten())
+            .map(|(keys, action)| (keys.as_slice(), action))
+            .collect()
+    }
+
+    /// The bindings in effect in `mode` as a prefix tree, for matching keys as they are pressed.
+    pub fn chords(&self, mode: Mode) -> KeyTrie {
+        let mut trie = KeyTrie::default();
+        for (keys, action) in self.effective(mode) {
+            trie.insert(keys, action.clone());
+        }
+        trie
+    }
+
+    /// The bindings in effect in `mode` that start with the keys pressed so far, as the rest of
+    /// each sequence and the action it runs, sorted by key.
+    pub fn continuations(&self, mode: Mode, pending: &[KeyEvent]) -> Vec<(String, String)> {
+        let mut hints: Vec<(String, String)> = self
+            .effective(mode)
+            .into_iter()
+            .filter(|(keys, _)| keys.len() > pending.len() && keys.starts_with(pending))
+            .map(|(keys, action)| {
+                let rest: Vec<String> = keys[pending.len()..]
+                    .iter()
+                    .map(|key| format!("<{}>", key_event_to_string(key)))
+                    .collect();
+                (rest.join(""), action.to_string())
+            })
+            .collect();
+        hints.sort();
+        hints
+    }
+}
+
+/// Whether `key` types a character into a text box, rather than being a shortcut.
+fn types_text(key: &KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char(_))
+        && !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// The bindings of one mode as a prefix tree, so that each key pressed narrows down the sequences
+/// it may be part of.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyTrie {
+    action: Option<Action>,
+    next: HashMap<KeyEvent, KeyTrie>,
+}
+
+impl KeyTrie {
+    pub fn insert(&mut self, keys: &[KeyEvent], action: Action) {
+        match keys.split_first() {
+            None => self.action = Some(action),
+            Some((first, rest)) => self.next.entry(*first).or_default().insert(rest, action),
+        }
+    }
+
+    /// Where `keys` lead, if any binding starts with them.
+    pub fn walk(&self, keys: &[KeyEvent]) -> Option<&Self> {
+        keys.iter().try_fold(self, |node, key| node.next.get(key))
+    }
+
+    /// The action of the binding that ends here, if one does.
+    pub fn action(&self) -> Option<&Action> {
+        self.action.as_ref()
+    }
+
+    /// Whether no longer binding goes on from here.
+    pub fn is_leaf(&self) -> bool {
+        self.next.is_empty()
+    }
+}
+
 fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
@@ -181,6 +699,7 @@ fn parse_key_code_with_modifiers(
         "hyphen" => KeyCode::Char('-'),
         "minus" => KeyCode::Char('-'),
         "tab" => KeyCode::Tab,
+        "leader" => return Ok(LEADER),
         c if c.len() == 1 => {
             let mut c = c.chars().next().unwrap();
             if modifiers.contains(KeyModifiers::SHIFT) {
@@ -214,7 +733,7 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
             char = format!("f({c})");
             &char
         }
-        KeyCode::Char(c) if c == ' ' => "space",
+        KeyCode::Char(' ') => "space",
         KeyCode::Char(c) => {
             char = c.to_string();
             &char
@@ -257,9 +776,33 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
 }
 
 pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
+    // Chords may also be written as keys separated by spaces, like `g g` or `space f t`
+    if raw.trim().contains(char::is_whitespace) {
+        let keys = raw
+            .split_whitespace()
+            .map(parse_key_sequence)
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(keys.concat());
+    }
     if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
         return Err(format!("Unable to parse `{}`", raw));
     }
+    // Bracketed keys may be mixed with plain characters, like `<leader>p` or `<ctrl-x>b`
+    if raw.contains('<') {
+        let mut keys = Vec::new();
+        let mut rest = raw;
+        while let Some(c) = rest.chars().next() {
+            let (key, after) = match c {
+                '<' => rest[1..]
+                    .split_once('>')
+                    .ok_or_else(|| format!("Unable to parse `{raw}`"))?,
+                _ => rest.split_at(c.len_utf8()),
+            };
+            keys.push(parse_key_event(key)?);
+            rest = after;
+        }
+        return Ok(keys);
+    }
     let raw = if !raw.contains("><") {
         let raw = raw.strip_prefix('<').unwrap_or(raw);
         let raw = raw.strip_prefix('>').unwrap_or(raw);
@@ -453,6 +996,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_parse_color_rgb() {
         let color = parse_color("rgb123");
         let expected = 16 + 1 * 36 + 2 * 6 + 3;
@@ -560,4 +1104,90 @@ mod tests {
             KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)
         );
     }
+
+    #[test]
+    fn test_continuations() {
+        let mut bindings = KeyBindings::default();
+        let home = bindings.entry(Mode::Home).or_default();
+        home.insert(parse_key_sequence("<g><g>").unwrap(), Action::BrowseListUp);
+        home.insert(
+            parse_key_sequence("<g><e>").unwrap(),
+            Action::BrowseListDown,
+        );
+        home.insert(parse_key_sequence("<q>").unwrap(), Action::Quit);
+        assert_eq!(
+            bindings.continuations(Mode::Home, &parse_key_sequence("<g>").unwrap()),
+            vec![
+                ("<e>".to_string(), "BrowseListDown".to_string()),
+                ("<g>".to_string(), "BrowseListUp".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chords() {
+        assert_eq!(
+            parse_key_sequence("space f t").unwrap(),
+            parse_key_sequence("<space><f><t>").unwrap()
+        );
+        let mut bindings = KeyBindings::default();
+        let home = bindings.entry(Mode::Home).or_default();
+        home.insert(parse_key_sequence("g").unwrap(), Action::BrowseListDown);
+        home.insert(parse_key_sequence("g g").unwrap(), Action::BrowseListUp);
+        home.insert(parse_key_sequence("<ctrl-x> d d").unwrap(), Action::Quit);
+        let chords = bindings.chords(Mode::Home);
+        let g = chords.walk(&parse_key_sequence("g").unwrap()).unwrap();
+        assert_eq!(g.action(), Some(&Action::BrowseListDown));
+        assert!(!g.is_leaf());
+        let ctrl_x = chords
+            .walk(&parse_key_sequence("<ctrl-x>").unwrap())
+            .unwrap();
+        assert_eq!(ctrl_x.action(), None);
+        let quit = chords
+            .walk(&parse_key_sequence("<ctrl-x><d><d>").unwrap())
+            .unwrap();
+        assert_eq!(quit.action(), Some(&Action::Quit));
+        assert!(quit.is_leaf());
+        assert!(chords.walk(&parse_key_sequence("d").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_leader() {
+        let mut bindings = KeyBindings::default();
+        let home = bindings.entry(Mode::Home).or_default();
+        home.insert(
+            parse_key_sequence("<leader>p").unwrap(),
+            Action::EnterProjectsMode,
+        );
+        home.insert(parse_key_sequence("<q>").unwrap(), Action::Quit);
+        let mut unset = bindings.clone();
+        unset.resolve_leader(None).unwrap();
+        assert_eq!(unset[&Mode::Home].len(), 1);
+        assert!(bindings.clone().resolve_leader(Some("<a><b>")).is_err());
+        bindings.resolve_leader(Some("<space>")).unwrap();
+        assert_eq!(
+            bindings[&Mode::Home].get(&parse_key_sequence("space p").unwrap()),
+            Some(&Action::EnterProjectsMode)
+        );
+    }
+
+    #[test]
+    fn test_mode_sections_win_over_home() {
+        let mut bindings = KeyBindings::default();
+        let x = parse_key_sequence("<x>").unwrap();
+        let q = parse_key_sequence("<ctrl-q>").unwrap();
+        let home = bindings.entry(Mode::Home).or_default();
+        home.insert(x.clone(), Action::Help);
+        home.insert(q.clone(), Action::Quit);
+        bindings
+            .entry(Mode::Browse)
+            .or_default()
+            .insert(x.clone(), Action::CompleteTodo(0));
+        let browse = bindings.effective(Mode::Browse);
+        assert_eq!(browse[x.as_slice()], &Action::CompleteTodo(0));
+        assert_eq!(browse[q.as_slice()], &Action::Quit);
+        let editing = bindings.effective(Mode::Editing);
+        assert!(!editing.contains_key(x.as_slice()));
+        assert_eq!(editing[q.as_slice()], &Action::Quit);
+    }
 }
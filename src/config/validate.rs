@@ -0,0 +1,509 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use strum::VariantNames;
+
+use config::FileFormat;
+
+use super::{
+    parse_color, parse_key_sequence, process_color_string, ArchiveConfig, DaemonConfig,
+    ExitSummary, GoogleTasksConfig, JiraConfig, KeyBindings, Keymap, MqttConfig, NotionConfig,
+    SmtpConfig, StatusLineConfig, StreaksConfig, SyncConfig, UrgencyConfig, VaultConfig,
+    CONFIG_FILES,
+};
+use crate::{action::Action, highlight, mode::Mode, table::Column, text::Truncation, themes};
+
+const SECTIONS: [&str; 35] = [
+    "data_file",
+    "keymap",
+    "leader",
+    "keybindings",
+    "styles",
+    "theme",
+    "code_theme",
+    "ascii",
+    "screen_reader",
+    "high_contrast",
+    "wrap",
+    "truncation",
+    "reduced_motion",
+    "language",
+    "exit_summary",
+    "daemon",
+    "mqtt",
+    "smtp",
+    "sync",
+    "google_tasks",
+    "vault",
+    "jira",
+    "notion",
+    "archive",
+    "streaks",
+    "daily_goal",
+    "habits",
+    "tag_colors",
+    "urgency",
+    "team",
+    "shared_files",
+    "status_line",
+    "status_bar",
+    "list_item",
+    "table",
+];
+
+const COLORS: &str = "black, red, green, yellow, blue, magenta, cyan, white, gray0-23, \
+                      color0-255 or rgbRGB with digits 0-5, optionally after bold, bright, \
+                      underline or inverse";
+
+/// Something wrong with the user's config file, located as closely as the format allows.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// How many single-character edits turn `a` into `b`.
+pub(crate) fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate closest to `word`, if any is close enough to be a likely typo.
+fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| (distance(&word, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= (word.len() / 3).max(2))
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn suggest(word: &str, candidates: &[&str]) -> String {
+    match closest(word, candidates) {
+        Some(candidate) => format!(", did you mean `{candidate}`?"),
+        None => format!("; expected one of {}", candidates.join(", ")),
+    }
+}
+
+fn style_problem(style: &str) -> Option<String> {
+    let lower = style.to_lowercase();
+    let (foreground, background) = lower.split_at(lower.find("on ").unwrap_or(lower.len()));
+    for part in [foreground, &background.replace("on ", "")] {
+        let color = process_color_string(part).0;
+        let color = color.trim();
+        if color.is_empty() {
+            continue;
+        }
+        let valid = match color.strip_prefix("rgb") {
+            Some(digits) => digits.len() == 3 && digits.chars().all(|c| ('0'..='5').contains(&c)),
+            None => parse_color(color).is_some(),
+        };
+        if !valid {
+            return Some(format!(
+                "unknown color `{color}` in style `{style}`; use {COLORS}"
+            ));
+        }
+    }
+    None
+}
+
+struct Checker<'a> {
+    file: &'a Path,
+    text: &'a str,
+    problems: Vec<Problem>,
+}
+
+impl Checker<'_> {
+    /// The first line mentioning `key` as an object key, quoted or not.
+    fn line_of(&self, key: &str) -> Option<usize> {
+        let quoted = [format!("\"{key}\""), format!("'{key}'")];
+        let bare = format!("{key}:");
+        self.text
+            .lines()
+            .position(|line| {
+                quoted.iter().any(|pattern| line.contains(pattern.as_str()))
+                    || line.trim_start().starts_with(&bare)
+            })
+            .map(|i| i + 1)
+    }
+
+    fn report(&mut self, key: &str, message: String) {
+        self.problems.push(Problem {
+            file: self.file.to_path_buf(),
+            line: self.line_of(key),
+            message,
+        });
+    }
+
+    /// Deserializes a section, reporting fields it would otherwise silently ignore.
+    fn section<T: DeserializeOwned>(&mut self, name: &str, value: &Value) {
+        let mut ignored = Vec::new();
+        let result: Result<T, _> =
+            serde_ignored::deserialize(value.clone(), |path| ignored.push(path.to_string()));
+        if let Err(e) = result {
+            self.report(name, format!("invalid `{name}` section: {e}"));
+        }
+        for path in ignored {
+            let key = path.rsplit('.').next().unwrap_or(&path).to_string();
+            self.report(&key, format!("unknown key `{path}` in `{name}`"));
+        }
+    }
+
+    fn modes<'v>(
+        &mut self,
+        section: &str,
+        value: &'v Value,
+    ) -> Vec<&'v serde_json::Map<String, Value>> {
+        let Some(modes) = value.as_object() else {
+            self.report(section, format!("`{section}` should map modes to tables"));
+            return Vec::new();
+        };
+        let mut tables = Vec::new();
+        for (mode, table) in modes {
+            if !Mode::VARIANTS.contains(&mode.as_str()) {
+                self.report(
+                    mode,
+                    format!("unknown mode `{mode}`{}", suggest(mode, Mode::VARIANTS)),
+                );
+            } else if let Some(table) = table.as_object() {
+                tables.push(table);
+            }
+        }
+        tables
+    }
+
+    fn leader(&mut self, value: &Value) {
+        let mut bindings = KeyBindings::default();
+        let resolved = match value.as_str() {
+            Some(raw) => bindings.resolve_leader(Some(raw)),
+            None => Err("the leader should be a key, like <space>".to_string()),
+        };
+        if let Err(e) = resolved {
+            self.report("leader", format!("invalid leader {value}: {e}"));
+        }
+    }
+
+    fn keybindings(&mut self, value: &Value) {
+        for table in self.modes("keybindings", value) {
+            // The same keys may be spelled several ways, and only one of them would take effect
+            let mut spellings: HashMap<Vec<KeyEvent>, &str> = HashMap::new();
+            for (keys, action) in table {
+                match parse_key_sequence(keys) {
+                    Ok(sequence) => {
+                        if let Some(other) = spellings.insert(sequence, keys) {
+                            self.report(
+                                keys,
+                                format!("`{keys}` and `{other}` are the same keys in one mode"),
+                            );
+                        }
+                    }
+                    Err(_) => self.report(
+                        keys,
+                        format!(
+                            "invalid key `{keys}`; write keys like <q>, <enter> or <ctrl-alt-a>"
+                        ),
+                    ),
+                }
+                if serde_json::from_value::<Action>(action.clone()).is_err() {
+                    let name = action.as_str().unwrap_or_default();
+                    self.report(
+                        keys,
+                        format!(
+                            "unknown action {action} for `{keys}`{}",
+                            suggest(name, Action::VARIANTS)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks a section mapping names, of tags or people, to colors.
+    fn colors(&mut self, section: &str, noun: &str, value: &Value) {
+        let Some(colors) = value.as_object() else {
+            self.report(section, format!("`{section}` should map {noun}s to colors"));
+            return;
+        };
+        for (name, color) in colors {
+            match color.as_str().map(style_problem) {
+                Some(None) => {}
+                Some(Some(message)) => self.report(name, message),
+                None => self.report(
+                    name,
+                    format!("color for {noun} `{name}` should be a string"),
+                ),
+            }
+        }
+    }
+
+    fn theme(&mut self, value: &Value) {
+        let names = themes::names();
+        match value.as_str() {
+            Some(name) if names.contains(&name) => {}
+            Some(name) => self.report(
+                "theme",
+                format!("unknown theme `{name}`{}", suggest(name, &names)),
+            ),
+            None => self.report("theme", "`theme` should be a name".to_string()),
+        }
+    }
+
+    fn code_theme(&mut self, value: &Value) {
+        let names = highlight::names();
+        match value.as_str() {
+            Some(name) if names.contains(&name) => {}
+            Some(name) => self.report(
+                "code_theme",
+                format!("unknown code theme `{name}`{}", suggest(name, &names)),
+            ),
+            None => self.report("code_theme", "`code_theme` should be a name".to_string()),
+        }
+    }
+
+    fn styles(&mut self, value: &Value) {
+        for table in self.modes("styles", value) {
+            for (name, style) in table {
+                match style.as_str().map(style_problem) {
+                    Some(None) => {}
+                    Some(Some(message)) => self.report(name, message),
+                    None => self.report(name, format!("style `{name}` should be a string")),
+                }
+            }
+        }
+    }
+}
+
+/// Checks a config file's contents for mistakes that would otherwise be ignored or panic later.
+pub fn check_value(file: &Path, text: &str, value: &Value) -> Vec<Problem> {
+    let mut checker = Checker {
+        file,
+        text,
+        problems: Vec::new(),
+    };
+    let Some(sections) = value.as_object() else {
+        checker.report("", "the config should be a table of sections".to_string());
+        return checker.problems;
+    };
+    for (name, value) in sections {
+        match name.as_str() {
+            "data_file" => checker.section::<PathBuf>(name, value),
+            "keymap" => checker.section::<Keymap>(name, value),
+            "leader" => checker.leader(value),
+            "keybindings" => checker.keybindings(value),
+            "styles" => checker.styles(value),
+            "theme" => checker.theme(value),
+            "code_theme" => checker.code_theme(value),
+            "ascii" => checker.section::<bool>(name, value),
+            "screen_reader" => checker.section::<bool>(name, value),
+            "high_contrast" => checker.section::<bool>(name, value),
+            "wrap" => checker.section::<bool>(name, value),
+            "truncation" => checker.section::<Truncation>(name, value),
+            "reduced_motion" => checker.section::<bool>(name, value),
+            "language" => checker.section::<String>(name, value),
+            "exit_summary" => checker.section::<ExitSummary>(name, value),
+            "daemon" => checker.section::<DaemonConfig>(name, value),
+            "mqtt" => checker.section::<MqttConfig>(name, value),
+            "smtp" => checker.section::<SmtpConfig>(name, value),
+            "sync" => checker.section::<SyncConfig>(name, value),
+            "google_tasks" => checker.section::<GoogleTasksConfig>(name, value),
+            "vault" => checker.section::<VaultConfig>(name, value),
+            "jira" => checker.section::<JiraConfig>(name, value),
+            "notion" => checker.section::<NotionConfig>(name, value),
+            "archive" => checker.section::<ArchiveConfig>(name, value),
+            "streaks" => checker.section::<StreaksConfig>(name, value),
+            "daily_goal" => checker.section::<usize>(name, value),
+            "habits" => checker.section::<Vec<String>>(name, value),
+            "urgency" => checker.section::<UrgencyConfig>(name, value),
+            "tag_colors" => checker.colors(name, "tag", value),
+            "team" => checker.colors(name, "member", value),
+            "shared_files" => checker.section::<BTreeMap<String, PathBuf>>(name, value),
+            "status_line" => checker.section::<StatusLineConfig>(name, value),
+            "status_bar" | "list_item" => checker.section::<String>(name, value),
+            "table" => checker.section::<Vec<Column>>(name, value),
+            "_data_dir" | "_config_dir" => {}
+            _ => checker.report(
+                name,
+                format!("unknown section `{name}`{}", suggest(name, &SECTIONS)),
+            ),
+        }
+    }
+    checker.problems
+}
+
+/// The line a syntax error is on, for parsers that only say so in their message, either as in
+/// "at line 3 column 7" or as a leading "3:7".
+fn error_line(message: &str) -> Option<usize> {
+    let number = |text: &str| -> Option<usize> {
+        let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    };
+    message
+        .match_indices("line ")
+        .find_map(|(at, word)| number(&message[at + word.len()..]))
+        .or_else(|| message.split_once(':').and_then(|(line, _)| number(line)))
+}
+
+/// Checks every config file in `config_dir`, returning problems in file order.
+pub fn check(config_dir: &Path) -> Result<Vec<Problem>> {
+    let mut problems = Vec::new();
+    for (file, format) in CONFIG_FILES {
+        let path = config_dir.join(file);
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let parsed: Result<Value, String> =
+            if matches!(format, FileFormat::Json5 | FileFormat::Json) {
+                json5::from_str(&text).map_err(|e| e.to_string())
+            } else {
+                config::Config::builder()
+                    .add_source(config::File::from(path.as_path()).format(format))
+                    .build()
+                    .and_then(|parsed| parsed.try_deserialize())
+                    .map_err(|e| {
+                        // The file is already named, drop the path the error repeats it by
+                        let message = e.to_string();
+                        match message.rsplit_once(" in ") {
+                            Some((message, uri)) if uri.ends_with(file) => message.to_string(),
+                            _ => message,
+                        }
+                    })
+            };
+        let value = match parsed {
+            Ok(value) => value,
+            Err(message) => {
+                problems.push(Problem {
+                    file: path,
+                    line: error_line(&message),
+                    message,
+                });
+                continue;
+            }
+        };
+        problems.extend(check_value(&path, &text, &value));
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_problems_point_at_lines() -> Result<()> {
+        let text = r#"{
+  "keybindings": {
+    "Home": {
+      "<ctrl-q>": "Quti",
+      "<hyper-x>": "Quit",
+    },
+  },
+  "styles": {
+    "Home": {
+      "title": "bold purple",
+    },
+  },
+  "mqtt": {
+    "hots": "localhost",
+  },
+  "exit_sumary": {},
+}"#;
+        let value: Value = json5::from_str(text)?;
+        let problems: Vec<String> = check_value(Path::new("config.json5"), text, &value)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "config.json5:16: unknown section `exit_sumary`, did you mean `exit_summary`?",
+                "config.json5:4: unknown action \"Quti\" for `<ctrl-q>`, did you mean `Quit`?",
+                "config.json5:5: invalid key `<hyper-x>`; write keys like <q>, <enter> or <ctrl-alt-a>",
+                "config.json5:13: invalid `mqtt` section: missing field `host`",
+                "config.json5:14: unknown key `hots` in `mqtt`",
+                format!("config.json5:10: unknown color `purple` in style `bold purple`; use {COLORS}").as_str(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_keys_spelled_twice() -> Result<()> {
+        let text = r#"{
+  "keybindings": {
+    "Browse": {
+      "<Ctrl-q>": "Quit",
+      "<ctrl-q>": "Help",
+    },
+    "Editing": {
+      "<ctrl-q>": "Quit",
+    },
+  },
+}"#;
+        let value: Value = json5::from_str(text)?;
+        let problems = check_value(Path::new("config.json5"), text, &value);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("are the same keys"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_syntax_errors() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for (file, text) in [
+            ("config.toml", "[keybindings]\nkeymap = \"vim\n"),
+            ("config.yaml", "keymap: vim\n  leader: [\n"),
+            ("config.ini", "[keymap\n"),
+        ] {
+            fs::write(dir.path().join(file), text)?;
+        }
+        let problems: Vec<(String, Option<usize>)> = check(dir.path())?
+            .into_iter()
+            .map(|problem| {
+                (
+                    problem.file.file_name().unwrap().to_string_lossy().into(),
+                    problem.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                ("config.yaml".into(), Some(2)),
+                ("config.toml".into(), Some(2)),
+                ("config.ini".into(), Some(1)),
+            ]
+        );
+        Ok(())
+    }
+}
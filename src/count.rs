@@ -0,0 +1,58 @@
+use chrono::{Days, NaiveDate};
+use clap::ValueEnum;
+
+use crate::todo::TodoItem;
+
+/// Which due dates `doit count` counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Due {
+    /// Due today or overdue
+    Today,
+    /// Due before today
+    Overdue,
+    /// Due within the next seven days or overdue
+    Week,
+}
+
+/// How many open todos are due as `due` asks, or how many are open at all without it.
+pub fn count(todos: &[TodoItem], due: Option<Due>, today: NaiveDate) -> usize {
+    let until = match due {
+        None => return todos.iter().filter(|todo| !todo.done).count(),
+        Some(Due::Today) => Some(today),
+        Some(Due::Overdue) => today.pred_opt(),
+        Some(Due::Week) => today.checked_add_days(Days::new(6)),
+    };
+    until.map_or(0, |until| {
+        todos.iter().filter(|todo| todo.is_due_on(until)).count()
+    })
+}
+
+/// `format` with every `{n}` replaced by the count.
+pub fn render(format: &str, n: usize) -> String {
+    format.replace("{n}", &n.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_count_due() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut todos = vec![
+            TodoItem::parse("late due:2024-04-30"),
+            TodoItem::parse("today due:2024-05-01"),
+            TodoItem::parse("friday due:2024-05-03"),
+            TodoItem::parse("someday"),
+            TodoItem::parse("done due:2024-04-01"),
+        ];
+        todos[4].done = true;
+        assert_eq!(count(&todos, None, today), 4);
+        assert_eq!(count(&todos, Some(Due::Overdue), today), 1);
+        assert_eq!(count(&todos, Some(Due::Today), today), 2);
+        assert_eq!(count(&todos, Some(Due::Week), today), 3);
+        assert_eq!(render("✓ {n} left", 2), "✓ 2 left");
+    }
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{Local, NaiveDate};
+use color_eyre::eyre::Result;
+use log::{error, info};
+use notify_rust::Notification;
+use uuid::Uuid;
+
+use crate::{
+    config::DaemonConfig,
+    todo::{self, TodoItem},
+};
+
+/// Runs headless until interrupted, re-reading the saved todos every poll interval and raising a
+/// desktop notification each time a todo enters one of the configured lead windows.
+pub async fn run(config: &DaemonConfig) -> Result<()> {
+    info!("Reminder daemon started");
+    let mut sent = HashSet::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_secs.max(1)));
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let todos = match todo::load(&todo::data_file()) {
+            Ok(todos) => todos,
+            Err(e) => {
+                error!("Failed to read todos: {e:?}");
+                continue;
+            }
+        };
+
+        let today = Local::now().date_naive();
+        for todo in unsent(&mut sent, &todos, &config.lead_days, today) {
+            notify(todo, todo.due.unwrap_or(today), today);
+        }
+    }
+
+    info!("Reminder daemon stopped");
+    Ok(())
+}
+
+/// Pairs every todo that has reached a lead window with the closest lead (in days) it has reached.
+pub fn due_reminders<'a>(
+    todos: &'a [TodoItem],
+    lead_days: &[u64],
+    today: NaiveDate,
+) -> Vec<(&'a TodoItem, u64)> {
+    todos
+        .iter()
+        .filter(|todo| !todo.done)
+        .filter_map(|todo| {
+            let days_left = (todo.due? - today).num_days();
+            lead_days
+                .iter()
+                .filter(|lead| days_left <= **lead as i64)
+                .min()
+                .map(|lead| (todo, *lead))
+        })
+        .collect()
+}
+
+/// The todos to remind of now, leaving out those already reminded of in the same lead window,
+/// which `sent` keeps by todo, due date and lead. Reminders no longer current are forgotten: the
+/// todo is done or gone, its due date moved, or it reached a closer lead.
+pub fn unsent<'a>(
+    sent: &mut HashSet<(Uuid, NaiveDate, u64)>,
+    todos: &'a [TodoItem],
+    lead_days: &[u64],
+    today: NaiveDate,
+) -> Vec<&'a TodoItem> {
+    let reminders: Vec<((Uuid, NaiveDate, u64), &TodoItem)> =
+        due_reminders(todos, lead_days, today)
+            .into_iter()
+            .map(|(todo, lead)| ((todo.id, todo.due.unwrap_or(today), lead), todo))
+            .collect();
+    sent.retain(|key| reminders.iter().any(|(current, _)| current == key));
+    reminders
+        .into_iter()
+        .filter(|(key, _)| sent.insert(*key))
+        .map(|(_, todo)| todo)
+        .collect()
+}
+
+fn notify(todo: &TodoItem, due: NaiveDate, today: NaiveDate) {
+    let when = match (due - today).num_days() {
+        0 => "Due today".to_string(),
+        1 => "Due tomorrow".to_string(),
+        n if n > 1 => format!("Due in {n} days"),
+        _ => format!("Overdue since {due}"),
+    };
+    if let Err(e) = Notification::new()
+        .appname(env!("CARGO_PKG_NAME"))
+        .summary(&when)
+        .body(&todo.title)
+        .show()
+    {
+        error!("Failed to send notification: {e:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn due_in(title: &str, today: NaiveDate, days: i64) -> TodoItem {
+        let mut todo = TodoItem::new(title.into());
+        todo.due = Some(today + chrono::Duration::days(days));
+        todo
+    }
+
+    #[test]
+    fn test_due_reminders_picks_closest_lead() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let todos = vec![
+            due_in("far", today, 5),
+            due_in("tomorrow", today, 1),
+            due_in("overdue", today, -2),
+            TodoItem::new("undated".into()),
+        ];
+        let reminders: Vec<(&str, u64)> = due_reminders(&todos, &[3, 1, 0], today)
+            .into_iter()
+            .map(|(todo, lead)| (todo.title.as_str(), lead))
+            .collect();
+        assert_eq!(reminders, vec![("tomorrow", 1), ("overdue", 0)]);
+    }
+
+    #[test]
+    fn test_unsent_remembers_current_reminders_only() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let mut todos = vec![due_in("tomorrow", today, 1), due_in("overdue", today, -2)];
+        let mut sent = HashSet::new();
+        let titles = |todos: Vec<&TodoItem>| -> Vec<String> {
+            todos.into_iter().map(|todo| todo.title.clone()).collect()
+        };
+        assert_eq!(
+            titles(unsent(&mut sent, &todos, &[1, 0], today)),
+            vec!["tomorrow", "overdue"]
+        );
+        assert!(unsent(&mut sent, &todos, &[1, 0], today).is_empty());
+
+        todos[0].title = "renamed".into();
+        todos[1].done = true;
+        assert!(unsent(&mut sent, &todos, &[1, 0], today).is_empty());
+        assert_eq!(sent.len(), 1);
+
+        let tomorrow = today.succ_opt().unwrap();
+        assert_eq!(
+            titles(unsent(&mut sent, &todos, &[1, 0], tomorrow)),
+            vec!["renamed"]
+        );
+        assert_eq!(sent.len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use color_eyre::eyre::Result;
+use tokio::sync::{mpsc::UnboundedSender, watch};
+use zbus::{dbus_interface, fdo, ConnectionBuilder, SignalContext};
+
+use crate::{action::Action, todo::TodoItem};
+
+pub const BUS_NAME: &str = "com.github.ChangedNameTo.DoIt";
+pub const OBJECT_PATH: &str = "/com/github/ChangedNameTo/DoIt";
+
+/// The object published on the session bus while the TUI runs.
+struct Todos {
+    tx: UnboundedSender<Action>,
+    todos: watch::Receiver<Vec<TodoItem>>,
+}
+
+impl Todos {
+    fn send(&self, action: Action) -> fdo::Result<()> {
+        self.tx
+            .send(action)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[dbus_interface(name = "com.github.ChangedNameTo.DoIt1")]
+impl Todos {
+    /// Adds a todo, accepting the same `due:<date>` syntax as the input box.
+    fn add(&self, text: String) -> fdo::Result<()> {
+        self.send(Action::InsertTodo(text))
+    }
+
+    /// Lists every todo as `(index, title, due, done)`, with an empty `due` when unset.
+    fn list(&self) -> Vec<(u32, String, String, bool)> {
+        self.todos
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(i, todo)| {
+                let due = todo.due.map(|due| due.to_string()).unwrap_or_default();
+                (i as u32, todo.title.clone(), due, todo.done)
+            })
+            .collect()
+    }
+
+    /// Marks the todo at `index` as done.
+    fn complete(&self, index: u32) -> fdo::Result<()> {
+        if index as usize >= self.todos.borrow().len() {
+            return Err(fdo::Error::InvalidArgs(format!("No todo at index {index}")));
+        }
+        self.send(Action::CompleteTodo(index as usize))
+    }
+
+    /// Emitted whenever the list changes, whether from the TUI or over the bus.
+    #[dbus_interface(signal)]
+    async fn changed(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+}
+
+/// Claims the well-known name on the session bus and emits `Changed` for every new snapshot.
+pub async fn serve(
+    tx: UnboundedSender<Action>,
+    mut todos: watch::Receiver<Vec<TodoItem>>,
+) -> Result<()> {
+    let object = Todos {
+        tx,
+        todos: todos.clone(),
+    };
+    let connection = ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, object)?
+        .build()
+        .await?;
+    let iface = connection
+        .object_server()
+        .interface::<_, Todos>(OBJECT_PATH)
+        .await?;
+
+    while todos.changed().await.is_ok() {
+        Todos::changed(iface.signal_context()).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use chrono::NaiveDate;
+use color_eyre::eyre::Result;
+use lettre::{
+    message::MultiPart, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{config::SmtpConfig, todo::TodoItem};
+
+/// Open todos grouped the way the digest presents them.
+pub struct Digest<'a> {
+    pub overdue: Vec<&'a TodoItem>,
+    pub due_today: Vec<&'a TodoItem>,
+    pub open: Vec<&'a TodoItem>,
+}
+
+impl<'a> Digest<'a> {
+    pub fn new(todos: &'a [TodoItem], today: NaiveDate) -> Self {
+        let mut digest = Self {
+            overdue: Vec::new(),
+            due_today: Vec::new(),
+            open: Vec::new(),
+        };
+        for todo in todos.iter().filter(|todo| !todo.done) {
+            match todo.due {
+                Some(due) if due < today => digest.overdue.push(todo),
+                Some(due) if due == today => digest.due_today.push(todo),
+                _ => digest.open.push(todo),
+            }
+        }
+        digest
+    }
+
+    fn sections(&self) -> [(&'static str, &[&'a TodoItem]); 3] {
+        [
+            ("Overdue", &self.overdue),
+            ("Due today", &self.due_today),
+            ("Open", &self.open),
+        ]
+    }
+
+    pub fn subject(&self, today: NaiveDate) -> String {
+        let open = self.overdue.len() + self.due_today.len() + self.open.len();
+        format!(
+            "Todo digest for {today}: {open} open, {} overdue",
+            self.overdue.len()
+        )
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (heading, todos) in self.sections() {
+            if todos.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("{heading} ({})\n", todos.len()));
+            for todo in todos {
+                out.push_str(&format!("  - {todo}\n"));
+            }
+            out.push('\n');
+        }
+        if out.is_empty() {
+            out.push_str("Nothing left to do.\n");
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<html><body style=\"font-family: sans-serif\">\n");
+        for (heading, todos) in self.sections() {
+            if todos.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("<h3>{heading} ({})</h3>\n<ul>\n", todos.len()));
+            for todo in todos {
+                out.push_str(&format!("<li>{}</li>\n", escape_html(&todo.to_string())));
+            }
+            out.push_str("</ul>\n");
+        }
+        if self.sections().iter().all(|(_, todos)| todos.is_empty()) {
+            out.push_str("<p>Nothing left to do.</p>\n");
+        }
+        out.push_str("</body></html>\n");
+        out
+    }
+}
+
+pub fn escape_html(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Sends the digest as a multipart text/HTML message through the configured SMTP relay.
+pub async fn send(config: &SmtpConfig, digest: &Digest<'_>, today: NaiveDate) -> Result<()> {
+    let email = Message::builder()
+        .from(config.from.parse()?)
+        .to(config.to.parse()?)
+        .subject(digest.subject(today))
+        .multipart(MultiPart::alternative_plain_html(
+            digest.to_text(),
+            digest.to_html(),
+        ))?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?;
+    if let Some(port) = config.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &config.username {
+        transport = transport.credentials(Credentials::new(
+            username.clone(),
+            config.password.clone().unwrap_or_default(),
+        ));
+    }
+    transport.build().send(email).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_digest_groups_by_due_date() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let todos = vec![
+            TodoItem::parse("late due:2024-02-28"),
+            TodoItem::parse("now due:2024-03-01"),
+            TodoItem::parse("later"),
+        ];
+        assert_eq!(
+            Digest::new(&todos, today).to_text(),
+            "Overdue (1)\n  - late (due 2024-02-28)\n\n\
+             Due today (1)\n  - now (due 2024-03-01)\n\n\
+             Open (1)\n  - later\n\n"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{config::validate::distance, todo::TodoItem};
+
+/// A title lowercased, with punctuation dropped and spacing evened out.
+fn normalize(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The open todo whose title is the same as `title` once normalized, or differs in no more than
+/// one character in five.
+pub fn find(todos: &[TodoItem], title: &str) -> Option<usize> {
+    let title = normalize(title);
+    if title.is_empty() {
+        return None;
+    }
+    todos
+        .iter()
+        .enumerate()
+        .filter(|(_, todo)| !todo.done)
+        .map(|(i, todo)| {
+            let other = normalize(&todo.title);
+            let longest = title.chars().count().max(other.chars().count());
+            (distance(&title, &other), longest, i)
+        })
+        .filter(|(distance, longest, _)| distance * 5 <= *longest)
+        .min_by_key(|(distance, ..)| *distance)
+        .map(|(.., i)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_find_close_titles() {
+        let mut todos = vec![
+            TodoItem::new("Call the dentist".into()),
+            TodoItem::new("Renew passport".into()),
+            TodoItem::new("Buy milk".into()),
+        ];
+        todos[2].done = true;
+        assert_eq!(find(&todos, "call the dentist!"), Some(0));
+        assert_eq!(find(&todos, "renew pasport"), Some(1));
+        assert_eq!(find(&todos, "buy milk"), None);
+        assert_eq!(find(&todos, "renew car insurance"), None);
+        assert_eq!(find(&todos, "..."), None);
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{Days, NaiveDate};
+
+use crate::{digest::escape_html, todo::TodoItem};
+
+const STYLE: &str =
+    "body { font-family: sans-serif; max-width: 48rem; margin: 2rem auto; color: #222 }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: .25rem }
+ul { list-style: none; padding-left: 0 }
+li { padding: .2rem 0 }
+.done { color: #999; text-decoration: line-through }
+.due { color: #666; font-size: .9em }
+.overdue .due { color: #c00 }
+.tag { background: #eef; border-radius: .25rem; padding: 0 .3rem; font-size: .85em }
+.comments { color: #555; font-size: .9em; padding-left: 1.5rem }";
+
+fn item(out: &mut String, todo: &TodoItem, today: NaiveDate) {
+    let mut class = Vec::new();
+    if todo.done {
+        class.push("done");
+    }
+    if todo.due.is_some_and(|due| due < today) && !todo.done {
+        class.push("overdue");
+    }
+    out.push_str(&format!("<li class=\"{}\">", class.join(" ")));
+    if todo.flagged {
+        out.push_str("⚑ ");
+    }
+    out.push_str(&escape_html(&todo.title));
+    for tag in &todo.tags {
+        out.push_str(&format!(
+            " <span class=\"tag\">#{}</span>",
+            escape_html(tag)
+        ));
+    }
+    if let Some(due) = todo.due {
+        out.push_str(&format!(" <span class=\"due\">due {due}</span>"));
+    }
+    if !todo.comments.is_empty() {
+        out.push_str("<ul class=\"comments\">");
+        for comment in &todo.comments {
+            out.push_str(&format!(
+                "<li>{} {}</li>",
+                comment.at.format("%Y-%m-%d"),
+                escape_html(&comment.text)
+            ));
+        }
+        out.push_str("</ul>");
+    }
+    out.push_str("</li>\n");
+}
+
+fn section(out: &mut String, heading: &str, todos: &[&TodoItem], today: NaiveDate) {
+    if todos.is_empty() {
+        return;
+    }
+    out.push_str(&format!(
+        "<h2>{} ({})</h2>\n<ul>\n",
+        escape_html(heading),
+        todos.len()
+    ));
+    for todo in todos {
+        item(out, todo, today);
+    }
+    out.push_str("</ul>\n");
+}
+
+/// Renders a standalone page for sharing: overdue and upcoming todos first, then every project
+/// with its completed todos struck through.
+pub fn render(todos: &[TodoItem], today: NaiveDate) -> String {
+    let week = today.checked_add_days(Days::new(7)).unwrap_or(today);
+    let open: Vec<&TodoItem> = todos.iter().filter(|todo| !todo.done).collect();
+    let overdue: Vec<&TodoItem> = open
+        .iter()
+        .copied()
+        .filter(|todo| todo.due.is_some_and(|due| due < today))
+        .collect();
+    let upcoming: Vec<&TodoItem> = open
+        .iter()
+        .copied()
+        .filter(|todo| todo.due.is_some_and(|due| due >= today && due <= week))
+        .collect();
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Todos on {today}</title>\n\
+         <style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Todos on {today}</h1>\n\
+         <p>{} open, {} done, {} overdue</p>\n",
+        open.len(),
+        todos.len() - open.len(),
+        overdue.len()
+    );
+    section(&mut out, "Overdue", &overdue, today);
+    section(&mut out, "Due in the next 7 days", &upcoming, today);
+
+    let mut projects: Vec<Option<&str>> = Vec::new();
+    for project in todos.iter().map(|todo| todo.project.as_deref()) {
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    for project in projects {
+        let items: Vec<&TodoItem> = todos
+            .iter()
+            .filter(|todo| todo.project.as_deref() == project)
+            .collect();
+        section(&mut out, project.unwrap_or("No project"), &items, today);
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_sections() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut done = TodoItem::parse("ship <beta> +work");
+        done.done = true;
+        let todos = vec![
+            TodoItem::parse("file taxes due:2024-02-20 #money"),
+            TodoItem::parse("standup notes +work due:2024-03-04"),
+            done,
+        ];
+        let html = render(&todos, today);
+        assert!(html.contains("<p>2 open, 1 done, 1 overdue</p>"));
+        assert!(html.contains("<h2>Overdue (1)</h2>"));
+        assert!(html.contains("<h2>Due in the next 7 days (1)</h2>"));
+        assert!(html.contains("<h2>work (2)</h2>"));
+        assert!(html.contains("<li class=\"done\">ship &lt;beta&gt;</li>"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use uuid::Uuid;
+
+use crate::todo::TodoItem;
+
+/// How comment times are written, as org inactive timestamps.
+pub const COMMENT_TIME: &str = "%Y-%m-%d %a %H:%M";
+/// What joins the headings a todo is grouped under into its project, as in `Work/Notes`.
+pub const GROUP_SEPARATOR: &str = "/";
+
+fn heading(out: &mut String, level: usize, todo: &TodoItem) {
+    let keyword = if todo.done { "DONE" } else { "TODO" };
+    let priority = if todo.flagged { "[#A] " } else { "" };
+    let tags = if todo.tags.is_empty() {
+        String::new()
+    } else {
+        format!(" :{}:", todo.tags.join(":"))
+    };
+    out.push_str(&format!(
+        "{} {keyword} {priority}{}{tags}\n",
+        "*".repeat(level),
+        todo.title
+    ));
+    let planning: Vec<String> = [("SCHEDULED", todo.scheduled), ("DEADLINE", todo.due)]
+        .into_iter()
+        .filter_map(|(keyword, date)| Some(format!("{keyword}: <{}>", date?.format("%Y-%m-%d %a"))))
+        .collect();
+    if !planning.is_empty() {
+        out.push_str(&format!(
+            "{}{}\n",
+            " ".repeat(level + 1),
+            planning.join(" ")
+        ));
+    }
+    for comment in &todo.comments {
+        let at = comment.at.with_timezone(&Local).format(COMMENT_TIME);
+        out.push_str(&format!(
+            "{}- [{at}] {}\n",
+            " ".repeat(level + 1),
+            comment.text
+        ));
+    }
+}
+
+/// Writes `todo` at `level` with its subtasks nested below it.
+fn subtree(out: &mut String, level: usize, todo: &TodoItem, todos: &[TodoItem]) {
+    heading(out, level, todo);
+    for subtask in todos
+        .iter()
+        .filter(|subtask| subtask.parent == Some(todo.id))
+    {
+        subtree(out, level + 1, subtask, todos);
+    }
+}
+
+/// Renders todos as an org file: todos without a project at the top level, then a heading for
+/// each group on the path of every project, holding its todos, in the order projects first
+/// appear. Subtasks are nested under the todo they belong to.
+pub fn render(todos: &[TodoItem]) -> String {
+    let ids: HashSet<Uuid> = todos.iter().map(|todo| todo.id).collect();
+    let roots: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|todo| !todo.parent.is_some_and(|parent| ids.contains(&parent)))
+        .collect();
+
+    let mut out = String::new();
+    for todo in roots.iter().filter(|todo| todo.project.is_none()) {
+        subtree(&mut out, 1, todo, todos);
+    }
+
+    let mut projects: Vec<&str> = Vec::new();
+    for project in roots.iter().filter_map(|todo| todo.project.as_deref()) {
+        if !projects.contains(&project) {
+            projects.push(project);
+        }
+    }
+    // The groups the last heading written is under, which the next project may share
+    let mut open: Vec<&str> = Vec::new();
+    for project in projects {
+        let path: Vec<&str> = project.split(GROUP_SEPARATOR).collect();
+        let shared = open.iter().zip(&path).take_while(|(a, b)| a == b).count();
+        for (depth, group) in path.iter().enumerate().skip(shared) {
+            out.push_str(&format!("{} {group}\n", "*".repeat(depth + 1)));
+        }
+        for todo in roots
+            .iter()
+            .filter(|todo| todo.project.as_deref() == Some(project))
+        {
+            subtree(&mut out, path.len() + 1, todo, todos);
+        }
+        open = path;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::import;
+
+    #[test]
+    fn test_org_round_trip() {
+        let org = "* TODO Buy milk\n\
+                   * Work\n\
+                   ** TODO [#A] Ship release :work:\n\
+                   \u{20}  DEADLINE: <2024-04-02 Tue>\n\
+                   ** DONE Write notes\n";
+        assert_eq!(render(&import::org::parse(org)), org);
+    }
+
+    #[test]
+    fn test_hierarchy_round_trip() {
+        let org = "* Work\n\
+                   ** TODO Ship release\n\
+                   \u{20}  SCHEDULED: <2024-04-01 Mon> DEADLINE: <2024-04-02 Tue>\n\
+                   *** TODO Write changelog\n\
+                   **** DONE Collect merged changes\n\
+                   ** Notes\n\
+                   *** TODO Tidy up\n";
+        let outline = |todos: &[TodoItem]| -> Vec<(String, Option<String>, Option<String>)> {
+            todos
+                .iter()
+                .map(|todo| {
+                    let parent = todos.iter().find(|other| Some(other.id) == todo.parent);
+                    (
+                        todo.to_string(),
+                        todo.scheduled.map(|date| date.to_string()),
+                        parent.map(|parent| parent.title.clone()),
+                    )
+                })
+                .collect()
+        };
+        let todos = import::org::parse(org);
+        assert_eq!(
+            outline(&todos),
+            vec![
+                (
+                    "Ship release +Work (due 2024-04-02)".into(),
+                    Some("2024-04-01".into()),
+                    None
+                ),
+                (
+                    "Write changelog +Work".into(),
+                    None,
+                    Some("Ship release".into())
+                ),
+                (
+                    "Collect merged changes +Work".into(),
+                    None,
+                    Some("Write changelog".into())
+                ),
+                ("Tidy up +Work/Notes".into(), None, None),
+            ]
+        );
+        let rendered = render(&todos);
+        assert_eq!(rendered, org);
+        assert_eq!(outline(&import::org::parse(&rendered)), outline(&todos));
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        let org = "* TODO Write report\n\
+                   \u{20} - [2024-05-01 Wed 09:30] outline done\n\
+                   \u{20} - [2024-05-02 Thu 17:05] first draft\n";
+        let todos = import::org::parse(org);
+        assert_eq!(todos[0].comments.len(), 2);
+        assert_eq!(todos[0].comments[1].text, "first draft");
+        assert_eq!(render(&todos), org);
+    }
+}
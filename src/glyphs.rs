@@ -0,0 +1,72 @@
+use ratatui::buffer::Buffer;
+
+/// The plain ASCII stand-in for a border or symbol character, if it is one.
+fn ascii(c: char) -> Option<char> {
+    let stand_in = match c {
+        '─' | '━' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '═' | '╴' | '╶' | '╸' | '╺' => {
+            '-'
+        }
+        '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '║' | '╵' | '╷' | '╹' | '╻' => {
+            '|'
+        }
+        '\u{2500}'..='\u{257f}' => '+',
+        '\u{2580}'..='\u{259f}' | '■' => '#',
+        '\u{2800}'..='\u{28ff}' | '·' => '.',
+        '★' => '*',
+        '↳' => '-',
+        '▸' | '▶' => '>',
+        '●' => 'o',
+        '⚑' => '!',
+        '✓' => '+',
+        _ => return None,
+    };
+    Some(stand_in)
+}
+
+/// Replaces the borders and symbols in a drawn frame with plain ASCII, leaving any other text,
+/// such as accented letters in a todo, alone.
+pub fn to_ascii(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        let mut chars = cell.symbol().chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if let Some(stand_in) = ascii(c) {
+                cell.set_char(stand_in);
+            }
+        }
+    }
+}
+
+/// Blanks out the borders in a drawn frame, leaving their titles, so that a screen reader only
+/// has text to read.
+pub fn strip_borders(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        let mut chars = cell.symbol().chars();
+        if let (Some('\u{2500}'..='\u{257f}'), None) = (chars.next(), chars.next()) {
+            cell.set_char(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::{layout::Rect, widgets::*};
+
+    use super::*;
+
+    #[test]
+    fn test_to_ascii() {
+        let area = Rect::new(0, 0, 8, 3);
+        let mut buffer = Buffer::empty(area);
+        Widget::render(
+            Paragraph::new("★ café").block(Block::default().borders(Borders::ALL)),
+            area,
+            &mut buffer,
+        );
+        to_ascii(&mut buffer);
+        assert_eq!(
+            buffer,
+            Buffer::with_lines(vec!["+------+", "|* café|", "+------+"])
+        );
+    }
+}
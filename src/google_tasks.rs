@@ -0,0 +1,423 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use color_eyre::eyre::{bail, eyre, Result};
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use uuid::Uuid;
+
+use crate::{
+    config::{Config, GoogleTasksConfig},
+    todo::{self, TodoItem},
+    utils::{read_json, write_json},
+};
+
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const TASKS_URL: &str = "https://tasks.googleapis.com/tasks/v1";
+const SCOPE: &str = "https://www.googleapis.com/auth/tasks";
+
+pub fn token_path(config: &Config) -> PathBuf {
+    config.config._config_dir.join("google_tokens.json")
+}
+
+pub fn state_path() -> PathBuf {
+    todo::state_file().with_file_name("google_tasks.json")
+}
+
+/// OAuth tokens, kept in the config directory between runs.
+#[derive(Debug, Serialize, Deserialize)]
+struct Tokens {
+    access_token: String,
+    refresh_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl Tokens {
+    /// Writes the tokens to `path`, readable by the user alone as they grant access to their
+    /// tasks.
+    fn save(&self, path: &Path) -> Result<()> {
+        write_json(path, self)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: Option<String>,
+}
+
+/// Which remote task each todo is linked to, and when the last sync started.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    pub last_sync: Option<DateTime<Utc>>,
+    pub links: BTreeMap<Uuid, String>,
+}
+
+/// What a sync brought in from Google Tasks, for the local list.
+#[derive(Debug, Default, PartialEq)]
+pub struct Pulled {
+    /// Todos changed or added there, to upsert.
+    pub upserts: Vec<TodoItem>,
+    /// Todos whose task was deleted there, to remove.
+    pub deleted: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Task {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    status: String,
+    due: Option<DateTime<Utc>>,
+    updated: DateTime<Utc>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskPage {
+    #[serde(default)]
+    items: Vec<Task>,
+    next_page_token: Option<String>,
+}
+
+impl Task {
+    fn apply_to(&self, todo: &mut TodoItem) {
+        todo.title = self.title.clone();
+        todo.done = self.status == "completed";
+        todo.due = self.due.map(|due| due.date_naive());
+        todo.updated = self.updated;
+    }
+}
+
+fn task_body(todo: &TodoItem) -> serde_json::Value {
+    let status = if todo.done {
+        "completed"
+    } else {
+        "needsAction"
+    };
+    // The API only keeps the date part of `due` but requires a full timestamp
+    let due = todo.due.map(|due| format!("{due}T00:00:00.000Z"));
+    json!({ "title": todo.title, "status": status, "due": due })
+}
+
+/// The PKCE challenge for `verifier`, as the `S256` method has it.
+fn challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Runs the installed-app OAuth flow: the user approves access in a browser, which redirects back
+/// to a one-shot listener on the loopback interface. A random `state` ties the redirect to this
+/// request, and PKCE keeps a code caught on its way back from being traded for tokens elsewhere.
+pub async fn authorize(config: &GoogleTasksConfig, token_path: &Path) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let redirect_uri = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let state = Uuid::new_v4().simple().to_string();
+    let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let auth_url = Url::parse_with_params(
+        AUTH_URL,
+        &[
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", SCOPE),
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+            ("state", state.as_str()),
+            ("code_challenge", challenge(&verifier).as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )?;
+    println!("Open this URL in a browser to allow access to Google Tasks:\n\n{auth_url}\n");
+
+    let (mut stream, _) = listener.accept().await?;
+    let mut request = vec![0; 4096];
+    let read = stream.read(&mut request).await?;
+    let request = String::from_utf8_lossy(&request[..read]);
+    let path = request
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| eyre!("Malformed redirect request"))?;
+    let query: HashMap<String, String> = Url::parse(&format!("http://localhost{path}"))?
+        .query_pairs()
+        .into_owned()
+        .collect();
+    let code = query
+        .get("code")
+        .filter(|_| query.get("state") == Some(&state));
+    let reply = if code.is_some() {
+        "Access granted, you can close this window."
+    } else {
+        "Access was not granted."
+    };
+    stream
+        .write_all(format!("HTTP/1.1 200 OK\r\n\r\n{reply}").as_bytes())
+        .await?;
+    let Some(code) = code else {
+        bail!("Authorization was denied");
+    };
+
+    let response: TokenResponse = Client::new()
+        .post(TOKEN_URL)
+        .form(&[
+            ("code", code.as_str()),
+            ("code_verifier", verifier.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let tokens = Tokens {
+        access_token: response.access_token,
+        refresh_token: response
+            .refresh_token
+            .ok_or_else(|| eyre!("Google did not return a refresh token"))?,
+        expires_at: Utc::now() + Duration::seconds(response.expires_in),
+    };
+    tokens.save(token_path)
+}
+
+/// Returns a valid access token, refreshing and storing it if it has expired.
+async fn access_token(client: &Client, config: &GoogleTasksConfig, path: &Path) -> Result<String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|_| eyre!("Not authorized with Google Tasks yet, run `sync --google` first"))?;
+    let mut tokens: Tokens = serde_json::from_str(&raw)?;
+    if tokens.expires_at > Utc::now() + Duration::seconds(60) {
+        return Ok(tokens.access_token);
+    }
+
+    let response: TokenResponse = client
+        .post(TOKEN_URL)
+        .form(&[
+            ("refresh_token", tokens.refresh_token.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    tokens.access_token = response.access_token;
+    tokens.expires_at = Utc::now() + Duration::seconds(response.expires_in);
+    tokens.save(path)?;
+    Ok(tokens.access_token)
+}
+
+async fn list_tasks(
+    client: &Client,
+    token: &str,
+    tasklist: &str,
+    updated_min: Option<DateTime<Utc>>,
+) -> Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut request = client
+            .get(format!("{TASKS_URL}/lists/{tasklist}/tasks"))
+            .bearer_auth(token)
+            .query(&[
+                ("showCompleted", "true"),
+                ("showHidden", "true"),
+                ("showDeleted", "true"),
+                ("maxResults", "100"),
+            ]);
+        if let Some(updated_min) = updated_min {
+            request = request.query(&[("updatedMin", updated_min.to_rfc3339())]);
+        }
+        if let Some(page_token) = &page_token {
+            request = request.query(&[("pageToken", page_token)]);
+        }
+        let page: TaskPage = request.send().await?.error_for_status()?.json().await?;
+        tasks.extend(page.items);
+        match page.next_page_token {
+            Some(next) => page_token = Some(next),
+            None => return Ok(tasks),
+        }
+    }
+}
+
+/// Applies `tasks` changed remotely to the todos linked to them in `state`, linking new ones.
+/// Deleted tasks take their todos with them, and are unlinked.
+fn pull(tasks: Vec<Task>, state: &mut SyncState, local: &[TodoItem]) -> Pulled {
+    let local_by_id: HashMap<Uuid, &TodoItem> = local.iter().map(|todo| (todo.id, todo)).collect();
+    let todo_by_task: HashMap<String, Uuid> = state
+        .links
+        .iter()
+        .map(|(id, task)| (task.clone(), *id))
+        .collect();
+
+    let mut pulled = Pulled::default();
+    for task in tasks {
+        match todo_by_task.get(&task.id) {
+            Some(id) if task.deleted => {
+                state.links.remove(id);
+                pulled.deleted.push(*id);
+            }
+            Some(id) => {
+                if let Some(todo) = local_by_id.get(id).filter(|t| task.updated > t.updated) {
+                    let mut todo = (*todo).clone();
+                    task.apply_to(&mut todo);
+                    pulled.upserts.push(todo);
+                }
+            }
+            None if task.deleted => {}
+            None => {
+                let mut todo = TodoItem::new(String::new());
+                task.apply_to(&mut todo);
+                state.links.insert(todo.id, task.id.clone());
+                pulled.upserts.push(todo);
+            }
+        }
+    }
+    pulled
+}
+
+/// Exchanges changes made since the last sync in both directions.
+///
+/// Remote changes are returned to apply to the local list, rather than applied directly, so that
+/// a running session can merge them into whatever it holds by then. Where both sides changed a
+/// todo, the more recently updated one wins. A todo whose task is gone, deleted or no longer
+/// found, is deleted too.
+pub async fn sync(
+    config: &GoogleTasksConfig,
+    token_path: &Path,
+    state_path: &Path,
+    local: &[TodoItem],
+) -> Result<Pulled> {
+    let client = Client::new();
+    let token = access_token(&client, config, token_path).await?;
+    let mut state: SyncState = read_json(state_path)?;
+    let started = Utc::now();
+    let tasklist = &config.tasklist;
+
+    let tasks = list_tasks(&client, &token, tasklist, state.last_sync).await?;
+    let mut pulled = pull(tasks, &mut state, local);
+    let done: HashSet<Uuid> = pulled
+        .upserts
+        .iter()
+        .map(|todo| todo.id)
+        .chain(pulled.deleted.iter().copied())
+        .collect();
+
+    let changed = local.iter().filter(|todo| {
+        !done.contains(&todo.id) && state.last_sync.is_none_or(|last| todo.updated > last)
+    });
+    for todo in changed {
+        match state.links.get(&todo.id) {
+            Some(task_id) => {
+                let response = client
+                    .patch(format!("{TASKS_URL}/lists/{tasklist}/tasks/{task_id}"))
+                    .bearer_auth(&token)
+                    .json(&task_body(todo))
+                    .send()
+                    .await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    state.links.remove(&todo.id);
+                    pulled.deleted.push(todo.id);
+                } else {
+                    response.error_for_status()?;
+                }
+            }
+            None => {
+                let task: Task = client
+                    .post(format!("{TASKS_URL}/lists/{tasklist}/tasks"))
+                    .bearer_auth(&token)
+                    .json(&task_body(todo))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                state.links.insert(todo.id, task.id);
+            }
+        }
+    }
+
+    state.last_sync = Some(started);
+    write_json(state_path, &state)?;
+    Ok(pulled)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_task_round_trip() -> Result<()> {
+        let task: Task = serde_json::from_value(json!({
+            "id": "abc",
+            "title": "Renew passport",
+            "status": "completed",
+            "due": "2024-06-01T00:00:00.000Z",
+            "updated": "2024-05-01T10:00:00.000Z"
+        }))?;
+        let mut todo = TodoItem::new(String::new());
+        task.apply_to(&mut todo);
+        assert_eq!(todo.to_string(), "Renew passport (due 2024-06-01)");
+        assert!(todo.done);
+        assert_eq!(
+            task_body(&todo),
+            json!({
+                "title": "Renew passport",
+                "status": "completed",
+                "due": "2024-06-01T00:00:00.000Z"
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // The example in RFC 7636, appendix B
+        assert_eq!(
+            challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_pull_deleted_tasks() -> Result<()> {
+        let linked = TodoItem::new("Renew passport".into());
+        let mut state = SyncState::default();
+        state.links.insert(linked.id, "abc".into());
+        let tasks: Vec<Task> = serde_json::from_value(json!([
+            { "id": "abc", "updated": "2024-05-01T10:00:00.000Z", "deleted": true },
+            { "id": "def", "updated": "2024-05-01T10:00:00.000Z", "deleted": true }
+        ]))?;
+        let pulled = pull(tasks, &mut state, std::slice::from_ref(&linked));
+        assert_eq!(pulled.deleted, vec![linked.id]);
+        assert!(pulled.upserts.is_empty());
+        assert!(state.links.is_empty());
+        Ok(())
+    }
+}
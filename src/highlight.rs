@@ -0,0 +1,98 @@
+use std::sync::OnceLock;
+
+use ratatui::prelude::*;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+/// The syntect theme code is highlighted with under each of `themes::PRESETS`.
+const PRESETS: [(&str, &str); 6] = [
+    ("gruvbox", "base16-mocha.dark"),
+    ("solarized-dark", "Solarized (dark)"),
+    ("solarized-light", "Solarized (light)"),
+    ("catppuccin", "base16-mocha.dark"),
+    ("monochrome", "base16-ocean.dark"),
+    (crate::themes::HIGH_CONTRAST, "base16-eighties.dark"),
+];
+
+/// The syntect theme used when neither `code_theme` nor `theme` picks one.
+const DEFAULT: &str = "base16-ocean.dark";
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// The names of the syntect themes that `code_theme` can pick.
+pub fn names() -> Vec<&'static str> {
+    themes().themes.keys().map(String::as_str).collect()
+}
+
+/// The syntect theme for code: `code_theme` if set, otherwise the one going with `theme`.
+pub fn theme<'a>(code_theme: Option<&'a str>, theme: Option<&str>) -> &'a str {
+    code_theme.unwrap_or_else(|| {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| Some(*preset) == theme)
+            .map_or(DEFAULT, |(_, code_theme)| code_theme)
+    })
+}
+
+/// `code` in `language`, named by its token or file extension such as `rust` or `rs`, as lines
+/// colored by the syntect theme `theme`. None when either is unknown.
+pub fn lines(code: &str, language: &str, theme: &str) -> Option<Vec<Line<'static>>> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(language)?;
+    let mut highlighter = HighlightLines::new(syntax, themes().themes.get(theme)?);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = highlighter.highlight_line(line, syntaxes).ok()?;
+            let spans = ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let mut modifier = Modifier::empty();
+                    if style.font_style.contains(FontStyle::BOLD) {
+                        modifier |= Modifier::BOLD;
+                    }
+                    if style.font_style.contains(FontStyle::ITALIC) {
+                        modifier |= Modifier::ITALIC;
+                    }
+                    if style.font_style.contains(FontStyle::UNDERLINE) {
+                        modifier |= Modifier::UNDERLINED;
+                    }
+                    let color =
+                        Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+                    let text = text.trim_end_matches(['\n', '\r']).to_string();
+                    Span::styled(text, Style::new().fg(color).add_modifier(modifier))
+                })
+                .collect::<Vec<_>>();
+            Some(Line::from(spans))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let highlighted = lines("let x = 1;\nx + 1\n", "rust", DEFAULT).unwrap();
+        assert_eq!(highlighted.len(), 2);
+        let keyword = &highlighted[0].spans[0];
+        assert_eq!(keyword.content, "let");
+        assert!(matches!(keyword.style.fg, Some(Color::Rgb(..))));
+        assert!(lines("x", "no-such-language", DEFAULT).is_none());
+        assert_eq!(theme(None, Some("solarized-light")), "Solarized (light)");
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+};
+
+use crate::links;
+
+/// Text drawn in a frame that can be clicked to open `url`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    pub url: String,
+    /// The cells the text is drawn in, by column and row.
+    pub cells: Vec<(u16, u16, Cell)>,
+}
+
+/// Whether the terminal makes OSC 8 hyperlinks clickable, going by what `var` says of the
+/// environment. Others may show the escape codes as text, so links are left out there.
+pub fn detect(var: impl Fn(&str) -> Option<String>) -> bool {
+    let term = var("TERM").unwrap_or_default();
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    let vte = var("VTE_VERSION").and_then(|version| version.parse::<u32>().ok());
+    matches!(
+        program.as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+    ) || term.contains("kitty")
+        || term.starts_with("foot")
+        || term.starts_with("alacritty")
+        || var("WT_SESSION").is_some()
+        || var("KONSOLE_VERSION").is_some()
+        || vte.is_some_and(|version| version >= 5000)
+}
+
+/// Where an attachment at `path` opens from, as a `file://` URL, if the file is there.
+pub fn file_url(path: &str) -> Option<String> {
+    let path = Path::new(path).canonicalize().ok()?;
+    Some(format!(
+        "file://{}",
+        path.to_string_lossy()
+            .replace('%', "%25")
+            .replace(' ', "%20")
+    ))
+}
+
+/// Text a component drew that links somewhere: `text`, opening `url`, written somewhere in
+/// `area`, where it may be cut short with a `…` or wrapped onto the following rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub area: Rect,
+    pub text: String,
+    pub url: String,
+}
+
+/// The cells of one word drawn on a row, with their columns.
+type Word<'a> = Vec<(&'a str, u16)>;
+
+/// The words drawn on row `y` of `area`, with punctuation around them left out at the start.
+fn words(buffer: &Buffer, area: Rect, y: u16) -> Vec<Word<'_>> {
+    let mut words = vec![Word::new()];
+    for x in area.left()..area.right() {
+        let symbol = buffer.get(x, y).symbol();
+        if symbol.trim().is_empty() {
+            words.push(Word::new());
+            continue;
+        }
+        let word = words.last_mut().expect("starts with a word");
+        if !(word.is_empty() && symbol.chars().all(|c| links::WRAPPERS.contains(&c))) {
+            word.push((symbol, x));
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+fn text(word: &[(&str, u16)]) -> String {
+    word.iter().map(|(symbol, _)| *symbol).collect()
+}
+
+/// `word` without the punctuation after it.
+fn trimmed<'a, 'b>(word: &'a [(&'b str, u16)]) -> &'a [(&'b str, u16)] {
+    let end = word
+        .iter()
+        .rposition(|(symbol, _)| !symbol.chars().all(|c| links::TRAILERS.contains(&c)))
+        .map_or(0, |last| last + 1);
+    &word[..end]
+}
+
+/// Whether `shown` is `text` as drawn, whole or cut short in the middle or at the end.
+fn shows(shown: &str, text: &str) -> bool {
+    match shown.split_once('…') {
+        None => shown == text,
+        Some((head, tail)) => {
+            !head.is_empty()
+                && head.len() + tail.len() < text.len()
+                && text.starts_with(head)
+                && text.ends_with(tail)
+        }
+    }
+}
+
+/// Where each of `targets` is drawn in `buffer`, as whole words only, so that a link is never
+/// found inside other text. A word at the end of a row that starts a target is followed onto
+/// the next rows, for text that was wrapped.
+pub fn find(buffer: &Buffer, targets: &[Target]) -> Vec<Link> {
+    let mut found: Vec<Link> = Vec::new();
+    for target in targets.iter().filter(|target| !target.text.is_empty()) {
+        let area = target.area.intersection(buffer.area);
+        let rows: Vec<Vec<Word>> = (area.top()..area.bottom())
+            .map(|y| words(buffer, area, y))
+            .collect();
+        for (row, y) in rows.iter().zip(area.top()..) {
+            for (i, word) in row.iter().enumerate() {
+                let mut cells: Vec<(u16, u16)> = Vec::new();
+                if shows(&text(trimmed(word)), &target.text) {
+                    cells.extend(trimmed(word).iter().map(|&(_, x)| (x, y)));
+                } else if i + 1 == row.len() {
+                    // Wrapped: the text goes on at the start of the rows below
+                    let mut parts = vec![(word.as_slice(), y)];
+                    let mut shown = text(word);
+                    while target.text.starts_with(&shown) && shown != target.text {
+                        let below = y + parts.len() as u16;
+                        let Some(next) = rows.get(usize::from(below - area.top())) else {
+                            break;
+                        };
+                        let Some(first) = next.first() else {
+                            break;
+                        };
+                        shown.push_str(&text(first));
+                        parts.push((first, below));
+                        // Only a row the text fills can have more of it after
+                        if next.len() > 1 {
+                            break;
+                        }
+                    }
+                    let last = parts.len() - 1;
+                    let shown: String = parts[..last]
+                        .iter()
+                        .map(|(word, _)| text(word))
+                        .chain([text(trimmed(parts[last].0))])
+                        .collect();
+                    if last > 0 && shown == target.text {
+                        for (j, &(word, y)) in parts.iter().enumerate() {
+                            let word = if j == last { trimmed(word) } else { word };
+                            cells.extend(word.iter().map(|&(_, x)| (x, y)));
+                        }
+                    }
+                }
+                if cells.is_empty() {
+                    continue;
+                }
+                let link = Link {
+                    url: target.url.clone(),
+                    cells: cells
+                        .into_iter()
+                        .map(|(x, y)| (x, y, buffer.get(x, y).clone()))
+                        .collect(),
+                };
+                if !found.contains(&link) {
+                    found.push(link);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// The escape code that starts text linking to `url`.
+pub fn open(url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\")
+}
+
+/// The escape code that ends linked text.
+pub const CLOSE: &str = "\x1b]8;;\x1b\\";
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    #[test]
+    fn test_find_links() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 30, 5));
+        buffer.set_string(0, 0, "see (https://x.org/a), today", Default::default());
+        buffer.set_string(0, 1, "myscan.pdf ▸ scan.pdf", Default::default());
+        buffer.set_string(0, 2, "https://example.c…", Default::default());
+        buffer.set_string(12, 3, "https://exam", Default::default());
+        buffer.set_string(0, 4, "ple.com/b", Default::default());
+        let target = |y, text: &str, url: &str| Target {
+            area: Rect::new(0, y, 30, 1),
+            text: text.to_string(),
+            url: url.to_string(),
+        };
+        let mut wrapped = target(3, "https://example.com/b", "https://example.com/b");
+        wrapped.area.height = 2;
+        let links = find(
+            &buffer,
+            &[
+                target(0, "https://x.org/a", "https://x.org/a"),
+                target(1, "scan.pdf", "file:///home/me/scan.pdf"),
+                target(2, "https://example.com/a", "https://example.com/a"),
+                wrapped,
+                // Drawn, but not where its component drew it
+                target(2, "today", "https://example.com/today"),
+            ],
+        );
+        let found: Vec<(&str, Vec<(u16, u16)>)> = links
+            .iter()
+            .map(|link| {
+                let cells = link.cells.iter().map(|&(x, y, _)| (x, y)).collect();
+                (link.url.as_str(), cells)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("https://x.org/a", (5..20).map(|x| (x, 0)).collect()),
+                (
+                    "file:///home/me/scan.pdf",
+                    (13..21).map(|x| (x, 1)).collect()
+                ),
+                ("https://example.com/a", (0..18).map(|x| (x, 2)).collect()),
+                (
+                    "https://example.com/b",
+                    (12..24)
+                        .map(|x| (x, 3))
+                        .chain((0..9).map(|x| (x, 4)))
+                        .collect()
+                ),
+            ]
+        );
+        assert!(detect(
+            |name| (name == "TERM").then(|| "xterm-kitty".to_string())
+        ));
+        assert!(!detect(
+            |name| (name == "TERM").then(|| "xterm-256color".to_string())
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fmt, sync::OnceLock};
+
+use crate::template;
+
+/// The translations shipped with the app, by language code, as gettext PO files whose message
+/// ids are the English text.
+const CATALOGS: [(&str, &str); 1] = [("de", include_str!("../.config/locales/de.po"))];
+
+static CATALOG: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+/// A string as written between the quotes of a PO file, with its `\"`, `\\` and `\n` escapes read.
+/// Catalogs are read once and kept for good, so the few strings with escapes are leaked.
+fn unescape(text: &'static str) -> &'static str {
+    if !text.contains('\\') {
+        return text;
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some(escaped) => escaped,
+                None => c,
+            },
+            _ => c,
+        });
+    }
+    Box::leak(unescaped.into_boxed_str())
+}
+
+/// The `msgid` and `msgstr` pairs of a PO file, leaving out untranslated ones.
+fn parse(po: &'static str) -> HashMap<&'static str, &'static str> {
+    let quoted = |line: &'static str, keyword: &str| {
+        let text = line
+            .strip_prefix(keyword)?
+            .trim()
+            .strip_prefix('"')?
+            .strip_suffix('"')?;
+        Some(unescape(text))
+    };
+    let mut catalog = HashMap::new();
+    let mut id = None;
+    for line in po.lines() {
+        if let Some(text) = quoted(line, "msgid") {
+            id = Some(text);
+        } else if let (Some(text), Some(id)) = (quoted(line, "msgstr"), id.take()) {
+            if !id.is_empty() && !text.is_empty() {
+                catalog.insert(id, text);
+            }
+        }
+    }
+    catalog
+}
+
+/// The language to show, as a code like `de`: the one from the config, or else the one in
+/// `LC_ALL`, `LC_MESSAGES` or `LANG`, read through `var`.
+pub fn language(configured: Option<&str>, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let locale = configured.map(str::to_string).or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(var)
+            .find(|value| !value.is_empty())
+    })?;
+    let code = locale.split(['_', '.', '-', '@']).next()?.to_lowercase();
+    Some(code).filter(|code| !code.is_empty())
+}
+
+/// Picks the translation used from now on. Only the first call has any effect, and languages
+/// without a translation stay in English.
+pub fn init(language: Option<&str>) {
+    if let Some((_, po)) = CATALOGS.iter().find(|(code, _)| Some(*code) == language) {
+        let _ = CATALOG.set(parse(po));
+    }
+}
+
+/// `text` in the chosen language, or as it is when there is no translation for it.
+pub fn tr(text: &'static str) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(text).copied())
+        .unwrap_or(text)
+}
+
+/// `text` in the chosen language with its `{name}` placeholders filled in from `args`, so that
+/// a translation can put them wherever its word order needs them.
+pub fn tr_with(text: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    template::fill(tr(text), &|name| {
+        args.iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_language_and_catalog() {
+        let env = |name: &str| (name == "LANG").then(|| "de_DE.UTF-8".to_string());
+        assert_eq!(language(None, env).as_deref(), Some("de"));
+        assert_eq!(language(Some("fr"), env).as_deref(), Some("fr"));
+        assert_eq!(language(None, |_| None), None);
+
+        let catalog = parse(CATALOGS[0].1);
+        assert_eq!(catalog.get("Press "), Some(&"Drücke "));
+        assert_eq!(catalog.get("Browsing"), Some(&"Blättern"));
+        assert!(!catalog.contains_key(""));
+        assert_eq!(
+            catalog.get("Complete \"{title}\""),
+            Some(&"„{title}“ erledigen")
+        );
+    }
+
+    #[test]
+    fn test_fill_in_placeholders() {
+        assert_eq!(
+            tr_with("Saved to {path}", &[("path", &"/tmp/{x}.json")]),
+            "Saved to /tmp/{x}.json"
+        );
+        assert_eq!(
+            tr_with("Move \"{title}\" to {quadrant}", &[("title", &"a")]),
+            "Move \"a\" to {quadrant}"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::{collections::BTreeSet, fmt::Write, fs, path::Path, sync::OnceLock};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::eyre::Result;
+use image::{imageops::FilterType, DynamicImage, RgbImage};
+use ratatui::layout::Rect;
+
+/// The ways a terminal can be asked to draw an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    /// iTerm2's inline images, which WezTerm understands too.
+    Iterm,
+    Sixel,
+}
+
+/// The cell size assumed when the terminal doesn't report its size in pixels.
+const CELL: (u32, u32) = (8, 16);
+/// How many bytes of image a kitty escape sequence carries at most.
+const KITTY_CHUNK: usize = 4096;
+
+/// How the terminal running the app draws images, going by what it says about itself in the
+/// environment, or none when it can't or won't say.
+pub fn protocol() -> Option<Protocol> {
+    static PROTOCOL: OnceLock<Option<Protocol>> = OnceLock::new();
+    *PROTOCOL.get_or_init(|| {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        detect(&var("TERM"), &var("TERM_PROGRAM"), &var("LC_TERMINAL"))
+    })
+}
+
+/// The protocol for a terminal from its `TERM`, `TERM_PROGRAM` and `LC_TERMINAL`.
+fn detect(term: &str, program: &str, lc_terminal: &str) -> Option<Protocol> {
+    if term.contains("kitty") || program == "ghostty" {
+        Some(Protocol::Kitty)
+    } else if matches!(program, "iTerm.app" | "WezTerm") || lc_terminal == "iTerm2" {
+        Some(Protocol::Iterm)
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// Whether the attachment at `path` is an image that can be previewed.
+pub fn is_image(path: &str) -> bool {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    matches!(
+        extension.as_deref(),
+        Some("png" | "jpg" | "jpeg" | "gif" | "webp")
+    )
+}
+
+/// The size of a cell in pixels.
+fn cell() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            u32::from(size.width / size.columns),
+            u32::from(size.height / size.rows),
+        ),
+        _ => CELL,
+    }
+}
+
+/// The image at `path` scaled to fit `area`, keeping its proportions.
+fn load(path: &Path, area: Rect) -> Result<DynamicImage> {
+    let (width, height) = cell();
+    let image = image::open(path)?;
+    Ok(image.resize(
+        u32::from(area.width) * width,
+        u32::from(area.height) * height,
+        FilterType::Triangle,
+    ))
+}
+
+/// What to write to the terminal to draw the image at `path` in `area`, starting at the cursor.
+pub fn draw(protocol: Protocol, path: &Path, area: Rect) -> Result<String> {
+    Ok(match protocol {
+        Protocol::Kitty => {
+            let image = load(path, area)?.to_rgba8();
+            let data = STANDARD.encode(image.as_raw());
+            let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut out = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                let chunk = std::str::from_utf8(chunk)?;
+                if i == 0 {
+                    let (width, height) = image.dimensions();
+                    write!(
+                        out,
+                        "\x1b_Ga=T,f=32,s={width},v={height},C=1,q=2,m={more};{chunk}\x1b\\"
+                    )?;
+                } else {
+                    write!(out, "\x1b_Gm={more};{chunk}\x1b\\")?;
+                }
+            }
+            out
+        }
+        Protocol::Iterm => {
+            let bytes = fs::read(path)?;
+            format!(
+                "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                bytes.len(),
+                area.width,
+                area.height,
+                STANDARD.encode(&bytes)
+            )
+        }
+        Protocol::Sixel => sixel(&load(path, area)?.to_rgb8()),
+    })
+}
+
+/// What to write to the terminal to take away images drawn before, where they aren't simply
+/// drawn over.
+pub fn clear(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+        Protocol::Iterm | Protocol::Sixel => "",
+    }
+}
+
+/// `image` as sixels, in 216 colors.
+fn sixel(image: &RgbImage) -> String {
+    let level = |value: u8| u16::from(value) * 5 / 255;
+    let colors: Vec<u16> = image
+        .pixels()
+        .map(|pixel| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+        .collect();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut out = format!("\x1bPq\"1;1;{width};{height}");
+    for color in colors.iter().collect::<BTreeSet<_>>() {
+        let percent = |level: u16| level * 100 / 5;
+        let _ = write!(
+            out,
+            "#{color};2;{};{};{}",
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+    for top in (0..height).step_by(6) {
+        let band = top..(top + 6).min(height);
+        let used: BTreeSet<u16> = band
+            .clone()
+            .flat_map(|y| colors[y * width..(y + 1) * width].iter().copied())
+            .collect();
+        for color in used {
+            let _ = write!(out, "#{color}");
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..width {
+                let bits = band
+                    .clone()
+                    .filter(|y| colors[y * width + x] == color)
+                    .fold(0, |bits, y| bits | 1 << (y - top));
+                let c = char::from(63 + bits as u8);
+                run = match run {
+                    Some((last, n)) if last == c => Some((c, n + 1)),
+                    Some(last) => {
+                        push_run(&mut out, last);
+                        Some((c, 1))
+                    }
+                    None => Some((c, 1)),
+                };
+            }
+            if let Some(last) = run {
+                push_run(&mut out, last);
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Writes `n` of the sixel `c`, run-length encoded when that is shorter.
+fn push_run(out: &mut String, (c, n): (char, usize)) {
+    if n > 3 {
+        let _ = write!(out, "!{n}{c}");
+    } else {
+        out.extend(std::iter::repeat_n(c, n));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect_and_encode() {
+        assert_eq!(detect("xterm-kitty", "", ""), Some(Protocol::Kitty));
+        assert_eq!(
+            detect("xterm-256color", "WezTerm", ""),
+            Some(Protocol::Iterm)
+        );
+        assert_eq!(detect("foot", "", ""), Some(Protocol::Sixel));
+        assert_eq!(detect("xterm-256color", "Apple_Terminal", ""), None);
+        assert!(is_image("scans/receipt.JPG"));
+        assert!(!is_image("notes.md"));
+
+        // Three red pixels and a blue one, as one band of sixels
+        let image = RgbImage::from_fn(2, 2, |x, y| {
+            if y == 0 || x == 0 {
+                image::Rgb([255, 0, 0])
+            } else {
+                image::Rgb([0, 0, 255])
+            }
+        });
+        assert_eq!(
+            sixel(&image),
+            "\x1bPq\"1;1;2;2#5;2;0;0;100#180;2;100;0;0#5?A$#180B@$-\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+
+use crate::todo::{self, TodoItem};
+
+pub mod org;
+pub mod reminders;
+pub mod trello;
+
+/// Appends imported todos to the data file, returning how many were added.
+pub fn append(path: &Path, imported: Vec<TodoItem>) -> Result<usize> {
+    let count = imported.len();
+    todo::update(path, |todos| todos.extend(imported))?;
+    Ok(count)
+}
@@ -0,0 +1,171 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::{
+    export::org::{COMMENT_TIME, GROUP_SEPARATOR},
+    intern::Name,
+    todo::{Comment, TodoItem},
+};
+
+/// A heading above the line being read.
+enum Outline {
+    /// A heading without a keyword, which only groups what is below it.
+    Group(String),
+    Todo(Uuid),
+}
+
+struct Heading<'a> {
+    level: usize,
+    keyword: Option<&'a str>,
+    flagged: bool,
+    title: String,
+    tags: Vec<String>,
+}
+
+impl<'a> Heading<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let level = line.chars().take_while(|c| *c == '*').count();
+        let rest = line[level..].strip_prefix(' ')?;
+        if level == 0 {
+            return None;
+        }
+
+        let mut words: Vec<&str> = rest.split_whitespace().collect();
+        let keyword = words
+            .first()
+            .copied()
+            .filter(|word| *word == "TODO" || *word == "DONE");
+        if keyword.is_some() {
+            words.remove(0);
+        }
+        let flagged = words.first() == Some(&"[#A]");
+        if words
+            .first()
+            .is_some_and(|word| word.starts_with("[#") && word.ends_with(']'))
+        {
+            words.remove(0);
+        }
+        let tags = match words.last() {
+            Some(last) if last.len() > 1 && last.starts_with(':') && last.ends_with(':') => {
+                let tags = last
+                    .split(':')
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                words.pop();
+                tags
+            }
+            _ => Vec::new(),
+        };
+
+        Some(Self {
+            level,
+            keyword,
+            flagged,
+            title: words.join(" "),
+            tags,
+        })
+    }
+}
+
+/// Finds the date of a `KEYWORD: <YYYY-MM-DD ...>` timestamp on a planning line.
+fn planning_date(line: &str, keyword: &str) -> Option<NaiveDate> {
+    let after = &line[line.find(keyword)? + keyword.len()..];
+    let date = after.trim_start().strip_prefix('<')?.get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// A `- [<timestamp>] text` list item under a todo, as the org export writes comments.
+fn comment(line: &str) -> Option<Comment> {
+    let (at, text) = line.trim_start().strip_prefix("- [")?.split_once("] ")?;
+    let at = NaiveDateTime::parse_from_str(at, COMMENT_TIME).ok()?;
+    Some(Comment {
+        at: Local
+            .from_local_datetime(&at)
+            .earliest()?
+            .with_timezone(&Utc),
+        text: text.to_string(),
+    })
+}
+
+/// Parses the `TODO` and `DONE` headings of an org file.
+///
+/// Headings without a keyword only group the todos below them, and the path of groups down to
+/// a todo, joined by `GROUP_SEPARATOR`, becomes its project. A todo right under another is its
+/// subtask. `DEADLINE` becomes the due date and `SCHEDULED` the scheduled one, an `[#A]` priority
+/// flags the todo and timestamped list items below it become its comments.
+pub fn parse(raw: &str) -> Vec<TodoItem> {
+    let mut todos: Vec<TodoItem> = Vec::new();
+    let mut outline: Vec<(usize, Outline)> = Vec::new();
+    let mut in_todo = false;
+
+    for line in raw.lines() {
+        if let Some(heading) = Heading::parse(line) {
+            outline.retain(|(level, _)| *level < heading.level);
+            in_todo = heading.keyword.is_some();
+            let Some(keyword) = heading.keyword else {
+                outline.push((heading.level, Outline::Group(heading.title)));
+                continue;
+            };
+            let mut todo = TodoItem::new(heading.title);
+            todo.done = keyword == "DONE";
+            todo.flagged = heading.flagged;
+            todo.tags = heading.tags.into_iter().map(Name::from).collect();
+            let groups: Vec<&str> = outline
+                .iter()
+                .filter_map(|(_, above)| match above {
+                    Outline::Group(title) => Some(title.as_str()),
+                    Outline::Todo(_) => None,
+                })
+                .collect();
+            todo.project = (!groups.is_empty()).then(|| groups.join(GROUP_SEPARATOR).into());
+            todo.parent = match outline.last() {
+                Some((_, Outline::Todo(parent))) => Some(*parent),
+                _ => None,
+            };
+            outline.push((heading.level, Outline::Todo(todo.id)));
+            todos.push(todo);
+        } else if let Some(todo) = todos.last_mut().filter(|_| in_todo) {
+            let deadline = planning_date(line, "DEADLINE:");
+            let scheduled = planning_date(line, "SCHEDULED:");
+            if deadline.is_some() || scheduled.is_some() {
+                todo.due = deadline.or(todo.due);
+                todo.scheduled = scheduled.or(todo.scheduled);
+            } else if let Some(comment) = comment(line) {
+                todo.comments.push(comment);
+            }
+        }
+    }
+    todos
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_org() {
+        let todos = parse(
+            "#+TITLE: Tasks\n\
+             * Work\n\
+             ** TODO [#A] Ship release :work:urgent:\n\
+             \u{20}  SCHEDULED: <2024-04-01 Mon> DEADLINE: <2024-04-02 Tue>\n\
+             ** Notes\n\
+             *** DONE Write notes\n\
+             * TODO Buy milk\n",
+        );
+        let rendered: Vec<String> = todos.iter().map(|todo| todo.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "Ship release +Work #work #urgent (due 2024-04-02) ⚑",
+                "Write notes +Work/Notes",
+                "Buy milk",
+            ]
+        );
+        assert!(todos[1].done);
+        assert_eq!(todos[0].scheduled, NaiveDate::from_ymd_opt(2024, 4, 1));
+    }
+}
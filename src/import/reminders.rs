@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use color_eyre::eyre::Result;
+use serde::Deserialize;
+
+use crate::{intern::Name, todo::TodoItem};
+
+/// One reminder as written by EventKit-based exporters, which name fields after `EKReminder`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Reminder {
+    title: String,
+    #[serde(default, alias = "calendar")]
+    list: Option<String>,
+    #[serde(default)]
+    due_date: Option<String>,
+    #[serde(default)]
+    due_date_components: Option<DateComponents>,
+    #[serde(default, alias = "isCompleted")]
+    completed: bool,
+    #[serde(default, alias = "isFlagged")]
+    flagged: bool,
+}
+
+/// The parts of a date as `NSDateComponents` holds them, any of which may be left out.
+#[derive(Deserialize)]
+struct DateComponents {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+impl DateComponents {
+    fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year?, self.month?, self.day?)
+    }
+}
+
+/// Parses a JSON array of reminders, mapping lists to projects.
+pub fn parse(raw: &str) -> Result<Vec<TodoItem>> {
+    let reminders: Vec<Reminder> = serde_json::from_str(raw)?;
+    Ok(reminders
+        .into_iter()
+        .map(|reminder| {
+            let mut todo = TodoItem::new(reminder.title);
+            todo.project = reminder.list.map(Name::from);
+            // Due dates may carry a time, which todos do not keep
+            todo.due = reminder
+                .due_date
+                .and_then(|due| NaiveDate::parse_from_str(due.get(..10)?, "%Y-%m-%d").ok())
+                .or_else(|| reminder.due_date_components?.date());
+            todo.done = reminder.completed;
+            todo.flagged = reminder.flagged;
+            todo
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_reminders() -> Result<()> {
+        let todos = parse(
+            r#"[
+                {"title": "Call mum", "list": "Family", "dueDate": "2024-05-12T09:00:00Z", "flagged": true},
+                {"title": "Old", "calendar": "Work", "isCompleted": true}
+            ]"#,
+        )?;
+        assert_eq!(todos[0].to_string(), "Call mum +Family (due 2024-05-12) ⚑");
+        assert_eq!(todos[1].project.as_deref(), Some("Work"));
+        assert!(todos[1].done);
+        Ok(())
+    }
+
+    #[test]
+    fn test_due_date_components() -> Result<()> {
+        let todos = parse(
+            r#"[
+                {"title": "Pay rent", "dueDateComponents": {"year": 2024, "month": 6, "day": 1, "hour": 9}},
+                {"title": "Someday", "dueDateComponents": {"month": 6, "day": 1}}
+            ]"#,
+        )?;
+        assert_eq!(todos[0].due, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(todos[1].due, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::NaiveDate;
+use color_eyre::eyre::Result;
+use serde::Deserialize;
+
+use crate::todo::TodoItem;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    name: String,
+    #[serde(default)]
+    desc: String,
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    closed: bool,
+    id_list: String,
+}
+
+/// The parts of a board's "Export as JSON" file that todos can hold.
+#[derive(Deserialize)]
+struct Board {
+    name: String,
+    #[serde(default)]
+    lists: Vec<List>,
+    #[serde(default)]
+    cards: Vec<Card>,
+}
+
+/// Turns a list name into a tag, which cannot contain spaces.
+fn tag(list: &str) -> String {
+    list.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Parses a board export, filing every card under the board as its project and tagging it with
+/// its list. Archived cards and cards on archived lists are left out.
+pub fn parse(raw: &str) -> Result<Vec<TodoItem>> {
+    let board: Board = serde_json::from_str(raw)?;
+    Ok(board
+        .cards
+        .into_iter()
+        .filter(|card| !card.closed)
+        .filter_map(|card| {
+            let list = board
+                .lists
+                .iter()
+                .find(|list| list.id == card.id_list && !list.closed)?;
+            let mut todo = TodoItem::new(card.name);
+            todo.project = Some(board.name.as_str().into());
+            todo.tags = vec![tag(&list.name).into()];
+            todo.notes = Some(card.desc).filter(|desc| !desc.is_empty());
+            todo.due = card
+                .due
+                .and_then(|due| NaiveDate::parse_from_str(due.get(..10)?, "%Y-%m-%d").ok());
+            todo.done = card.due_complete;
+            Some(todo)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_board() -> Result<()> {
+        let todos = parse(
+            r#"{
+                "name": "Launch",
+                "lists": [
+                    {"id": "l1", "name": "In Progress"},
+                    {"id": "l2", "name": "Old", "closed": true}
+                ],
+                "cards": [
+                    {"name": "Write copy", "desc": "For the landing page", "due": "2024-06-01T12:00:00.000Z", "idList": "l1"},
+                    {"name": "Archived", "closed": true, "idList": "l1"},
+                    {"name": "On an archived list", "idList": "l2"}
+                ]
+            }"#,
+        )?;
+        let rendered: Vec<String> = todos.iter().map(|todo| todo.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec!["Write copy +Launch #in-progress (due 2024-06-01)"]
+        );
+        assert_eq!(todos[0].notes.as_deref(), Some("For the landing page"));
+        Ok(())
+    }
+}
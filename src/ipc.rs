@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::{bail, Result, WrapErr};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::mpsc::UnboundedSender,
+};
+
+use crate::{action::Action, todo};
+
+/// A single newline-delimited JSON message sent by an external command to the running TUI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Request {
+    Add(String),
+    /// Passes a custom action on to the components, e.g. `{"Custom":["pomodoro",{"minutes":25}]}`
+    /// from a script.
+    Custom(String, serde_json::Value),
+}
+
+impl From<Request> for Action {
+    fn from(request: Request) -> Self {
+        match request {
+            Request::Add(raw) => Action::InsertTodo(raw),
+            Request::Custom(name, value) => Action::Custom(name, value),
+        }
+    }
+}
+
+/// The socket lives alongside the data file so that it always addresses the session editing it.
+pub fn socket_path() -> PathBuf {
+    todo::state_file().with_extension("sock")
+}
+
+/// Removes the socket file once the listener goes away, including when its task is aborted.
+#[cfg(unix)]
+struct SocketGuard(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Accepts connections on the session socket, forwarding each request to the app as an action.
+#[cfg(unix)]
+pub async fn serve(tx: UnboundedSender<Action>) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = socket_path();
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            bail!("Another session is already listening on {}", path.display());
+        }
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    let _guard = SocketGuard(path.clone());
+    // Anyone who can connect can change the todos, so only the user may
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    info!("Listening for commands on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let reply = match serde_json::from_str::<Request>(&line) {
+                    Ok(request) => match tx.send(request.into()) {
+                        Ok(()) => "ok".to_string(),
+                        Err(e) => format!("error: {e}"),
+                    },
+                    Err(e) => format!("error: {e}"),
+                };
+                if let Err(e) = writer.write_all(format!("{reply}\n").as_bytes()).await {
+                    error!("Failed to reply on command socket: {e:?}");
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn serve(tx: UnboundedSender<Action>) -> Result<()> {
+    bail!("The command socket is only supported on Unix platforms")
+}
+
+/// Sends a request to the running session and waits for it to be acknowledged.
+#[cfg(unix)]
+pub async fn send(request: &Request) -> Result<()> {
+    let path = socket_path();
+    let stream = tokio::net::UnixStream::connect(&path)
+        .await
+        .wrap_err_with(|| format!("No running session found at {}", path.display()))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", serde_json::to_string(request)?).as_bytes())
+        .await?;
+
+    let reply = BufReader::new(reader).lines().next_line().await?;
+    match reply.as_deref() {
+        Some("ok") => Ok(()),
+        Some(reply) => bail!("Session rejected the request: {reply}"),
+        None => bail!("Session closed the connection without replying"),
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn send(request: &Request) -> Result<()> {
+    bail!("The command socket is only supported on Unix platforms")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_custom_request_becomes_action() {
+        let request: Request =
+            serde_json::from_str(r#"{"Custom":["pomodoro",{"minutes":25}]}"#).unwrap();
+        assert_eq!(
+            Action::from(request),
+            Action::Custom("pomodoro".into(), json!({ "minutes": 25 }))
+        );
+    }
+}
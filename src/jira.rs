@@ -0,0 +1,271 @@
+use chrono::{NaiveDate, Utc};
+use color_eyre::eyre::{eyre, Result};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    config::JiraConfig,
+    todo::{EventKind, TodoItem},
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusCategory {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    status_category: StatusCategory,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fields {
+    summary: String,
+    duedate: Option<NaiveDate>,
+    status: Status,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    key: String,
+    fields: Fields,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    #[serde(default)]
+    issues: Vec<Issue>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Transition {
+    id: String,
+    to: Status,
+}
+
+#[derive(Deserialize)]
+struct Transitions {
+    transitions: Vec<Transition>,
+}
+
+impl Issue {
+    /// Builds the todo for an issue, with an id derived from its key so that every fetch updates
+    /// the same todo.
+    fn to_todo(&self, config: &JiraConfig, project: &str) -> TodoItem {
+        let url = issue_url(config, &self.key);
+        TodoItem {
+            id: Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes()),
+            updated: Utc::now(),
+            title: format!("{} {}", self.key, self.fields.summary),
+            due: self.fields.duedate,
+            done: self.fields.status.status_category.key == "done",
+            project: Some(project.into()),
+            url: Some(url),
+            ..TodoItem::default()
+        }
+    }
+}
+
+/// Brings fetched issues into `todos`. Only what Jira owns is taken from an issue already in the
+/// list, its summary, due date and completion, so local notes, tags and the rest stay, and the todo
+/// is only marked as changed when one of those differs. An issue completed locally stays done,
+/// since completing it sends its own transition to Jira.
+pub fn merge(todos: &mut Vec<TodoItem>, issues: Vec<TodoItem>) {
+    for issue in issues {
+        let Some(todo) = todos.iter_mut().find(|todo| todo.id == issue.id) else {
+            todos.push(issue);
+            continue;
+        };
+        let mut changed = false;
+        if todo.title != issue.title {
+            todo.title = issue.title;
+            changed = true;
+        }
+        if todo.due != issue.due {
+            todo.due = issue.due;
+            changed = true;
+        }
+        if issue.done && !todo.done {
+            todo.done = true;
+            todo.completed = Some(issue.updated);
+            todo.log(EventKind::Completed);
+            changed = true;
+        }
+        if changed {
+            todo.touch();
+        }
+    }
+}
+
+fn issue_url(config: &JiraConfig, key: &str) -> String {
+    format!("{}/browse/{key}", config.base_url.trim_end_matches('/'))
+}
+
+/// The key of the issue a todo was fetched from, if any.
+pub fn issue_key<'a>(config: &JiraConfig, todo: &'a TodoItem) -> Option<&'a str> {
+    let prefix = issue_url(config, "");
+    todo.url.as_deref()?.strip_prefix(&prefix)
+}
+
+fn request(client: &Client, config: &JiraConfig, method: Method, path: &str) -> RequestBuilder {
+    client
+        .request(
+            method,
+            format!(
+                "{}/rest/api/3/{path}",
+                config.base_url.trim_end_matches('/')
+            ),
+        )
+        .basic_auth(&config.email, Some(&config.api_token))
+}
+
+/// Runs every project's JQL query, returning the matching issues as todos in that project.
+pub async fn fetch(config: &JiraConfig) -> Result<Vec<TodoItem>> {
+    let client = Client::new();
+    let mut todos = Vec::new();
+    for (project, jql) in &config.projects {
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut search = request(&client, config, Method::GET, "search/jql").query(&[
+                ("jql", jql.as_str()),
+                ("fields", "summary,duedate,status"),
+                ("maxResults", "100"),
+            ]);
+            if let Some(page_token) = &page_token {
+                search = search.query(&[("nextPageToken", page_token)]);
+            }
+            let page: SearchPage = search.send().await?.error_for_status()?.json().await?;
+            todos.extend(
+                page.issues
+                    .iter()
+                    .map(|issue| issue.to_todo(config, project)),
+            );
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+    }
+    Ok(todos)
+}
+
+/// Moves an issue through the first available transition into a done status.
+pub async fn complete(config: &JiraConfig, key: &str) -> Result<()> {
+    transition(config, key, true).await
+}
+
+/// Moves an issue through the first available transition out of its done status.
+pub async fn reopen(config: &JiraConfig, key: &str) -> Result<()> {
+    transition(config, key, false).await
+}
+
+async fn transition(config: &JiraConfig, key: &str, done: bool) -> Result<()> {
+    let client = Client::new();
+    let path = format!("issue/{key}/transitions");
+    let available: Transitions = request(&client, config, Method::GET, &path)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let transition = available
+        .transitions
+        .iter()
+        .find(|transition| (transition.to.status_category.key == "done") == done)
+        .ok_or_else(|| {
+            let status = if done { "a done" } else { "an open" };
+            eyre!("{key} has no transition to {status} status")
+        })?;
+    request(&client, config, Method::POST, &path)
+        .json(&json!({ "transition": { "id": transition.id } }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_issue_to_todo() -> Result<()> {
+        let config = JiraConfig {
+            base_url: "https://example.atlassian.net/".into(),
+            email: "me@example.com".into(),
+            api_token: "token".into(),
+            projects: Default::default(),
+        };
+        let issue: Issue = serde_json::from_value(json!({
+            "key": "OPS-42",
+            "fields": {
+                "summary": "Rotate certificates",
+                "duedate": "2024-05-01",
+                "status": { "statusCategory": { "key": "indeterminate" } }
+            }
+        }))?;
+        let todo = issue.to_todo(&config, "ops");
+        assert_eq!(
+            todo.to_string(),
+            "OPS-42 Rotate certificates +ops (due 2024-05-01)"
+        );
+        assert!(!todo.done);
+        assert_eq!(issue_key(&config, &todo), Some("OPS-42"));
+        assert_eq!(issue.to_todo(&config, "ops").id, todo.id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_keeps_local_fields() {
+        let config = JiraConfig {
+            base_url: "https://example.atlassian.net".into(),
+            email: "me@example.com".into(),
+            api_token: "token".into(),
+            projects: Default::default(),
+        };
+        let issue = |summary: &str, status: &str| -> Issue {
+            serde_json::from_value(json!({
+                "key": "OPS-42",
+                "fields": {
+                    "summary": summary,
+                    "duedate": null,
+                    "status": { "statusCategory": { "key": status } }
+                }
+            }))
+            .unwrap()
+        };
+        let mut todos = Vec::new();
+        merge(
+            &mut todos,
+            vec![issue("Rotate", "new").to_todo(&config, "ops")],
+        );
+        todos[0].notes = Some("ask Sam first".into());
+        todos[0].starred = true;
+        todos[0].done = true;
+        let updated = todos[0].updated;
+
+        merge(
+            &mut todos,
+            vec![issue("Rotate", "new").to_todo(&config, "ops")],
+        );
+        assert_eq!(todos[0].updated, updated);
+        assert!(todos[0].done);
+
+        merge(
+            &mut todos,
+            vec![issue("Rotate certs", "done").to_todo(&config, "ops")],
+        );
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].title, "OPS-42 Rotate certs");
+        assert_eq!(todos[0].notes.as_deref(), Some("ask Sam first"));
+        assert!(todos[0].starred);
+    }
+}
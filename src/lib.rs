@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+pub use doit_core::{action, backup, crdt, intern, lock, migrate, todo};
+
+pub mod agenda;
+pub mod app;
+pub mod archive;
+pub mod bench;
+pub mod bidi;
+pub mod bulk_edit;
+pub mod capture;
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod count;
+pub mod daemon;
+#[cfg(target_os = "linux")]
+pub mod dbus;
+pub mod digest;
+pub mod duplicates;
+pub mod export;
+pub mod glyphs;
+pub mod google_tasks;
+pub mod highlight;
+pub mod hyperlinks;
+pub mod i18n;
+pub mod images;
+pub mod import;
+pub mod ipc;
+pub mod jira;
+pub mod links;
+pub mod markdown;
+pub mod matrix;
+pub mod mode;
+pub mod mqtt;
+pub mod notion;
+pub mod palette;
+pub mod qr;
+pub mod recovery;
+pub mod setup;
+pub mod stats;
+pub mod status;
+pub mod streaks;
+pub mod sync;
+pub mod table;
+pub mod tags;
+pub mod template;
+pub mod text;
+pub mod themes;
+pub mod tui;
+pub mod undo;
+pub mod urgency;
+pub mod utils;
+pub mod vault;
@@ -0,0 +1,35 @@
+/// Punctuation that often wraps a link in prose without being part of it.
+pub const WRAPPERS: [char; 4] = ['(', '<', '"', '\''];
+pub const TRAILERS: [char; 10] = [')', '>', '"', '\'', '.', ',', ';', ':', '!', '?'];
+
+/// The web link in `word`, without any punctuation around it.
+pub fn url(word: &str) -> Option<&str> {
+    let word = word.trim_start_matches(WRAPPERS).trim_end_matches(TRAILERS);
+    let rest = word
+        .strip_prefix("https://")
+        .or_else(|| word.strip_prefix("http://"))?;
+    (!rest.is_empty()).then_some(word)
+}
+
+/// Every web link in `text`, in order.
+pub fn find(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace().filter_map(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_find_links_in_prose() {
+        let text = "see (https://example.com/a?b=1), or <http://x.org>. not https:// or ftp://y";
+        assert_eq!(
+            find(text).collect::<Vec<_>>(),
+            vec!["https://example.com/a?b=1", "http://x.org"]
+        );
+        assert_eq!(url("https://example.com."), Some("https://example.com"));
+        assert_eq!(url("example.com"), None);
+    }
+}
@@ -2,39 +2,343 @@
 #![allow(unused_imports)]
 #![allow(unused_variables)]
 
-pub mod action;
-pub mod app;
-pub mod cli;
-pub mod components;
-pub mod config;
-pub mod mode;
-pub mod tui;
-pub mod utils;
-
 use clap::Parser;
-use cli::Cli;
 use color_eyre::eyre::Result;
 use env_logger::Env;
 use log::{debug, error, info, log_enabled, trace, Level};
-use utils::initialize_logging;
-
-use crate::{
+use rust_todos::{
+    agenda,
     app::App,
-    utils::{initialize_panic_handler, version},
+    backup, bench, capture,
+    cli::{Cli, Command, ExportFormat, ImportSource},
+    config::{self, Config},
+    count, daemon, digest, export, google_tasks, i18n, import, ipc, jira, lock, notion, qr,
+    recovery, setup, status, sync, todo,
+    utils::{self, initialize_logging, initialize_panic_handler, version},
 };
 
+/// How many frames `--bench-render` draws for each list size.
+const BENCH_FRAMES: u32 = 20;
+
 async fn tokio_main() -> Result<()> {
     trace!("Program started");
     initialize_panic_handler()?;
     initialize_logging()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    if args.bench_render {
+        print!("{}", bench::report(BENCH_FRAMES)?);
+        return Ok(());
+    }
+    let config_dir = utils::get_config_dir();
+    if args.command.is_none() && setup::needed(&config_dir, &todo::data_file()) {
+        setup::run(&config_dir, &todo::data_file())?;
+    }
+    let problems = config::validate::check(&config_dir)?;
+    if !problems.is_empty() {
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        color_eyre::eyre::bail!("The config file has problems:\n{}", problems.join("\n"));
+    }
+    let local = if args.global {
+        None
+    } else {
+        todo::find_local(&std::env::current_dir()?)
+    };
+    let config = Config::new()?;
+    if let Some(data_file) = config.data_file.clone() {
+        todo::set_global_file(data_file);
+    }
+    if let Some(local) = local {
+        // Only the todos themselves are kept in the project, the rest with the global ones
+        if let Some(dir) = todo::state_path(&local).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        todo::set_data_file(local);
+    }
+    // Held until the session ends, and taken before anything is restored, so a second one cannot
+    // write the data file underneath this one
+    let _lock = if matches!(args.command, None | Some(Command::Tutorial)) {
+        let Some(lock) = lock::try_acquire(&todo::data_file())? else {
+            color_eyre::eyre::bail!(
+                "{} is already open in another session; quit that one first, or add todos to it with `add --running`",
+                todo::data_file().display()
+            );
+        };
+        Some(lock)
+    } else {
+        None
+    };
+    if args.command.is_none() {
+        let (mut input, mut output) = (std::io::stdin().lock(), std::io::stdout());
+        backup::recover(&mut input, &mut output, &todo::data_file())?;
+        recovery::offer(&mut input, &mut output, &todo::data_file())?;
+    }
+    let language = i18n::language(config.language.as_deref(), |name| std::env::var(name).ok());
+    i18n::init(language.as_deref());
+    match args.command {
+        Some(Command::Daemon) => daemon::run(&config.daemon).await,
+        Some(Command::Add { running, ref text }) => add(running, &text.join(" ")).await,
+        Some(Command::Capture) => capture::run().await,
+        Some(Command::Count { due, ref format }) => count(due, format),
+        Some(Command::StatusLine { tmux }) => status_line(&config, tmux),
+        Some(Command::Status { waybar }) => status(&config, waybar),
+        Some(Command::Digest { email }) => digest(&config, email).await,
+        Some(Command::Sync {
+            google,
+            jira,
+            notion,
+        }) => sync(&config, google, jira, notion).await,
+        Some(Command::Agenda {
+            days,
+            print,
+            ref pdf,
+        }) => agenda(days, print, pdf.as_deref()),
+        Some(Command::Qr { ref project, index }) => qr(project.as_deref(), index),
+        Some(Command::Import { ref source }) => import(source),
+        Some(Command::Export { ref format }) => export(format),
+        Some(Command::View { ref file }) => view(&args, file.as_deref()).await,
+        Some(Command::Tutorial) => run_tui(&args, true).await,
+        None => run_tui(&args, false).await,
+    }
+}
+
+/// Adds through the running session when there is one, so it shows up there straight away, and
+/// to the data file otherwise unless `running` insists on a session.
+async fn add(running: bool, raw: &str) -> Result<()> {
+    let sent = ipc::send(&ipc::Request::Add(raw.to_string())).await;
+    if running || sent.is_ok() {
+        return sent;
+    }
+    todo::update(&todo::data_file(), |todos| {
+        todos.push(todo::TodoItem::parse(raw))
+    })
+}
+
+async fn digest(config: &Config, email: bool) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let today = chrono::Local::now().date_naive();
+    let digest = digest::Digest::new(&todos, today);
+    if !email {
+        print!("{}", digest.to_text());
+        return Ok(());
+    }
+    let Some(smtp) = &config.smtp else {
+        color_eyre::eyre::bail!("`digest --email` needs an `smtp` section in the config file");
+    };
+    digest::send(smtp, &digest, today).await
+}
+
+async fn sync(config: &Config, google: bool, jira: bool, notion: bool) -> Result<()> {
+    if google {
+        return sync_google_tasks(config).await;
+    }
+    if jira {
+        return sync_jira(config).await;
+    }
+    if notion {
+        return sync_notion(config).await;
+    }
+    let Some(sync_config) = &config.sync else {
+        color_eyre::eyre::bail!("`sync` needs a `sync` section in the config file");
+    };
+    let key_file = sync_config
+        .key_file
+        .clone()
+        .unwrap_or_else(|| config.config._config_dir.join("sync.key"));
+    let key = sync::load_or_create_key(&key_file)?;
+    let remote = sync::DirRemote(sync_config.remote.clone());
+    let merged = sync::sync(&remote, &key, &todo::data_file())?;
+    println!("Merged {merged} remote document(s)");
+    Ok(())
+}
+
+async fn sync_google_tasks(config: &Config) -> Result<()> {
+    let Some(google) = &config.google_tasks else {
+        color_eyre::eyre::bail!(
+            "`sync --google` needs a `google_tasks` section in the config file"
+        );
+    };
+    let token_path = google_tasks::token_path(config);
+    if !token_path.exists() {
+        google_tasks::authorize(google, &token_path).await?;
+    }
+    let path = todo::data_file();
+    let state_path = google_tasks::state_path();
+    let pulled = google_tasks::sync(google, &token_path, &state_path, &todo::load(&path)?).await?;
+    let (count, deleted) = (pulled.upserts.len(), pulled.deleted.len());
+    todo::update(&path, |todos| {
+        todo::upsert(todos, pulled.upserts);
+        todos.retain(|todo| !pulled.deleted.contains(&todo.id));
+    })?;
+    println!("Pulled {count} change(s) and {deleted} deletion(s) from Google Tasks");
+    Ok(())
+}
+
+async fn sync_jira(config: &Config) -> Result<()> {
+    let Some(jira_config) = &config.jira else {
+        color_eyre::eyre::bail!("`sync --jira` needs a `jira` section in the config file");
+    };
+    let issues = jira::fetch(jira_config).await?;
+    let count = issues.len();
+    todo::update(&todo::data_file(), |todos| jira::merge(todos, issues))?;
+    println!("Fetched {count} issue(s) from Jira");
+    Ok(())
+}
+
+async fn sync_notion(config: &Config) -> Result<()> {
+    let Some(notion_config) = &config.notion else {
+        color_eyre::eyre::bail!("`sync --notion` needs a `notion` section in the config file");
+    };
+    let path = todo::data_file();
+    let pulled = notion::sync(notion_config, &notion::state_path(), &todo::load(&path)?).await?;
+    let (count, deleted) = (pulled.upserts.len(), pulled.deleted.len());
+    todo::update(&path, |todos| {
+        todo::upsert(todos, pulled.upserts);
+        todos.retain(|todo| !pulled.deleted.contains(&todo.id));
+    })?;
+    println!("Pulled {count} change(s) and {deleted} deletion(s) from Notion");
+    for failed in &pulled.failed {
+        eprintln!("Could not push {failed}");
+    }
+    Ok(())
+}
+
+fn count(due: Option<count::Due>, format: &str) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let n = count::count(&todos, due, chrono::Local::now().date_naive());
+    println!("{}", count::render(format, n));
+    Ok(())
+}
+
+fn status_line(config: &Config, tmux: bool) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let counts = status::counts(&todos, chrono::Local::now().date_naive());
+    let escapes = if tmux {
+        status::Escapes::Tmux
+    } else {
+        status::Escapes::Ansi
+    };
+    let format = &config.status_line.format;
+    println!("{}", status::render(format, &counts, escapes));
+    Ok(())
+}
+
+fn status(config: &Config, waybar: bool) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let today = chrono::Local::now().date_naive();
+    let format = &config.status_line.format;
+    if waybar {
+        let module = status::waybar(&todos, format, today);
+        println!("{}", serde_json::to_string(&module)?);
+    } else {
+        let counts = status::counts(&todos, today);
+        println!(
+            "{}",
+            status::render(format, &counts, status::Escapes::Plain)
+        );
+    }
+    Ok(())
+}
+
+fn agenda(days: u64, print: bool, pdf: Option<&std::path::Path>) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let today = chrono::Local::now().date_naive();
+    let lines = agenda::lines(&todos, today, days, agenda::WIDTH);
+    let pages = agenda::paginate(&lines);
+    if print {
+        print!("{}", agenda::to_text(&pages));
+    } else {
+        println!("{}", lines.join("\n"));
+    }
+    if let Some(pdf) = pdf {
+        std::fs::write(pdf, agenda::to_pdf(&pages))?;
+    }
+    Ok(())
+}
+
+fn qr(project: Option<&str>, index: Option<usize>) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let text = match (project, index) {
+        (_, Some(index)) => {
+            let Some(todo) = todos.get(index) else {
+                color_eyre::eyre::bail!("There is no todo {index}");
+            };
+            qr::checklist(None, &[todo])
+        }
+        (Some(project), None) => {
+            let open: Vec<&todo::TodoItem> = todos
+                .iter()
+                .filter(|todo| !todo.done && todo.project.as_deref() == Some(project))
+                .collect();
+            if open.is_empty() {
+                color_eyre::eyre::bail!("Project {project} has no open todos");
+            }
+            qr::checklist(Some(project), &open)
+        }
+        (None, None) => color_eyre::eyre::bail!("Pass a todo index or --project to share"),
+    };
+    println!("{}", qr::render(&text)?);
+    Ok(())
+}
+
+fn import(source: &ImportSource) -> Result<()> {
+    let todos = match source {
+        ImportSource::Reminders { file } => {
+            import::reminders::parse(&std::fs::read_to_string(file)?)?
+        }
+        ImportSource::Org { file } => import::org::parse(&std::fs::read_to_string(file)?),
+        ImportSource::Trello { file } => import::trello::parse(&std::fs::read_to_string(file)?)?,
+    };
+    let count = import::append(&todo::data_file(), todos)?;
+    println!("Imported {count} todo(s)");
+    Ok(())
+}
+
+fn export(format: &ExportFormat) -> Result<()> {
+    let todos = todo::load(&todo::data_file())?;
+    let (rendered, file) = match format {
+        ExportFormat::Org { file } => (export::org::render(&todos), file),
+        ExportFormat::Html { file } => {
+            let today = chrono::Local::now().date_naive();
+            (export::html::render(&todos, today), file)
+        }
+    };
+    match file {
+        Some(file) => std::fs::write(file, rendered)?,
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+async fn run_tui(args: &Cli, tutorial: bool) -> Result<()> {
+    let mut app = App::new(args.tick_rate, args.frame_rate, tutorial)?;
     app.run().await?;
 
+    let exit_summary = &app.config.exit_summary;
+    if args.summary || exit_summary.enabled {
+        let due_today_only = args.due_today || exit_summary.due_today_only;
+        let todos = todo::load(&todo::data_file())?;
+        print!("{}", todo::summary(&todos, due_today_only));
+    }
+
     Ok(())
 }
 
+/// Opens `file` in the TUI for browsing only. Org exports are read as such, anything else as a
+/// data file or a backup of one.
+async fn view(args: &Cli, file: Option<&std::path::Path>) -> Result<()> {
+    let file = file.map_or_else(todo::data_file, std::path::Path::to_path_buf);
+    if !file.exists() {
+        color_eyre::eyre::bail!("{} does not exist", file.display());
+    }
+    let todos = match file.extension().and_then(|extension| extension.to_str()) {
+        Some("org") => import::org::parse(&std::fs::read_to_string(&file)?),
+        _ => todo::load(&file)?,
+    };
+    let mut app = App::viewer(args.tick_rate, args.frame_rate, todos)?;
+    app.run().await
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     if let Err(e) = tokio_main().await {
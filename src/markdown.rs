@@ -0,0 +1,258 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::prelude::*;
+
+use crate::highlight;
+
+/// How the notes of a todo are laid out: `words` styles plain text, `link` the text of links and
+/// `code` code spans and blocks, apart from fenced blocks in a language, which are highlighted
+/// with the syntect theme `code_theme`.
+pub struct Styles<'a> {
+    pub words: &'a dyn Fn(&str, Style) -> Vec<Span<'static>>,
+    pub link: Style,
+    pub code: Style,
+    pub code_theme: &'a str,
+}
+
+/// Lays out `markdown` as styled lines: bold, italic and struck through text, code spans, links,
+/// headings, lists with their bullets or numbers, quotes and fenced code blocks.
+pub fn lines(markdown: &str, styles: &Styles) -> Vec<Line<'static>> {
+    let mut writer = Writer {
+        styles,
+        lines: Vec::new(),
+        spans: Vec::new(),
+        stack: vec![Style::default()],
+        lists: Vec::new(),
+        quotes: 0,
+        code_block: None,
+        code: String::new(),
+    };
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(markdown, options) {
+        writer.event(event);
+    }
+    writer.flush();
+    writer.lines
+}
+
+struct Writer<'a, 'b> {
+    styles: &'a Styles<'b>,
+    lines: Vec<Line<'static>>,
+    /// The line being written.
+    spans: Vec<Span<'static>>,
+    /// The style of text at this point, innermost last.
+    stack: Vec<Style>,
+    /// The next number of each list the text is in, or none for bulleted ones, innermost last.
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    /// The language of the code block the text is in, empty when not given.
+    code_block: Option<String>,
+    /// The code of the block so far.
+    code: String,
+}
+
+impl Writer<'_, '_> {
+    fn style(&self) -> Style {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    fn push(&mut self, style: Style) {
+        self.stack.push(self.style().patch(style));
+    }
+
+    fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Ends the line being written, if anything is on it.
+    fn flush(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+    }
+
+    /// Starts a line, with the bars of the quotes it is in.
+    fn start_line(&mut self) {
+        self.flush();
+        if self.quotes > 0 {
+            self.spans.push(Span::raw("│ ".repeat(self.quotes)));
+        }
+    }
+
+    /// Leaves an empty line between blocks, but not before the first or inside lists.
+    fn gap(&mut self) {
+        self.flush();
+        if !self.lines.is_empty() && self.lists.is_empty() {
+            self.lines.push(Line::raw(""));
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block.is_some() => self.code.push_str(&text),
+            Event::Text(text) => {
+                let spans = (self.styles.words)(&text, self.style());
+                self.spans.extend(spans);
+            }
+            Event::Code(code) => {
+                let style = self.style().patch(self.styles.code);
+                self.spans.push(Span::styled(code.to_string(), style));
+            }
+            Event::SoftBreak | Event::HardBreak => self.start_line(),
+            Event::Rule => {
+                self.gap();
+                self.lines.push(Line::raw("───"));
+            }
+            Event::TaskListMarker(done) => {
+                let check = if done { "[x] " } else { "[ ] " };
+                self.spans.push(Span::raw(check));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                self.spans
+                    .push(Span::styled(html.to_string(), self.style()));
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes out the code block that just ended, highlighted if its language is known.
+    fn code_block(&mut self) {
+        let language = self.code_block.take().unwrap_or_default();
+        let code = std::mem::take(&mut self.code);
+        let highlighted = Some(language.as_str())
+            .filter(|language| !language.is_empty())
+            .and_then(|language| highlight::lines(&code, language, self.styles.code_theme));
+        let lines = highlighted.unwrap_or_else(|| {
+            code.lines()
+                .map(|line| Line::styled(line.to_string(), self.styles.code))
+                .collect()
+        });
+        for line in lines {
+            self.start_line();
+            self.spans.extend(line.spans);
+            self.flush();
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                if self.lists.is_empty() {
+                    self.gap();
+                }
+                if self.spans.is_empty() {
+                    self.start_line();
+                }
+            }
+            Tag::Heading { level, .. } => {
+                self.gap();
+                self.start_line();
+                let marks = match level {
+                    HeadingLevel::H1 => Modifier::BOLD | Modifier::UNDERLINED,
+                    _ => Modifier::BOLD,
+                };
+                self.push(Style::new().add_modifier(marks));
+            }
+            Tag::BlockQuote(_) => {
+                self.gap();
+                self.quotes += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.gap();
+                self.code_block = Some(match kind {
+                    CodeBlockKind::Fenced(language) => language.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                });
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.gap();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.start_line();
+                let depth = self.lists.len().saturating_sub(1);
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.spans
+                    .push(Span::raw(format!("{}{bullet}", "  ".repeat(depth))));
+            }
+            Tag::Emphasis => self.push(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.push(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { .. } => self.push(self.styles.link),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush(),
+            TagEnd::Heading(_) => {
+                self.pop();
+                self.flush();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quotes -= 1;
+            }
+            TagEnd::CodeBlock => self.code_block(),
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            TagEnd::Item => self.flush(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => self.pop(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let words = |text: &str, style: Style| vec![Span::styled(text.to_string(), style)];
+        let styles = Styles {
+            words: &words,
+            link: Style::new().fg(Color::Blue),
+            code: Style::new().fg(Color::Cyan),
+            code_theme: "base16-ocean.dark",
+        };
+        let lines = lines(
+            "Call **the bank** about `IBAN`:\n\n1. ask for [fees](https://example.com)\n2. close it",
+            &styles,
+        );
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        assert_eq!(
+            lines,
+            vec![
+                Line::from(vec![
+                    Span::raw("Call "),
+                    Span::styled("the bank", bold),
+                    Span::raw(" about "),
+                    Span::styled("IBAN", Style::new().fg(Color::Cyan)),
+                    Span::raw(":"),
+                ]),
+                Line::raw(""),
+                Line::from(vec![
+                    Span::raw("1. "),
+                    Span::raw("ask for "),
+                    Span::styled("fees", Style::new().fg(Color::Blue)),
+                ]),
+                Line::from(vec![Span::raw("2. "), Span::raw("close it")]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{Days, NaiveDate};
+
+use crate::todo::{EventKind, TodoItem};
+
+/// How many days ahead a due date makes a todo urgent.
+const URGENT_WITHIN: u64 = 2;
+
+/// The four quadrants of the Eisenhower matrix, in reading order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quadrant {
+    /// Urgent and important
+    DoFirst,
+    /// Important but not urgent
+    Schedule,
+    /// Urgent but not important
+    Delegate,
+    /// Neither
+    Drop,
+}
+
+pub const QUADRANTS: [Quadrant; 4] = [
+    Quadrant::DoFirst,
+    Quadrant::Schedule,
+    Quadrant::Delegate,
+    Quadrant::Drop,
+];
+
+impl std::fmt::Display for Quadrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quadrant::DoFirst => write!(f, "Do first"),
+            Quadrant::Schedule => write!(f, "Schedule"),
+            Quadrant::Delegate => write!(f, "Delegate"),
+            Quadrant::Drop => write!(f, "Drop"),
+        }
+    }
+}
+
+impl Quadrant {
+    fn important(self) -> bool {
+        matches!(self, Quadrant::DoFirst | Quadrant::Schedule)
+    }
+
+    fn urgent(self) -> bool {
+        matches!(self, Quadrant::DoFirst | Quadrant::Delegate)
+    }
+}
+
+/// The last day a todo due then is urgent on `today`.
+fn horizon(today: NaiveDate) -> NaiveDate {
+    today
+        .checked_add_days(Days::new(URGENT_WITHIN))
+        .unwrap_or(today)
+}
+
+/// Flagged todos are important, and those due within `URGENT_WITHIN` days are urgent.
+pub fn quadrant(todo: &TodoItem, today: NaiveDate) -> Quadrant {
+    match (todo.flagged, todo.is_due_on(horizon(today))) {
+        (true, true) => Quadrant::DoFirst,
+        (true, false) => Quadrant::Schedule,
+        (false, true) => Quadrant::Delegate,
+        (false, false) => Quadrant::Drop,
+    }
+}
+
+/// Changes a todo's flag and due date so it lands in `to`. Becoming urgent makes it due today,
+/// and stopping being urgent puts the due date off to the day after it would be urgent, keeping
+/// it scheduled. A due date moved is logged in the todo's history.
+pub fn move_to(todo: &mut TodoItem, to: Quadrant, today: NaiveDate) {
+    let from = quadrant(todo, today);
+    todo.flagged = to.important();
+    if to.urgent() != from.urgent() {
+        todo.due = if to.urgent() {
+            Some(today)
+        } else {
+            horizon(today).succ_opt()
+        };
+        todo.log(EventKind::Rescheduled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_place_and_move() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut todo = TodoItem::parse("file taxes due:2024-05-02");
+        assert_eq!(quadrant(&todo, today), Quadrant::Delegate);
+
+        move_to(&mut todo, Quadrant::DoFirst, today);
+        assert!(todo.flagged);
+        assert_eq!(todo.due, NaiveDate::from_ymd_opt(2024, 5, 2));
+        move_to(&mut todo, Quadrant::Schedule, today);
+        assert_eq!(todo.due, NaiveDate::from_ymd_opt(2024, 5, 4));
+        assert_eq!(quadrant(&todo, today), Quadrant::Schedule);
+        move_to(&mut todo, Quadrant::Delegate, today);
+        assert_eq!((todo.flagged, todo.due), (false, Some(today)));
+        assert_eq!(quadrant(&todo, today), Quadrant::Delegate);
+        let moved = todo
+            .history
+            .iter()
+            .filter(|event| event.kind == EventKind::Rescheduled);
+        assert_eq!(moved.count(), 2);
+    }
+}
@@ -1,8 +1,24 @@
 use serde::{Deserialize, Serialize};
+use strum::EnumVariantNames;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(
+    Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, EnumVariantNames,
+)]
+/// A section of the keybindings and styles. `Home` holds those for every mode of the list, and
+/// the rest those for the mode of the list they are named after, which win over `Home`'s. While
+/// typing, in `Editing`, `Home` bindings that start with a plain character are left out.
 pub enum Mode {
     #[default]
     Home,
     Edit,
+    Normal,
+    Editing,
+    Browse,
+    Help,
+    Tags,
+    Projects,
+    History,
+    Stats,
+    Matrix,
+    Themes,
 }
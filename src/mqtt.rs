@@ -0,0 +1,115 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::Local;
+use color_eyre::eyre::Result;
+use log::warn;
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use serde_json::json;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::{config::MqttConfig, todo::TodoItem};
+
+/// A lifecycle change detected between two snapshots of the list.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    Added(usize, &'a TodoItem),
+    Completed(usize, &'a TodoItem),
+}
+
+impl Event<'_> {
+    fn payload(&self) -> serde_json::Value {
+        let (name, index, todo) = match self {
+            Event::Added(i, todo) => ("added", i, todo),
+            Event::Completed(i, todo) => ("completed", i, todo),
+        };
+        json!({ "event": name, "index": index, "todo": todo })
+    }
+}
+
+/// Compares two snapshots by todo id, so that reordering or deleting todos isn't taken for adding
+/// or completing them. Indexes are where the todos are in `new`.
+pub fn diff<'a>(old: &[TodoItem], new: &'a [TodoItem]) -> Vec<Event<'a>> {
+    let done: HashMap<Uuid, bool> = old.iter().map(|todo| (todo.id, todo.done)).collect();
+    new.iter()
+        .enumerate()
+        .filter_map(|(i, todo)| match done.get(&todo.id) {
+            None => Some(Event::Added(i, todo)),
+            Some(false) if todo.done => Some(Event::Completed(i, todo)),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Publishes an event for every change to the list, plus retained `open` and `due_today` counts
+/// that automations can watch for reaching zero.
+pub async fn publish(config: MqttConfig, mut todos: watch::Receiver<Vec<TodoItem>>) -> Result<()> {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    let (client, mut eventloop) = AsyncClient::new(options, 16);
+
+    // The event loop drives the connection and has to be polled for anything to be sent
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = eventloop.poll().await {
+                warn!("MQTT connection error: {e:?}");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    });
+
+    let topic = |name: &str| format!("{}/{name}", config.topic);
+    let mut previous: Option<Vec<TodoItem>> = None;
+    while todos.changed().await.is_ok() {
+        let current = todos.borrow_and_update().clone();
+
+        // The first snapshot is the list loaded from disk, not a change made by the user
+        if let Some(previous) = &previous {
+            for event in diff(previous, &current) {
+                let payload = event.payload().to_string();
+                client
+                    .publish(topic("events"), QoS::AtLeastOnce, false, payload)
+                    .await?;
+            }
+        }
+
+        let today = Local::now().date_naive();
+        let open = current.iter().filter(|todo| !todo.done).count();
+        let due_today = current.iter().filter(|todo| todo.is_due_on(today)).count();
+        client
+            .publish(topic("open"), QoS::AtLeastOnce, true, open.to_string())
+            .await?;
+        client
+            .publish(topic("due_today"), QoS::AtLeastOnce, true, due_today.to_string())
+            .await?;
+
+        previous = Some(current);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_detects_added_and_completed() {
+        let old = vec![TodoItem::new("a".into()), TodoItem::new("b".into())];
+        let mut new = old.clone();
+        new[1].done = true;
+        new.push(TodoItem::new("c".into()));
+        assert_eq!(
+            diff(&old, &new),
+            vec![Event::Completed(1, &new[1]), Event::Added(2, &new[2])]
+        );
+
+        // Deleting the first todo and moving the rest around changes nothing about them
+        let moved = vec![new[2].clone(), new[1].clone()];
+        assert_eq!(diff(&new, &moved), vec![]);
+    }
+}
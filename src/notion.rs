@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::eyre::Result;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    config::NotionConfig,
+    todo::{self, TodoItem},
+    utils::{read_json, write_json},
+};
+
+const API_URL: &str = "https://api.notion.com/v1";
+const API_VERSION: &str = "2022-06-28";
+
+pub fn state_path() -> PathBuf {
+    todo::state_file().with_file_name("notion.json")
+}
+
+/// Which page each todo is linked to, and when the last sync started.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    pub last_sync: Option<DateTime<Utc>>,
+    pub links: BTreeMap<Uuid, String>,
+}
+
+/// What a sync brought in from Notion, for the local list.
+#[derive(Debug, Default, PartialEq)]
+pub struct Pulled {
+    /// Todos changed or added there, to upsert.
+    pub upserts: Vec<TodoItem>,
+    /// Todos whose page was archived there, to remove.
+    pub deleted: Vec<Uuid>,
+    /// Todos that could not be pushed, and why, to report.
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    id: String,
+    last_edited_time: DateTime<Utc>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    properties: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct QueryPage {
+    #[serde(default)]
+    results: Vec<Page>,
+    next_cursor: Option<String>,
+}
+
+impl Page {
+    fn apply_to(&self, config: &NotionConfig, todo: &mut TodoItem) {
+        let property = |name: &str| self.properties.get(name).unwrap_or(&Value::Null);
+        todo.title = property(&config.title_property)["title"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| part["plain_text"].as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        todo.done = property(&config.done_property)["checkbox"]
+            .as_bool()
+            .unwrap_or_default();
+        // Dates may carry a time, which todos do not keep
+        todo.due = property(&config.due_property)["date"]["start"]
+            .as_str()
+            .and_then(|start| NaiveDate::parse_from_str(start.get(..10)?, "%Y-%m-%d").ok());
+        todo.updated = self.last_edited_time;
+    }
+}
+
+fn page_properties(config: &NotionConfig, todo: &TodoItem) -> Value {
+    let due = todo.due.map(|due| json!({ "start": due.to_string() }));
+    json!({
+        config.title_property.as_str(): { "title": [{ "text": { "content": todo.title } }] },
+        config.done_property.as_str(): { "checkbox": todo.done },
+        config.due_property.as_str(): { "date": due },
+    })
+}
+
+fn request(client: &Client, config: &NotionConfig, method: Method, path: &str) -> RequestBuilder {
+    client
+        .request(method, format!("{API_URL}/{path}"))
+        .bearer_auth(&config.token)
+        .header("Notion-Version", API_VERSION)
+}
+
+async fn query_pages(
+    client: &Client,
+    config: &NotionConfig,
+    edited_after: Option<DateTime<Utc>>,
+) -> Result<Vec<Page>> {
+    let mut pages = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut body = json!({ "page_size": 100 });
+        if let Some(edited_after) = edited_after {
+            body["filter"] = json!({
+                "timestamp": "last_edited_time",
+                "last_edited_time": { "on_or_after": edited_after.to_rfc3339() },
+            });
+        }
+        if let Some(cursor) = &cursor {
+            body["start_cursor"] = json!(cursor);
+        }
+        let path = format!("databases/{}/query", config.database_id);
+        let page: QueryPage = request(client, config, Method::POST, &path)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        pages.extend(page.results);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(pages),
+        }
+    }
+}
+
+/// Applies `pages` changed remotely to the todos linked to them in `state`, linking new ones.
+/// Archived pages take their todos with them, and are unlinked.
+fn pull(
+    pages: Vec<Page>,
+    config: &NotionConfig,
+    state: &mut SyncState,
+    local: &[TodoItem],
+) -> Pulled {
+    let local_by_id: HashMap<Uuid, &TodoItem> = local.iter().map(|todo| (todo.id, todo)).collect();
+    let todo_by_page: HashMap<String, Uuid> = state
+        .links
+        .iter()
+        .map(|(id, page)| (page.clone(), *id))
+        .collect();
+
+    let mut pulled = Pulled::default();
+    for page in pages {
+        match todo_by_page.get(&page.id) {
+            Some(id) if page.archived => {
+                state.links.remove(id);
+                pulled.deleted.push(*id);
+            }
+            Some(id) => {
+                if let Some(todo) = local_by_id
+                    .get(id)
+                    .filter(|t| page.last_edited_time > t.updated)
+                {
+                    let mut todo = (*todo).clone();
+                    page.apply_to(config, &mut todo);
+                    pulled.upserts.push(todo);
+                }
+            }
+            None if page.archived => {}
+            None => {
+                let mut todo = TodoItem::new(String::new());
+                page.apply_to(config, &mut todo);
+                state.links.insert(todo.id, page.id.clone());
+                pulled.upserts.push(todo);
+            }
+        }
+    }
+    pulled
+}
+
+/// Updates the page linked to `todo`, or creates and links one.
+async fn push(
+    client: &Client,
+    config: &NotionConfig,
+    state: &mut SyncState,
+    todo: &TodoItem,
+) -> Result<()> {
+    let properties = page_properties(config, todo);
+    match state.links.get(&todo.id) {
+        Some(page_id) => {
+            request(client, config, Method::PATCH, &format!("pages/{page_id}"))
+                .json(&json!({ "properties": properties }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        None => {
+            let page: Page = request(client, config, Method::POST, "pages")
+                .json(&json!({
+                    "parent": { "database_id": config.database_id },
+                    "properties": properties,
+                }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            state.links.insert(todo.id, page.id);
+        }
+    }
+    Ok(())
+}
+
+/// Exchanges changes made since the last sync with the database in both directions.
+///
+/// Works like the Google Tasks sync: remote changes are returned to apply to the local list, and
+/// where both sides changed a todo, the more recently updated one wins. A todo whose page was
+/// archived is deleted too. A todo that fails to push doesn't stop the others, and the links made
+/// so far are kept either way; the sync then doesn't move past it, so it is tried again next time.
+pub async fn sync(config: &NotionConfig, state_path: &Path, local: &[TodoItem]) -> Result<Pulled> {
+    let client = Client::new();
+    let mut state: SyncState = read_json(state_path)?;
+    let started = Utc::now();
+
+    let pages = query_pages(&client, config, state.last_sync).await?;
+    let mut pulled = pull(pages, config, &mut state, local);
+    let done: HashSet<Uuid> = pulled
+        .upserts
+        .iter()
+        .map(|todo| todo.id)
+        .chain(pulled.deleted.iter().copied())
+        .collect();
+
+    let last_sync = state.last_sync;
+    let changed = local.iter().filter(|todo| {
+        !done.contains(&todo.id) && last_sync.is_none_or(|last| todo.updated > last)
+    });
+    for todo in changed {
+        if let Err(err) = push(&client, config, &mut state, todo).await {
+            pulled.failed.push(format!("\"{}\": {err}", todo.title));
+        }
+    }
+
+    if pulled.failed.is_empty() {
+        state.last_sync = Some(started);
+    }
+    write_json(state_path, &state)?;
+    Ok(pulled)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_page_round_trip() -> Result<()> {
+        let config: NotionConfig = serde_json::from_value(json!({
+            "token": "secret",
+            "database_id": "db",
+        }))?;
+        let page: Page = serde_json::from_value(json!({
+            "id": "page",
+            "last_edited_time": "2024-05-01T10:00:00.000Z",
+            "properties": {
+                "Name": { "title": [{ "plain_text": "Book " }, { "plain_text": "flights" }] },
+                "Done": { "checkbox": true },
+                "Due": { "date": { "start": "2024-06-01" } }
+            }
+        }))?;
+        let mut todo = TodoItem::new(String::new());
+        page.apply_to(&config, &mut todo);
+        assert_eq!(todo.to_string(), "Book flights (due 2024-06-01)");
+        assert!(todo.done);
+        assert_eq!(
+            page_properties(&config, &todo),
+            json!({
+                "Name": { "title": [{ "text": { "content": "Book flights" } }] },
+                "Done": { "checkbox": true },
+                "Due": { "date": { "start": "2024-06-01" } }
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pull_archived_pages() -> Result<()> {
+        let config: NotionConfig = serde_json::from_value(json!({
+            "token": "secret",
+            "database_id": "db",
+        }))?;
+        let linked = TodoItem::new("Book flights".into());
+        let mut state = SyncState::default();
+        state.links.insert(linked.id, "page".into());
+        let pages: Vec<Page> = serde_json::from_value(json!([
+            { "id": "page", "last_edited_time": "2024-05-01T10:00:00.000Z", "archived": true },
+            { "id": "other", "last_edited_time": "2024-05-01T10:00:00.000Z", "archived": true }
+        ]))?;
+        let pulled = pull(pages, &config, &mut state, std::slice::from_ref(&linked));
+        assert_eq!(pulled.deleted, vec![linked.id]);
+        assert!(pulled.upserts.is_empty());
+        assert!(state.links.is_empty());
+        Ok(())
+    }
+}
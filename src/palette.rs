@@ -0,0 +1,169 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+/// How many colors the terminal can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Depth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// None at all, as asked for with `NO_COLOR` or on a dumb terminal.
+    Mono,
+}
+
+/// The ANSI colors in palette order.
+const ANSI: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Works out the color depth from `NO_COLOR`, `COLORTERM` and `TERM`, read through `var`.
+pub fn detect(var: impl Fn(&str) -> Option<String>) -> Depth {
+    if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return Depth::Mono;
+    }
+    if var("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit") {
+        return Depth::TrueColor;
+    }
+    match var("TERM").as_deref() {
+        Some("dumb") => Depth::Mono,
+        Some(term) if term.ends_with("-direct") => Depth::TrueColor,
+        Some(term) if term.contains("256color") => Depth::Ansi256,
+        _ => Depth::Ansi16,
+    }
+}
+
+/// The nearest of the 6x6x6 color cube in the 256-color palette.
+fn to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| ((u16::from(c) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// The ANSI black, gray or white closest to a shade of gray from 0 to 255.
+fn gray(lightness: u16) -> Color {
+    match lightness {
+        0..=40 => Color::Black,
+        41..=150 => Color::DarkGray,
+        151..=220 => Color::Gray,
+        _ => Color::White,
+    }
+}
+
+/// The nearest ANSI color to a 256-color palette entry.
+fn to_16(index: u8) -> Color {
+    match index {
+        0..=15 => ANSI[usize::from(index)],
+        16..=231 => {
+            let cube = index - 16;
+            let levels = [cube / 36, cube / 6 % 6, cube % 6];
+            let max = *levels.iter().max().unwrap_or(&0);
+            let min = *levels.iter().min().unwrap_or(&0);
+            if max - min <= 1 {
+                let value = |level: u8| [0, 95, 135, 175, 215, 255][usize::from(level)];
+                return gray((value(max) + value(min)) / 2);
+            }
+            let bits = levels
+                .iter()
+                .enumerate()
+                .filter(|(_, level)| **level * 2 > max)
+                .map(|(i, _)| 1 << i)
+                .sum::<usize>();
+            ANSI[bits + if max >= 4 { 8 } else { 0 }]
+        }
+        _ => gray(8 + 10 * u16::from(index - 232)),
+    }
+}
+
+/// `color` as the nearest the terminal can show, or the default for `Depth::Mono`.
+pub fn fit(color: Color, depth: Depth) -> Color {
+    match (depth, color) {
+        (_, Color::Reset) | (Depth::TrueColor, _) => color,
+        (Depth::Mono, _) => Color::Reset,
+        (Depth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(to_256(r, g, b)),
+        (Depth::Ansi16, Color::Rgb(r, g, b)) => to_16(to_256(r, g, b)),
+        (Depth::Ansi16, Color::Indexed(index)) => to_16(index),
+        _ => color,
+    }
+}
+
+/// Brings every cell of a drawn frame within `depth`. Without colors, cells that had a background
+/// are shown reversed instead, so the selection stays visible.
+pub fn degrade(buffer: &mut Buffer, depth: Depth) {
+    if depth == Depth::TrueColor {
+        return;
+    }
+    for cell in &mut buffer.content {
+        if depth == Depth::Mono && cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = fit(cell.fg, depth);
+        cell.bg = fit(cell.bg, depth);
+    }
+}
+
+/// Takes blinking off every cell of a drawn frame.
+pub fn stop_blinking(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        cell.modifier
+            .remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect_and_fit() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(detect(env(&[("COLORTERM", "truecolor")])), Depth::TrueColor);
+        assert_eq!(detect(env(&[("TERM", "xterm-256color")])), Depth::Ansi256);
+        assert_eq!(detect(env(&[("TERM", "xterm")])), Depth::Ansi16);
+        let no_color = &[("NO_COLOR", "1"), ("COLORTERM", "truecolor")];
+        assert_eq!(detect(env(no_color)), Depth::Mono);
+
+        assert_eq!(
+            fit(Color::Rgb(255, 0, 0), Depth::Ansi256),
+            Color::Indexed(196)
+        );
+        assert_eq!(fit(Color::Rgb(255, 0, 0), Depth::Ansi16), Color::LightRed);
+        assert_eq!(fit(Color::Indexed(214), Depth::Ansi16), Color::LightYellow);
+        assert_eq!(fit(Color::Indexed(237), Depth::Ansi16), Color::DarkGray);
+        assert_eq!(fit(Color::Indexed(250), Depth::Ansi16), Color::Gray);
+        assert_eq!(fit(Color::Yellow, Depth::Mono), Color::Reset);
+    }
+
+    #[test]
+    fn test_stop_blinking() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 2, 1));
+        let blinking = Modifier::BOLD | Modifier::RAPID_BLINK;
+        buffer.get_mut(0, 0).modifier = blinking;
+        stop_blinking(&mut buffer);
+        assert_eq!(buffer.get(0, 0).modifier, Modifier::BOLD);
+    }
+}
@@ -0,0 +1,49 @@
+use color_eyre::eyre::Result;
+use qrcode::{render::unicode::Dense1x2, QrCode};
+
+use crate::todo::TodoItem;
+
+/// The text encoded for sharing: a checklist that reads well in any phone's scanner app.
+pub fn checklist(heading: Option<&str>, todos: &[&TodoItem]) -> String {
+    let mut out = String::new();
+    if let Some(heading) = heading {
+        out.push_str(&format!("{heading}\n"));
+    }
+    for todo in todos {
+        let mark = if todo.done { "x" } else { " " };
+        out.push_str(&format!("- [{mark}] {todo}\n"));
+    }
+    out
+}
+
+/// Draws `text` as a QR code in half-height block characters, light on dark so that it scans
+/// from a terminal with a dark background.
+pub fn render(text: &str) -> Result<String> {
+    let code = QrCode::new(text.as_bytes())?;
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_checklist() -> Result<()> {
+        let mut done = TodoItem::parse("milk +shopping");
+        done.done = true;
+        let eggs = TodoItem::parse("eggs +shopping");
+        let text = checklist(Some("shopping"), &[&done, &eggs]);
+        assert_eq!(
+            text,
+            "shopping\n- [x] milk +shopping\n- [ ] eggs +shopping\n"
+        );
+        assert!(render(&text)?.lines().count() > 10);
+        Ok(())
+    }
+}
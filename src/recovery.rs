@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use color_eyre::eyre::Result;
+use tokio::sync::watch;
+
+use crate::{
+    crdt::Document,
+    todo::{self, TodoItem},
+};
+
+/// The todos the running session last reported, for saving should it panic.
+static LATEST: OnceLock<watch::Receiver<Vec<TodoItem>>> = OnceLock::new();
+
+/// Where the panic handler saves the todos of `data_file`, alongside it.
+pub fn path(data_file: &Path) -> PathBuf {
+    let mut path = todo::state_path(data_file).into_os_string();
+    path.push(".recovered");
+    PathBuf::from(path)
+}
+
+/// Follows the todos the session reports, so that `save` has the latest ones.
+pub fn follow(todos: watch::Receiver<Vec<TodoItem>>) {
+    let _ = LATEST.set(todos);
+}
+
+/// Writes the latest todos of the data file, leaving out those of shared files, to the recovery
+/// file. Nothing is written before the session has reported any. Called from the panic handler,
+/// so problems are only logged.
+pub fn save(data_file: &Path) {
+    let Some(latest) = LATEST.get() else {
+        return;
+    };
+    if !latest.has_changed().unwrap_or(false) {
+        return;
+    }
+    let todos = todo::from_origin(&latest.borrow(), None);
+    let path = path(data_file);
+    let written = serde_json::to_vec(&todos)
+        .map_err(std::io::Error::from)
+        .and_then(|json| fs::write(&path, json));
+    match written {
+        Ok(()) => log::info!("Saved the todos to {}", path.display()),
+        Err(e) => log::error!("Failed to save the todos to {}: {e}", path.display()),
+    }
+}
+
+/// Offers to load the todos saved by the panic handler. They are joined with the saved ones as of
+/// when they were last changed, so todos added or edited since the crash stay as they are. The
+/// recovery file is removed whatever the answer, so the offer is only made once.
+pub fn offer(input: &mut impl BufRead, output: &mut impl Write, data_file: &Path) -> Result<()> {
+    let path = path(data_file);
+    let Ok(raw) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let recovered: Vec<TodoItem> = serde_json::from_str(&raw)?;
+    write!(
+        output,
+        "The last session crashed, leaving {} todos in {}. Load them? [Y/n]: ",
+        recovered.len(),
+        path.display()
+    )?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    if !line.trim().eq_ignore_ascii_case("n") {
+        todo::save_document(data_file, &Document::from_todos(&recovered))?;
+    }
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_offer_recovered_todos() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-recovery-{}", Uuid::new_v4()));
+        let data_file = dir.join("home.json");
+        todo::update(&data_file, |todos| todos.push(TodoItem::parse("saved")))?;
+        let mut recovered = todo::load(&data_file)?;
+        recovered.push(TodoItem::parse("unsaved"));
+        fs::write(path(&data_file), serde_json::to_vec(&recovered)?)?;
+        // Added from elsewhere after the crash
+        todo::update(&data_file, |todos| todos.push(TodoItem::parse("synced")))?;
+
+        let mut output = Vec::new();
+        offer(&mut "\n".as_bytes(), &mut output, &data_file)?;
+        let titles: Vec<String> = todo::load(&data_file)?
+            .into_iter()
+            .map(|todo| todo.title)
+            .collect();
+        assert_eq!(titles, vec!["saved", "unsaved", "synced"]);
+        assert!(!path(&data_file).exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
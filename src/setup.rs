@@ -0,0 +1,172 @@
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+
+use crate::config::{Keymap, CONFIG_FILES};
+
+/// Colors for the list highlight, completed todos and the input box, as style strings.
+const THEMES: [(&str, [&str; 3]); 2] = [
+    ("dark", ["bold white on color238", "color244", "yellow"]),
+    ("light", ["bold black on color252", "color246", "blue"]),
+];
+
+/// What the first-run questions were answered with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Answers {
+    pub data_file: PathBuf,
+    pub theme: String,
+    pub keymap: Keymap,
+}
+
+/// Whether this looks like the first launch: no config file and no saved todos.
+pub fn needed(config_dir: &Path, data_file: &Path) -> bool {
+    !data_file.exists()
+        && CONFIG_FILES
+            .iter()
+            .all(|(file, _)| !config_dir.join(file).exists())
+}
+
+/// Asks one question, re-asking until the answer is one of `choices`. An empty answer takes the
+/// first choice.
+fn choose<'a>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    choices: &[&'a str],
+) -> Result<&'a str> {
+    loop {
+        write!(output, "{question} [{}]: ", choices.join("/"))?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(choices[0]);
+        }
+        let answer = line.trim().to_lowercase();
+        if answer.is_empty() {
+            return Ok(choices[0]);
+        }
+        if let Some(choice) = choices.iter().find(|choice| **choice == answer) {
+            return Ok(choice);
+        }
+        writeln!(output, "Please answer one of {}.", choices.join(", "))?;
+    }
+}
+
+pub fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    default_data_file: &Path,
+) -> Result<Answers> {
+    writeln!(
+        output,
+        "Welcome! A few questions to set things up, Enter takes the default.\n"
+    )?;
+    write!(
+        output,
+        "Where should todos be saved? [{}]: ",
+        default_data_file.display()
+    )?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let data_file = match line.trim() {
+        "" => default_data_file.to_path_buf(),
+        path => PathBuf::from(path),
+    };
+
+    let themes: Vec<&str> = THEMES
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(["none"])
+        .collect();
+    let theme = choose(input, output, "Color theme", &themes)?.to_string();
+    let keymap = match choose(
+        input,
+        output,
+        "Keys for moving through the list",
+        &["vim", "emacs", "arrows"],
+    )? {
+        "emacs" => Keymap::Emacs,
+        "arrows" => Keymap::Arrows,
+        _ => Keymap::Vim,
+    };
+    Ok(Answers {
+        data_file,
+        theme,
+        keymap,
+    })
+}
+
+/// The config file written for the answers, commented so it can be edited by hand later.
+pub fn render(answers: &Answers) -> Result<String> {
+    let mut out = String::from("{\n  // Written by the first-run setup\n");
+    out.push_str(&format!(
+        "  \"data_file\": {},\n",
+        serde_json::to_string(&answers.data_file)?
+    ));
+    out.push_str(&format!(
+        "  \"keymap\": {},\n",
+        serde_json::to_string(&answers.keymap)?
+    ));
+    if let Some((_, [highlight, done, input])) =
+        THEMES.iter().find(|(name, _)| *name == answers.theme)
+    {
+        out.push_str(&format!(
+            "  \"styles\": {{\n    \"Home\": {{\n      \"highlight\": \"{highlight}\",\n      \
+             \"done\": \"{done}\",\n      \"input\": \"{input}\",\n    }},\n  }},\n"
+        ));
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// Runs the questions on the terminal and writes the answers as the config file.
+pub fn run(config_dir: &Path, default_data_file: &Path) -> Result<()> {
+    let answers = ask(
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+        default_data_file,
+    )?;
+    fs::create_dir_all(config_dir)?;
+    let path = config_dir.join(CONFIG_FILES[0].0);
+    fs::write(&path, render(&answers)?)?;
+    println!("\nSaved to {}, starting up.", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_ask_and_render() -> Result<()> {
+        let mut input = "\nblue\nlight\nEmacs\n".as_bytes();
+        let mut output = Vec::new();
+        let answers = ask(&mut input, &mut output, Path::new("todos.json"))?;
+        assert_eq!(
+            answers,
+            Answers {
+                data_file: "todos.json".into(),
+                theme: "light".into(),
+                keymap: Keymap::Emacs,
+            }
+        );
+        assert!(String::from_utf8(output)?.contains("Please answer one of dark, light, none."));
+
+        let rendered = render(&answers)?;
+        let config: serde_json::Value = json5::from_str(&rendered)?;
+        assert_eq!(config["keymap"], "Emacs");
+        assert_eq!(config["styles"]["Home"]["done"], "color246");
+        assert_eq!(
+            crate::config::validate::check_value(Path::new("config.json5"), &rendered, &config),
+            vec![]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+
+use crate::{
+    streaks,
+    todo::{EventKind, TodoItem},
+};
+
+/// How many days back goal attainment is counted over.
+const GOAL_WINDOW: u64 = 30;
+
+/// Figures about the list for the stats view.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub open: usize,
+    pub done: usize,
+    pub completed_today: usize,
+    pub streak: u32,
+    /// The daily goal and on how many of the last `GOAL_WINDOW` days it was met.
+    pub goal: Option<(usize, usize)>,
+}
+
+/// How many todos were completed on each day, in local time.
+pub fn completions_per_day(todos: &[TodoItem]) -> BTreeMap<NaiveDate, usize> {
+    let mut days = BTreeMap::new();
+    for at in todos.iter().filter_map(|todo| todo.completed) {
+        *days
+            .entry(at.with_timezone(&Local).date_naive())
+            .or_default() += 1;
+    }
+    days
+}
+
+/// The estimate left open at the end of each of the last `days` days, oldest first, counting
+/// only todos in `project` when one is given. Todos count from the day they were created, or
+/// from the start when their history does not say.
+pub fn burndown(
+    todos: &[TodoItem],
+    project: Option<&str>,
+    today: NaiveDate,
+    days: u64,
+) -> Vec<(NaiveDate, u32)> {
+    let local_day = |at: DateTime<Utc>| at.with_timezone(&Local).date_naive();
+    let todos: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|todo| project.is_none() || todo.project.as_deref() == project)
+        .collect();
+    (0..days)
+        .rev()
+        .filter_map(|back| today.checked_sub_days(Days::new(back)))
+        .map(|day| {
+            let remaining = todos
+                .iter()
+                .filter(|todo| {
+                    let created = todo
+                        .history
+                        .iter()
+                        .find(|event| event.kind == EventKind::Created)
+                        .map(|event| local_day(event.at));
+                    let completed = todo.completed.map(local_day);
+                    created.is_none_or(|created| created <= day)
+                        && completed.is_none_or(|completed| completed > day)
+                        && !(todo.done && completed.is_none())
+                })
+                .filter_map(|todo| todo.estimate)
+                .sum();
+            (day, remaining)
+        })
+        .collect()
+}
+
+/// The estimate completed per day, averaged over the last `days` days.
+pub fn velocity(todos: &[TodoItem], project: Option<&str>, today: NaiveDate, days: u64) -> f64 {
+    let since = today.checked_sub_days(Days::new(days)).unwrap_or(today);
+    let done: u32 = todos
+        .iter()
+        .filter(|todo| project.is_none() || todo.project.as_deref() == project)
+        .filter(|todo| {
+            todo.completed
+                .is_some_and(|at| at.with_timezone(&Local).date_naive() > since)
+        })
+        .filter_map(|todo| todo.estimate)
+        .sum();
+    f64::from(done) / days.max(1) as f64
+}
+
+pub fn compute(todos: &[TodoItem], today: NaiveDate, goal: Option<usize>) -> Stats {
+    let days = completions_per_day(todos);
+    let goal = goal.map(|goal| {
+        let met = (0..GOAL_WINDOW)
+            .filter_map(|back| today.checked_sub_days(Days::new(back)))
+            .filter(|day| days.get(day).is_some_and(|count| *count >= goal))
+            .count();
+        (goal, met)
+    });
+    let done = todos.iter().filter(|todo| todo.done).count();
+    Stats {
+        open: todos.len() - done,
+        done,
+        completed_today: days.get(&today).copied().unwrap_or_default(),
+        streak: streaks::current(todos, today),
+        goal,
+    }
+}
+
+impl Stats {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Open: {}", self.open),
+            format!("Done: {}", self.done),
+            format!("Completed today: {}", self.completed_today),
+            format!("Streak: {} days", self.streak),
+        ];
+        if let Some((goal, met)) = self.goal {
+            lines.push(format!(
+                "Daily goal of {goal}: met on {met} of the last {GOAL_WINDOW} days"
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::streaks::completed_days_ago;
+
+    #[test]
+    fn test_goal_attainment() {
+        let mut todos: Vec<TodoItem> = [0, 0, 1, 2, 2, 40, 40].map(completed_days_ago).to_vec();
+        todos.push(TodoItem::new("open".into()));
+        let stats = compute(&todos, Local::now().date_naive(), Some(2));
+        assert_eq!(
+            stats,
+            Stats {
+                open: 1,
+                done: 7,
+                completed_today: 2,
+                streak: 3,
+                goal: Some((2, 2)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_burndown() {
+        let today = Local::now().date_naive();
+        let mut early = completed_days_ago(1);
+        early.estimate = Some(3);
+        let mut open = TodoItem::parse("open est:5 +work");
+        open.log(EventKind::Created);
+        let mut elsewhere = TodoItem::parse("elsewhere est:8 +home");
+        elsewhere.log(EventKind::Created);
+        let todos = vec![early, open, elsewhere];
+
+        let remaining: Vec<u32> = burndown(&todos, None, today, 3)
+            .into_iter()
+            .map(|(_, remaining)| remaining)
+            .collect();
+        assert_eq!(remaining, vec![3, 0, 13]);
+        let remaining: Vec<u32> = burndown(&todos, Some("work"), today, 3)
+            .into_iter()
+            .map(|(_, remaining)| remaining)
+            .collect();
+        assert_eq!(remaining, vec![0, 0, 5]);
+        assert_eq!(velocity(&todos, None, today, 3), 1.0);
+    }
+}
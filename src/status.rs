@@ -0,0 +1,160 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{stats, todo::TodoItem};
+
+/// The color placeholders a status line format can use, with their ANSI and tmux escapes.
+const COLORS: [(&str, &str, &str); 7] = [
+    ("red", "\x1b[31m", "#[fg=red]"),
+    ("green", "\x1b[32m", "#[fg=green]"),
+    ("yellow", "\x1b[33m", "#[fg=yellow]"),
+    ("blue", "\x1b[34m", "#[fg=blue]"),
+    ("magenta", "\x1b[35m", "#[fg=magenta]"),
+    ("cyan", "\x1b[36m", "#[fg=cyan]"),
+    ("reset", "\x1b[0m", "#[default]"),
+];
+
+/// What the status line counts.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub done_today: usize,
+    pub open: usize,
+    pub overdue: usize,
+    pub flagged: usize,
+}
+
+/// How colors are written, for a terminal such as in a starship module or for tmux, or left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Escapes {
+    Ansi,
+    Tmux,
+    Plain,
+}
+
+/// A custom module's output in the JSON Waybar reads, one object per line.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Waybar {
+    pub text: String,
+    /// The todos due today or overdue, one per line.
+    pub tooltip: String,
+    /// `overdue`, `due` or `clear`, for styling the module.
+    pub class: &'static str,
+}
+
+pub fn counts(todos: &[TodoItem], today: NaiveDate) -> Counts {
+    let open = || todos.iter().filter(|todo| !todo.done);
+    Counts {
+        done_today: stats::completions_per_day(todos)
+            .get(&today)
+            .copied()
+            .unwrap_or(0),
+        open: open().count(),
+        overdue: today.pred_opt().map_or(0, |yesterday| {
+            open().filter(|t| t.is_due_on(yesterday)).count()
+        }),
+        flagged: open().filter(|todo| todo.flagged).count(),
+    }
+}
+
+/// Fills in `format`: `{done}`, `{open}`, `{overdue}` and `{flagged}` become counts and color
+/// names in braces, such as `{green}` or `{reset}`, become escapes.
+pub fn render(format: &str, counts: &Counts, escapes: Escapes) -> String {
+    let mut line = format
+        .replace("{done}", &counts.done_today.to_string())
+        .replace("{open}", &counts.open.to_string())
+        .replace("{overdue}", &counts.overdue.to_string())
+        .replace("{flagged}", &counts.flagged.to_string());
+    for (name, ansi, tmux) in COLORS {
+        let escape = match escapes {
+            Escapes::Ansi => ansi,
+            Escapes::Tmux => tmux,
+            Escapes::Plain => "",
+        };
+        line = line.replace(&format!("{{{name}}}"), escape);
+    }
+    line
+}
+
+/// The Waybar module for the list, with the status line as plain text.
+pub fn waybar(todos: &[TodoItem], format: &str, today: NaiveDate) -> Waybar {
+    let counts = counts(todos, today);
+    let due: Vec<String> = todos
+        .iter()
+        .filter(|todo| todo.is_due_on(today))
+        .map(ToString::to_string)
+        .collect();
+    let class = if counts.overdue > 0 {
+        "overdue"
+    } else if !due.is_empty() {
+        "due"
+    } else {
+        "clear"
+    };
+    Waybar {
+        text: render(format, &counts, Escapes::Plain),
+        tooltip: if due.is_empty() {
+            "Nothing due today".to_string()
+        } else {
+            due.join("\n")
+        },
+        class,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render_counts() {
+        let today = Local::now().date_naive();
+        let mut todos = vec![
+            TodoItem::parse("late due:2000-01-01"),
+            TodoItem::parse("flagged"),
+            TodoItem::parse("finished"),
+        ];
+        todos[1].flagged = true;
+        todos[2].done = true;
+        todos[2].completed = Some(chrono::Utc::now());
+        let counts = counts(&todos, today);
+        assert_eq!(
+            counts,
+            Counts {
+                done_today: 1,
+                open: 2,
+                overdue: 1,
+                flagged: 1
+            }
+        );
+        let format = "{green}✓{done} {yellow}●{open} {red}⚑{flagged}{reset}";
+        assert_eq!(
+            render(format, &counts, Escapes::Tmux),
+            "#[fg=green]✓1 #[fg=yellow]●2 #[fg=red]⚑1#[default]"
+        );
+        assert_eq!(
+            render("{red}{overdue}{reset}", &counts, Escapes::Ansi),
+            "\x1b[31m1\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_waybar_class() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut todos = vec![TodoItem::parse("call bank due:2024-05-01")];
+        let module = waybar(&todos, "{red}{open}{reset}", today);
+        assert_eq!((module.text.as_str(), module.class), ("1", "due"));
+        assert!(module.tooltip.contains("call bank"));
+
+        todos[0].due = NaiveDate::from_ymd_opt(2024, 4, 1);
+        assert_eq!(waybar(&todos, "", today).class, "overdue");
+        todos[0].done = true;
+        let module = waybar(&todos, "", today);
+        assert_eq!(
+            (module.tooltip.as_str(), module.class),
+            ("Nothing due today", "clear")
+        );
+    }
+}
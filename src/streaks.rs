@@ -0,0 +1,72 @@
+use std::collections::BTreeSet;
+
+use chrono::{Days, Local, NaiveDate};
+
+use crate::todo::TodoItem;
+
+/// The days, in local time, that something was completed on.
+fn completion_days(todos: &[TodoItem]) -> BTreeSet<NaiveDate> {
+    todos
+        .iter()
+        .filter_map(|todo| todo.completed)
+        .map(|at| at.with_timezone(&Local).date_naive())
+        .collect()
+}
+
+/// How many days in a row up to `today` had at least one completion. A streak that reached
+/// yesterday still counts, since today is not over yet.
+pub fn current(todos: &[TodoItem], today: NaiveDate) -> u32 {
+    let days = completion_days(todos);
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        match today.checked_sub_days(Days::new(1)) {
+            Some(yesterday) => yesterday,
+            None => return 0,
+        }
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        match day.checked_sub_days(Days::new(1)) {
+            Some(previous) => day = previous,
+            None => break,
+        }
+    }
+    streak
+}
+
+/// A todo completed at noon local time `days` days ago, for tests that count completions.
+#[cfg(test)]
+pub(crate) fn completed_days_ago(days: i64) -> TodoItem {
+    use chrono::{Duration, TimeZone, Utc};
+
+    let mut todo = TodoItem::new(format!("{days}"));
+    let noon = Local::now().date_naive().and_hms_opt(12, 0, 0).unwrap();
+    let noon = Local
+        .from_local_datetime(&noon)
+        .unwrap()
+        .with_timezone(&Utc);
+    todo.done = true;
+    todo.completed = Some(noon - Duration::days(days));
+    todo
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_current_streak() {
+        let today = Local::now().date_naive();
+        let todos: Vec<TodoItem> = [1, 2, 3, 5].map(completed_days_ago).to_vec();
+        assert_eq!(current(&todos, today), 3);
+
+        let mut todos = todos;
+        todos.push(completed_days_ago(0));
+        assert_eq!(current(&todos, today), 4);
+        assert_eq!(current(&[completed_days_ago(2)], today), 0);
+    }
+}
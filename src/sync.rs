@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use color_eyre::eyre::{bail, eyre, Result};
+use log::warn;
+use uuid::Uuid;
+
+use crate::{crdt::Document, migrate, todo};
+
+const EXTENSION: &str = "doit";
+const NONCE_LEN: usize = 24;
+
+/// Somewhere blobs can be listed, read and written by name. The remote only ever sees ciphertext.
+pub trait Remote {
+    fn list(&self) -> Result<Vec<String>>;
+    fn get(&self, name: &str) -> Result<Vec<u8>>;
+    fn put(&self, name: &str, data: &[u8]) -> Result<()>;
+}
+
+/// A directory as the remote, such as a Syncthing/Dropbox folder or a mounted S3 bucket or
+/// WebDAV share.
+pub struct DirRemote(pub PathBuf);
+
+impl Remote for DirRemote {
+    fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.0)? {
+            if let Some(name) = entry?.file_name().to_str() {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        Ok(fs::read(self.0.join(name))?)
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> Result<()> {
+        // Write then rename, so other devices never read a half-written blob
+        let tmp = self.0.join(format!(".{name}.tmp"));
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, self.0.join(name))?;
+        Ok(())
+    }
+}
+
+/// Reads the shared key, generating one on first use. Copy this file to every device.
+pub fn load_or_create_key(path: &Path) -> Result<Key> {
+    if let Ok(bytes) = fs::read(path) {
+        if bytes.len() != 32 {
+            bail!("Sync key at {} is not 32 bytes long", path.display());
+        }
+        return Ok(*Key::from_slice(&bytes));
+    }
+    let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, key)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(key)
+}
+
+/// This device's name on the remote, generated once and kept alongside the data file.
+pub fn device_id(data_file: &Path) -> Result<Uuid> {
+    let path = todo::state_path(data_file).with_file_name("device.id");
+    if let Ok(raw) = fs::read_to_string(&path) {
+        return Ok(Uuid::parse_str(raw.trim())?);
+    }
+    let id = Uuid::new_v4();
+    fs::write(&path, id.to_string())?;
+    Ok(id)
+}
+
+/// Encrypts a document as `nonce || ciphertext`.
+pub fn seal(key: &Key, document: &Document) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = serde_json::to_vec(&migrate::encode(document)?)?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|e| eyre!("Failed to encrypt: {e}"))?;
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+pub fn open(key: &Key, blob: &[u8]) -> Result<Document> {
+    if blob.len() < NONCE_LEN {
+        bail!("Blob is too short to be a sealed document");
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let plaintext = XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| eyre!("Failed to decrypt, is the sync key the same on every device?"))?;
+    migrate::decode(serde_json::from_slice(&plaintext)?)
+}
+
+/// Joins every other device's document into the local one, saves it, and publishes the result
+/// as this device's document. Returns how many remote documents were merged.
+pub fn sync(remote: &dyn Remote, key: &Key, data_file: &Path) -> Result<usize> {
+    let ours = format!("{}.{EXTENSION}", device_id(data_file)?);
+    let mut document = todo::load_document(data_file)?;
+    let mut merged = 0;
+
+    for name in remote.list()? {
+        if name == ours || !name.ends_with(&format!(".{EXTENSION}")) {
+            continue;
+        }
+        match remote.get(&name).and_then(|blob| open(key, &blob)) {
+            Ok(theirs) => {
+                document.join(&theirs);
+                merged += 1;
+            }
+            Err(e) => warn!("Skipping {name}: {e:?}"),
+        }
+    }
+
+    let document = todo::save_document(data_file, &document)?;
+    remote.put(&ours, &seal(key, &document)?)?;
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::todo::TodoItem;
+
+    #[test]
+    fn test_seal_round_trip() -> Result<()> {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let document = Document::from_todos(&[TodoItem::new("secret".into())]);
+        let blob = seal(&key, &document)?;
+        assert!(!String::from_utf8_lossy(&blob).contains("secret"));
+        assert_eq!(open(&key, &blob)?, document);
+
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(open(&other, &blob).is_err());
+        Ok(())
+    }
+}
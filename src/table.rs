@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{config::UrgencyConfig, i18n::tr, todo::TodoItem, urgency};
+
+/// A column of the table view of the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Title,
+    Due,
+    /// `⚑` for flagged todos.
+    Priority,
+    Tags,
+    /// Days since the todo was created.
+    Age,
+    /// The score `urgency::sort` orders by.
+    Urgency,
+}
+
+impl Column {
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Title => tr("Title"),
+            Column::Due => tr("Due"),
+            Column::Priority => tr("Priority"),
+            Column::Tags => tr("Tags"),
+            Column::Age => tr("Age"),
+            Column::Urgency => tr("Urgency"),
+        }
+    }
+
+    /// How wide the column is, with room for the header and its sort mark. The title takes
+    /// what the others leave.
+    pub fn width(self) -> Constraint {
+        let content = match self {
+            Column::Title => return Constraint::Min(10),
+            Column::Tags => return Constraint::Percentage(25),
+            Column::Due => 10,
+            Column::Priority => 1,
+            Column::Age => 4,
+            Column::Urgency => 5,
+        };
+        Constraint::Length(content.max(self.header().width() as u16 + 2))
+    }
+
+    /// What the column shows for `todo` on `today`.
+    pub fn cell(self, todo: &TodoItem, today: NaiveDate, weights: &UrgencyConfig) -> String {
+        match self {
+            Column::Title => todo.title.clone(),
+            Column::Due => todo.due.map(|due| due.to_string()).unwrap_or_default(),
+            Column::Priority => if todo.flagged { "⚑" } else { "" }.to_string(),
+            Column::Tags => todo
+                .tags
+                .iter()
+                .map(|tag| format!("#{tag}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Column::Age => format!("{}d", urgency::age(todo, today)),
+            Column::Urgency => format!("{:.1}", urgency::score(todo, today, weights)),
+        }
+    }
+
+    /// How `a` and `b` go in ascending order of the column.
+    pub fn compare(
+        self,
+        a: &TodoItem,
+        b: &TodoItem,
+        today: NaiveDate,
+        weights: &UrgencyConfig,
+    ) -> Ordering {
+        match self {
+            Column::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            Column::Due => a.due.cmp(&b.due),
+            Column::Priority => a.flagged.cmp(&b.flagged),
+            Column::Tags => a.tags.cmp(&b.tags),
+            Column::Age => urgency::age(a, today).cmp(&urgency::age(b, today)),
+            Column::Urgency => {
+                urgency::score(a, today, weights).total_cmp(&urgency::score(b, today, weights))
+            }
+        }
+    }
+}
+
+/// Sorts `todos` by `column`, descending if asked, keeping the order of those that tie.
+pub fn sort(
+    todos: &mut [TodoItem],
+    column: Column,
+    descending: bool,
+    today: NaiveDate,
+    weights: &UrgencyConfig,
+) {
+    todos.sort_by(|a, b| {
+        let order = column.compare(a, b, today, weights);
+        if descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
+/// The sort after `current` when cycling through `columns`: the same column descending after
+/// ascending, otherwise the next column ascending.
+pub fn next_sort(columns: &[Column], current: Option<(Column, bool)>) -> Option<(Column, bool)> {
+    match current {
+        Some((column, false)) if columns.contains(&column) => Some((column, true)),
+        Some((column, true)) => {
+            let at = columns.iter().position(|&c| c == column);
+            let next = at.map_or(0, |at| (at + 1) % columns.len());
+            columns.get(next).map(|&column| (column, false))
+        }
+        _ => columns.first().map(|&column| (column, false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_next_sort() {
+        let columns = [Column::Title, Column::Due];
+        let mut sort = None;
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            sort = next_sort(&columns, sort);
+            seen.push(sort);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Some((Column::Title, false)),
+                Some((Column::Title, true)),
+                Some((Column::Due, false)),
+                Some((Column::Due, true)),
+                Some((Column::Title, false)),
+            ]
+        );
+    }
+}
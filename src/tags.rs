@@ -0,0 +1,267 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use color_eyre::eyre::Result;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    intern::Name,
+    todo::{EventKind, TodoItem},
+};
+
+/// The file in the config directory that tag colors picked on the tags screen are saved to.
+pub const COLORS_FILE: &str = "tag_colors.json";
+
+/// The colors the tags screen cycles through, before going back to none.
+pub const COLORS: [&str; 6] = ["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+/// Every tag in use and how many todos carry it, by name.
+pub fn counts(todos: &[TodoItem]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in todos.iter().flat_map(|todo| &todo.tags) {
+        *counts.entry(tag).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect()
+}
+
+/// Every project in use and how many of its todos are still open, by name.
+pub fn projects(todos: &[TodoItem]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for todo in todos {
+        if let Some(project) = &todo.project {
+            *counts.entry(project).or_default() += usize::from(!todo.done);
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(project, count)| (project.to_string(), count))
+        .collect()
+}
+
+/// Every context in use, by name.
+pub fn contexts(todos: &[TodoItem]) -> Vec<String> {
+    let contexts: BTreeSet<&str> = todos
+        .iter()
+        .filter_map(|todo| todo.context.as_deref())
+        .collect();
+    contexts.into_iter().map(str::to_string).collect()
+}
+
+/// Everyone on the team and anyone else todos are assigned to, by name.
+pub fn assignees(todos: &[TodoItem], team: &BTreeMap<String, String>) -> Vec<String> {
+    let assignees: BTreeSet<&str> = todos
+        .iter()
+        .filter_map(|todo| todo.assignee.as_deref())
+        .chain(team.keys().map(String::as_str))
+        .collect();
+    assignees.into_iter().map(str::to_string).collect()
+}
+
+/// The badge color for an assignee: theirs from the team, or one picked from their name.
+pub fn assignee_color<'a>(name: &str, team: &'a BTreeMap<String, String>) -> &'a str {
+    team.get(name).map_or_else(
+        || COLORS[name.bytes().map(usize::from).sum::<usize>() % COLORS.len()],
+        String::as_str,
+    )
+}
+
+/// Existing tags, projects or contexts that could finish `word`, a `#tag`, `+project` or
+/// `@context` being typed.
+pub fn complete(todos: &[TodoItem], word: &str) -> Vec<String> {
+    let (sigil, prefix) = match word.chars().next() {
+        Some(sigil @ ('#' | '+' | '@')) => (sigil, &word[1..]),
+        _ => return Vec::new(),
+    };
+    let names: BTreeSet<&str> = match sigil {
+        '#' => todos
+            .iter()
+            .flat_map(|todo| &todo.tags)
+            .map(Name::as_str)
+            .collect(),
+        '+' => todos
+            .iter()
+            .filter_map(|todo| todo.project.as_deref())
+            .collect(),
+        _ => todos
+            .iter()
+            .filter_map(|todo| todo.context.as_deref())
+            .collect(),
+    };
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(prefix) && *name != prefix)
+        .map(|name| format!("{sigil}{name}"))
+        .collect()
+}
+
+/// Renames `from` to `to` on every todo, merging the two where a todo already has `to`.
+pub fn rename(todos: &mut [TodoItem], from: &str, to: &str) {
+    for todo in todos
+        .iter_mut()
+        .filter(|todo| todo.tags.iter().any(|t| t == from))
+    {
+        if todo.tags.iter().any(|t| t == to) {
+            todo.tags.retain(|t| t != from);
+        } else {
+            for tag in todo.tags.iter_mut().filter(|t| *t == from) {
+                *tag = to.into();
+            }
+        }
+        todo.touch();
+        todo.log(EventKind::Edited);
+    }
+}
+
+/// Takes `tag` off every todo.
+pub fn delete(todos: &mut [TodoItem], tag: &str) {
+    for todo in todos
+        .iter_mut()
+        .filter(|todo| todo.tags.iter().any(|t| t == tag))
+    {
+        todo.tags.retain(|t| t != tag);
+        todo.touch();
+        todo.log(EventKind::Edited);
+    }
+}
+
+/// How links to a project are written among a todo's attachments.
+pub const PROJECT_LINK: &str = "project:";
+
+/// A project name made from a todo title: lowercase words joined by dashes.
+fn project_name(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Turns the todo at `index` into a project of its own: its subtasks, however deep, move into
+/// the new project, the direct ones losing their parent, and the todo is left where it was with
+/// a link to the project attached. Returns the project's name.
+pub fn promote(todos: &mut [TodoItem], index: usize) -> Option<String> {
+    let promoted = todos.get(index)?;
+    let (id, name) = (promoted.id, project_name(&promoted.title));
+    if name.is_empty() {
+        return None;
+    }
+    let parents: BTreeMap<Uuid, Option<Uuid>> =
+        todos.iter().map(|todo| (todo.id, todo.parent)).collect();
+    let is_under = |mut parent: Option<Uuid>| {
+        for _ in 0..parents.len() {
+            match parent {
+                Some(p) if p == id => return true,
+                Some(p) => parent = parents.get(&p).copied().flatten(),
+                None => return false,
+            }
+        }
+        false
+    };
+    for todo in todos.iter_mut().filter(|todo| is_under(todo.parent)) {
+        todo.project = Some(name.as_str().into());
+        if todo.parent == Some(id) {
+            todo.parent = None;
+        }
+        todo.touch();
+        todo.log(EventKind::Edited);
+    }
+    let promoted = &mut todos[index];
+    promoted.attachments.push(format!("{PROJECT_LINK}{name}"));
+    promoted.touch();
+    promoted.log(EventKind::Edited);
+    Some(name)
+}
+
+/// The color after `current` in `COLORS`, or none after the last one.
+pub fn next_color(current: Option<&str>) -> Option<&'static str> {
+    match current.and_then(|color| COLORS.iter().position(|c| *c == color)) {
+        Some(i) => COLORS.get(i + 1).copied(),
+        None => Some(COLORS[0]),
+    }
+}
+
+/// Saves tag colors as their own config file, so the user's hand-written one is left alone.
+pub fn save_colors(config_dir: &Path, colors: &BTreeMap<String, String>) -> Result<()> {
+    #[derive(Serialize)]
+    struct File<'a> {
+        tag_colors: &'a BTreeMap<String, String>,
+    }
+    crate::utils::write_json(&config_dir.join(COLORS_FILE), &File { tag_colors: colors })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_rename_merge_and_delete() {
+        let mut todos = vec![
+            TodoItem::parse("a #work #urgent"),
+            TodoItem::parse("b #job #work"),
+            TodoItem::parse("c #job"),
+        ];
+        assert_eq!(
+            counts(&todos),
+            vec![("job".into(), 2), ("urgent".into(), 1), ("work".into(), 2)]
+        );
+        rename(&mut todos, "job", "work");
+        assert_eq!(
+            counts(&todos),
+            vec![("urgent".into(), 1), ("work".into(), 3)]
+        );
+        assert_eq!(todos[1].tags, vec!["work"]);
+        delete(&mut todos, "urgent");
+        assert_eq!(todos[0].tags, vec!["work"]);
+        assert_eq!(next_color(None), Some("red"));
+        assert_eq!(next_color(Some("cyan")), None);
+    }
+
+    #[test]
+    fn test_promote_to_project() {
+        let mut todos = vec![
+            TodoItem::parse("Kitchen: remodel +home"),
+            TodoItem::parse("pick tiles"),
+            TodoItem::parse("grout"),
+            TodoItem::parse("unrelated +home"),
+        ];
+        todos[1].parent = Some(todos[0].id);
+        todos[2].parent = Some(todos[1].id);
+        assert_eq!(promote(&mut todos, 0).as_deref(), Some("kitchen-remodel"));
+        let projects: Vec<Option<&str>> = todos.iter().map(|t| t.project.as_deref()).collect();
+        assert_eq!(
+            projects,
+            vec![
+                Some("home"),
+                Some("kitchen-remodel"),
+                Some("kitchen-remodel"),
+                Some("home")
+            ]
+        );
+        assert_eq!(todos[1].parent, None);
+        assert_eq!(todos[2].parent, Some(todos[1].id));
+        assert_eq!(todos[0].attachments, vec!["project:kitchen-remodel"]);
+    }
+
+    #[test]
+    fn test_complete_tags_and_projects() {
+        let todos = vec![
+            TodoItem::parse("a #work #weekend +house"),
+            TodoItem::parse("b #wait +holiday @home"),
+        ];
+        assert_eq!(complete(&todos, "#we"), vec!["#weekend"]);
+        assert_eq!(complete(&todos, "#w"), vec!["#wait", "#weekend", "#work"]);
+        assert_eq!(complete(&todos, "+h"), vec!["+holiday", "+house"]);
+        assert_eq!(complete(&todos, "+house"), Vec::<String>::new());
+        assert_eq!(complete(&todos, "@"), vec!["@home"]);
+        assert_eq!(complete(&todos, "w"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,59 @@
+/// Fills in a template of `{name}` placeholders with `value(name)`. A section
+/// `{?name}...{/name}` is only kept when `name` has a value that isn't empty, so separators can
+/// go along with what they separate, as in `{title}{?due} (due {due}){/due}`. Placeholders
+/// `value` doesn't know, and unclosed ones, are kept as they are written.
+pub fn fill(template: &str, value: &impl Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[1..end];
+        if let Some(name) = name.strip_prefix('?') {
+            let close = format!("{{/{name}}}");
+            if let Some(len) = rest[end + 1..].find(&close) {
+                let inner = &rest[end + 1..end + 1 + len];
+                if value(name).is_some_and(|value| !value.is_empty()) {
+                    filled.push_str(&fill(inner, value));
+                }
+                rest = &rest[end + 1 + len + close.len()..];
+                continue;
+            }
+        } else if let Some(value) = value(name) {
+            filled.push_str(&value);
+            rest = &rest[end + 1..];
+            continue;
+        }
+        filled.push_str(&rest[..=end]);
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let value = |name: &str| match name {
+            "title" => Some("pay rent".to_string()),
+            "due" => Some(String::new()),
+            "tags" => Some("#home".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            fill(
+                "{title}{?due} (due {due}){/due}{?tags} {tags}{/tags} {other}",
+                &value
+            ),
+            "pay rent #home {other}"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Where text too long for its room is cut short.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Truncation {
+    /// Keeps the start and cuts off the end.
+    #[default]
+    End,
+    /// Keeps the start and the end and cuts out the middle.
+    Middle,
+}
+
+/// Cuts `text` short with a `…` so that it takes at most `width` cells.
+pub fn truncate(text: &str, width: usize, truncation: Truncation) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let room = width - 1;
+    let (start, end) = match truncation {
+        Truncation::End => (room, 0),
+        Truncation::Middle => (room - room / 2, room / 2),
+    };
+    let mut kept: String = take_width(text.chars(), start).collect();
+    kept.push('…');
+    let tail: Vec<char> = take_width(text.chars().rev(), end).collect();
+    kept.extend(tail.into_iter().rev());
+    kept
+}
+
+/// The leading `chars` that fit in `width` cells.
+fn take_width(chars: impl Iterator<Item = char>, width: usize) -> impl Iterator<Item = char> {
+    let mut used = 0;
+    chars.take_while(move |c| {
+        used += c.width().unwrap_or(0);
+        used <= width
+    })
+}
+
+/// Breaks `text` into lines of at most `width` cells, between words where it can and inside
+/// words too long for a line of their own.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return vec![text.to_string()];
+    }
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        if !line.is_empty() && line.width() + 1 + word.width() > width {
+            lines.push(std::mem::take(&mut line));
+        } else if !line.is_empty() {
+            line.push(' ');
+        }
+        for c in word.chars() {
+            if line.width() + c.width().unwrap_or(0) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("renew the passport before the trip", 12),
+            vec!["renew the", "passport", "before the", "trip"]
+        );
+        assert_eq!(wrap("unbelievably", 5), vec!["unbel", "ievab", "ly"]);
+        assert_eq!(wrap("pay rent", 20), vec!["pay rent"]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("pay rent", 8, Truncation::End), "pay rent");
+        assert_eq!(truncate("pay rent", 5, Truncation::End), "pay …");
+        assert_eq!(truncate("日本語", 4, Truncation::End), "日…");
+        assert_eq!(
+            truncate("renew the passport.pdf", 11, Truncation::Middle),
+            "renew…t.pdf"
+        );
+    }
+}
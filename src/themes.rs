@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+use serde::Serialize;
+
+use crate::{
+    config::{parse_style, Styles},
+    mode::Mode,
+};
+
+/// The file in the config directory that the theme picked in the TUI is saved to.
+pub const FILE: &str = "theme.json";
+
+/// The styles a preset sets, in the order `PRESETS` lists them.
+const KEYS: [&str; 5] = ["highlight", "done", "starred", "input", "link"];
+
+/// Built-in themes by name, as style strings for each of `KEYS`.
+pub const PRESETS: [(&str, [&str; 5]); 6] = [
+    (
+        "gruvbox",
+        [
+            "bold color223 on color237",
+            "color245",
+            "color214",
+            "color142",
+            "underline color109",
+        ],
+    ),
+    (
+        "solarized-dark",
+        [
+            "bold color254 on color235",
+            "color240",
+            "color136",
+            "color37",
+            "underline color33",
+        ],
+    ),
+    (
+        "solarized-light",
+        [
+            "bold color235 on color254",
+            "color245",
+            "color136",
+            "color33",
+            "underline color37",
+        ],
+    ),
+    (
+        "catppuccin",
+        [
+            "bold color189 on color237",
+            "color243",
+            "color216",
+            "color183",
+            "underline color117",
+        ],
+    ),
+    (
+        "monochrome",
+        [
+            "bold black on white",
+            "gray10",
+            "bold white",
+            "bold white",
+            "underline white",
+        ],
+    ),
+    (
+        HIGH_CONTRAST,
+        [
+            "bold black on color226",
+            "white",
+            "bold color226",
+            "bold white",
+            "bold underline color51",
+        ],
+    ),
+];
+
+/// The preset the `high_contrast` switch uses.
+pub const HIGH_CONTRAST: &str = "high-contrast";
+
+pub fn names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Sets the styles of the preset called `name` over whatever `styles` had for them. Returns
+/// whether there is such a preset.
+pub fn apply(styles: &mut Styles, name: &str) -> bool {
+    let Some((_, preset)) = PRESETS.iter().find(|(preset, _)| *preset == name) else {
+        return false;
+    };
+    let home = styles.entry(Mode::Home).or_default();
+    for (key, style) in KEYS.iter().zip(preset) {
+        home.insert(key.to_string(), parse_style(style));
+    }
+    true
+}
+
+/// Saves the theme as its own config file, so the user's hand-written one is left alone.
+pub fn save(config_dir: &Path, name: &str) -> Result<()> {
+    #[derive(Serialize)]
+    struct File<'a> {
+        theme: &'a str,
+    }
+    crate::utils::write_json(&config_dir.join(FILE), &File { theme: name })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::style::{Color, Modifier, Style};
+
+    use super::*;
+
+    #[test]
+    fn test_apply_preset() {
+        let mut styles = Styles::default();
+        styles
+            .entry(Mode::Home)
+            .or_default()
+            .insert("tag".into(), Style::default().fg(Color::Red));
+        assert!(!apply(&mut styles, "nord"));
+        assert!(apply(&mut styles, "gruvbox"));
+        let home = &styles[&Mode::Home];
+        assert_eq!(home.len(), 6);
+        assert_eq!(
+            home["highlight"],
+            Style::default()
+                .fg(Color::Indexed(223))
+                .bg(Color::Indexed(237))
+                .add_modifier(Modifier::BOLD)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::todo::TodoItem;
+
+/// What one operation changed in the list, kept so that undoing it puts back only that: the
+/// todos it touched as they were before, and the order of the list if it moved todos around.
+/// Whatever else changed since, a sync say, is left as it is.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changes {
+    /// Where each todo the operation touched was and what it was, by id, or `None` for a todo it
+    /// added.
+    before: Vec<(usize, Uuid, Option<TodoItem>)>,
+    /// The ids in the order the list had, if the operation reordered it.
+    order: Option<Vec<Uuid>>,
+}
+
+impl Changes {
+    /// What an operation changed, going from `before` to `after`.
+    pub fn between(before: &[TodoItem], after: &[TodoItem]) -> Self {
+        let was: HashMap<Uuid, &TodoItem> = before.iter().map(|todo| (todo.id, todo)).collect();
+        let now: HashMap<Uuid, &TodoItem> = after.iter().map(|todo| (todo.id, todo)).collect();
+        let mut changed: Vec<(usize, Uuid, Option<TodoItem>)> = before
+            .iter()
+            .enumerate()
+            .filter(|(_, todo)| now.get(&todo.id) != Some(todo))
+            .map(|(i, todo)| (i, todo.id, Some(todo.clone())))
+            .collect();
+        changed.extend(
+            after
+                .iter()
+                .enumerate()
+                .filter(|(_, todo)| !was.contains_key(&todo.id))
+                .map(|(i, todo)| (i, todo.id, None)),
+        );
+        let kept = |todos: &[TodoItem], other: &HashMap<Uuid, &TodoItem>| -> Vec<Uuid> {
+            todos
+                .iter()
+                .map(|todo| todo.id)
+                .filter(|id| other.contains_key(id))
+                .collect()
+        };
+        let order = (kept(before, &now) != kept(after, &was))
+            .then(|| before.iter().map(|todo| todo.id).collect());
+        Self {
+            before: changed,
+            order,
+        }
+    }
+
+    /// Puts back the todos the operation touched in `todos`, leaving the others as they are now.
+    pub fn revert(self, todos: &mut Vec<TodoItem>) {
+        let mut removed = Vec::new();
+        for (index, id, before) in self.before {
+            match (todos.iter().position(|todo| todo.id == id), before) {
+                (Some(at), Some(before)) => todos[at] = before,
+                (Some(at), None) => {
+                    todos.remove(at);
+                }
+                (None, Some(before)) => removed.push((index, before)),
+                (None, None) => {}
+            }
+        }
+        for (index, todo) in removed {
+            todos.insert(index.min(todos.len()), todo);
+        }
+        if let Some(order) = self.order {
+            let rank: HashMap<Uuid, usize> = order.into_iter().zip(0..).collect();
+            // Todos the list didn't have then go after the rest
+            todos.sort_by_key(|todo| rank.get(&todo.id).copied().unwrap_or(usize::MAX));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn titles(todos: &[TodoItem]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_revert_only_what_changed() {
+        let before: Vec<TodoItem> = ["a", "b", "c"]
+            .map(|title| TodoItem::new(title.into()))
+            .to_vec();
+        let mut todos = before.clone();
+        todos[0].done = true;
+        todos.remove(1);
+        todos.push(TodoItem::new("d".into()));
+        let changes = Changes::between(&before, &todos);
+
+        // Changed by a sync after the operation
+        todos[1].title = "c from elsewhere".into();
+        changes.revert(&mut todos);
+        assert_eq!(titles(&todos), vec!["a", "b", "c from elsewhere"]);
+        assert!(!todos[0].done);
+
+        let before = todos.clone();
+        todos.reverse();
+        let changes = Changes::between(&before, &todos);
+        todos.push(TodoItem::new("e".into()));
+        changes.revert(&mut todos);
+        assert_eq!(titles(&todos), vec!["a", "b", "c from elsewhere", "e"]);
+    }
+}
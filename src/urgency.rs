@@ -0,0 +1,104 @@
+use chrono::{Local, NaiveDate};
+
+use crate::{
+    config::UrgencyConfig,
+    todo::{EventKind, TodoItem},
+};
+
+/// Age stops adding to the score after this many days.
+const MAX_AGE: f64 = 365.0;
+
+/// How close the due date is, from 0.2 two weeks or more out to 1.0 a week or more overdue.
+fn due_factor(due: NaiveDate, today: NaiveDate) -> f64 {
+    let days_overdue = (today - due).num_days() as f64;
+    if days_overdue >= 7.0 {
+        1.0
+    } else if days_overdue >= -14.0 {
+        ((days_overdue + 14.0) * 0.8 / 21.0) + 0.2
+    } else {
+        0.2
+    }
+}
+
+/// More tags count for more, up to three.
+fn tags_factor(count: usize) -> f64 {
+    match count {
+        0 => 0.0,
+        1 => 0.8,
+        2 => 0.9,
+        _ => 1.0,
+    }
+}
+
+/// How pressing a todo is, Taskwarrior style: a sum of how close it is to due, whether it is
+/// flagged or starred, how long ago it was created and its tags, weighted by `weights`.
+/// Completed todos score nothing.
+pub fn score(todo: &TodoItem, today: NaiveDate, weights: &UrgencyConfig) -> f64 {
+    if todo.done {
+        return 0.0;
+    }
+    let age = age(todo, today) as f64;
+    let boosts: f64 = todo
+        .tags
+        .iter()
+        .filter_map(|tag| weights.tag_boosts.get(tag.as_str()))
+        .sum();
+    weights.due * todo.due.map_or(0.0, |due| due_factor(due, today))
+        + weights.priority * f64::from(u8::from(todo.flagged))
+        + weights.starred * f64::from(u8::from(todo.starred))
+        + weights.age * (age / MAX_AGE).clamp(0.0, 1.0)
+        + weights.tags * tags_factor(todo.tags.len())
+        + weights.project * f64::from(u8::from(todo.project.is_some()))
+        + boosts
+}
+
+/// How many days before `today` the todo was created, or last changed when that isn't known.
+pub fn age(todo: &TodoItem, today: NaiveDate) -> i64 {
+    let created = todo
+        .history
+        .iter()
+        .find(|event| event.kind == EventKind::Created)
+        .map_or(todo.updated, |event| event.at);
+    (today - created.with_timezone(&Local).date_naive()).num_days()
+}
+
+/// Sorts the most urgent todos first, keeping the order of those that score the same.
+pub fn sort(todos: &mut [TodoItem], today: NaiveDate, weights: &UrgencyConfig) {
+    todos.sort_by(|a, b| score(b, today, weights).total_cmp(&score(a, today, weights)));
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_score_and_sort() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut todos = vec![
+            TodoItem::parse("someday"),
+            TodoItem::parse("soon due:2024-05-08 #home"),
+            TodoItem::parse("late due:2024-04-01"),
+        ];
+        for todo in &mut todos {
+            todo.updated = today.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        }
+        let weights = UrgencyConfig::default();
+        assert_eq!(score(&todos[0], today, &weights), 0.0);
+        assert_eq!(score(&todos[2], today, &weights), 12.0);
+        let soon = 12.0 * (7.0 * 0.8 / 21.0 + 0.2) + 0.8;
+        assert!((score(&todos[1], today, &weights) - soon).abs() < 1e-9);
+
+        sort(&mut todos, today, &weights);
+        let titles: Vec<&str> = todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["late", "soon", "someday"]);
+
+        let weights: UrgencyConfig =
+            json5::from_str("{ due: 1, tag_boosts: { home: 20 } }").unwrap();
+        assert_eq!(weights.priority, 6.0);
+        sort(&mut todos, today, &weights);
+        let titles: Vec<&str> = todos.iter().map(|todo| todo.title.as_str()).collect();
+        assert_eq!(titles, vec!["soon", "late", "someday"]);
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use color_eyre::eyre::Result;
 use directories::ProjectDirs;
@@ -57,6 +60,7 @@ pub fn initialize_panic_handler() -> Result<()> {
         .into_hooks();
     eyre_hook.install()?;
     std::panic::set_hook(Box::new(move |panic_info| {
+        crate::recovery::save(&crate::todo::data_file());
         if let Ok(mut t) = crate::tui::Tui::new() {
             if let Err(r) = t.exit() {
                 error!("Unable to exit Terminal: {:?}", r);
@@ -187,6 +191,53 @@ macro_rules! trace_dbg {
     };
 }
 
+/// Reads a JSON state file, treating a missing file as the default value.
+pub fn read_json<T: for<'de> serde::Deserialize<'de> + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => Ok(serde_json::from_str(&raw)?),
+        Err(_) => Ok(T::default()),
+    }
+}
+
+pub fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+/// Writes `contents` to a new file in the temporary directory, named `rust-todos-*` with
+/// `suffix`, for handing to an editor. The file is created afresh and readable by the user alone,
+/// as others can see into the temporary directory. Whoever asked for it removes it.
+pub fn temp_file(suffix: &str, contents: &str) -> Result<PathBuf> {
+    let mut file = tempfile::Builder::new()
+        .prefix("rust-todos-")
+        .suffix(suffix)
+        .tempfile()?;
+    file.write_all(contents.as_bytes())?;
+    let (_, path) = file.keep()?;
+    Ok(path)
+}
+
+/// Opens `path` in the user's `$VISUAL` or `$EDITOR`, falling back to `vi`, and waits for it to
+/// close. The variable may carry arguments, such as `code --wait`.
+pub fn run_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        color_eyre::eyre::bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
 pub fn version() -> String {
     let author = clap::crate_authors!();
 
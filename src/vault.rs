@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use uuid::Uuid;
+
+use crate::todo::TodoItem;
+
+/// A `- [ ] text` checkbox on one line of a note.
+struct Checkbox<'a> {
+    /// Byte offset of the character between the brackets.
+    mark: usize,
+    done: bool,
+    text: &'a str,
+}
+
+impl<'a> Checkbox<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let indent = line.len() - line.trim_start().len();
+        let rest = &line[indent..];
+        let rest = rest
+            .strip_prefix("- [")
+            .or_else(|| rest.strip_prefix("* ["))?;
+        let done = match rest.chars().next()? {
+            ' ' => false,
+            'x' | 'X' => true,
+            _ => return None,
+        };
+        let text = rest[1..].strip_prefix("] ")?.trim();
+        Some(Self {
+            mark: indent + 3,
+            done,
+            text,
+        })
+    }
+}
+
+/// Gives every checkbox in a note an id from its file and text, counting repeats of the same text
+/// so that they stay apart.
+fn checkbox_ids<'a>(source: &str, raw: &'a str) -> Vec<(usize, Checkbox<'a>, Uuid)> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    raw.lines()
+        .enumerate()
+        .filter_map(|(i, line)| Checkbox::parse(line).map(|checkbox| (i, checkbox)))
+        .map(|(i, checkbox)| {
+            let count = seen.entry(checkbox.text).or_default();
+            let name = format!("{source}:{count}:{}", checkbox.text);
+            *count += 1;
+            let id = Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes());
+            (i, checkbox, id)
+        })
+        .collect()
+}
+
+/// Reads the checkboxes of one note as todos, with `#tags` and the other quick-add tokens pulled
+/// out of the text.
+pub fn parse(source: &str, raw: &str, modified: DateTime<Utc>) -> Vec<TodoItem> {
+    checkbox_ids(source, raw)
+        .into_iter()
+        .map(|(_, checkbox, id)| {
+            let mut todo = TodoItem::parse(checkbox.text);
+            todo.id = id;
+            todo.updated = modified;
+            todo.done = checkbox.done;
+            todo.source = Some(source.to_string());
+            todo
+        })
+        .collect()
+}
+
+fn notes(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        // Skip `.obsidian`, `.trash` and the like
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            notes(&path, found)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Collects the checkboxes of every note under `dir`.
+pub fn scan(dir: &Path) -> Result<Vec<TodoItem>> {
+    let mut paths = Vec::new();
+    notes(dir, &mut paths)?;
+    paths.sort();
+
+    let mut todos = Vec::new();
+    for path in paths {
+        let source = path.strip_prefix(dir)?.to_string_lossy().into_owned();
+        let modified: DateTime<Utc> = fs::metadata(&path)?.modified()?.into();
+        todos.extend(parse(&source, &fs::read_to_string(&path)?, modified));
+    }
+    Ok(todos)
+}
+
+/// Ticks or clears the checkbox a todo was read from, leaving the rest of the note untouched.
+/// Returns false if the checkbox is no longer there.
+pub fn write_done(dir: &Path, todo: &TodoItem) -> Result<bool> {
+    let Some(source) = &todo.source else {
+        return Ok(false);
+    };
+    let path = dir.join(source);
+    let raw = fs::read_to_string(&path)?;
+    let Some((line, checkbox, _)) = checkbox_ids(source, &raw)
+        .into_iter()
+        .find(|(_, _, id)| *id == todo.id)
+    else {
+        return Ok(false);
+    };
+    if checkbox.done == todo.done {
+        return Ok(true);
+    }
+
+    let mark = if todo.done { "x" } else { " " };
+    let mut out: Vec<String> = raw.lines().map(str::to_string).collect();
+    out[line].replace_range(checkbox.mark..checkbox.mark + 1, mark);
+    let mut out = out.join("\n");
+    if raw.ends_with('\n') {
+        out.push('\n');
+    }
+    fs::write(path, out)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_completion_written_back() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("doit-vault-{}", Uuid::new_v4()));
+        fs::create_dir_all(dir.join(".obsidian"))?;
+        fs::write(dir.join(".obsidian/ignored.md"), "- [ ] hidden\n")?;
+        fs::write(
+            dir.join("home.md"),
+            "# Chores\n- [ ] fix sink #diy\n  * [x] buy washer\nnot a [ ] task\n",
+        )?;
+
+        let mut todos = scan(&dir)?;
+        let rendered: Vec<String> = todos.iter().map(|todo| todo.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec!["fix sink #diy [home.md]", "buy washer [home.md]"]
+        );
+        assert!(todos[1].done);
+
+        todos[0].done = true;
+        assert!(write_done(&dir, &todos[0])?);
+        assert_eq!(
+            fs::read_to_string(dir.join("home.md"))?,
+            "# Chores\n- [x] fix sink #diy\n  * [x] buy washer\nnot a [ ] task\n"
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}